@@ -1,6 +1,16 @@
 from ._metaedit import MetadataEditor as _MetadataEditor
+from ._metaedit import detect_target as _detect_target
+from ._metaedit import validate_icon as _validate_icon
+from ._metaedit import icon_dominant_color as _icon_dominant_color
+from ._metaedit import set_quiet as _set_quiet
+from ._metaedit import parse_version, set_signed_policy
+from ._metaedit import set_defaults as _set_defaults, clear_defaults
+from ._metaedit import set_temp_dir as _set_temp_dir
+from ._metaedit import repair as _repair
+from ._metaedit import inspect_dir as _inspect_dir
+from ._metaedit import MetaEditError, PEParseError, IconError
 from pathlib import Path
-from typing import Optional, Union
+from typing import BinaryIO, Iterable, Iterator, Optional, Tuple, Union
 
 class MetadataEditor:
     """
@@ -9,22 +19,552 @@ class MetadataEditor:
     def __init__(self, file_path: Union[str, Path]):
         self.file_path = str(Path(file_path).absolute())
         self._editor = _MetadataEditor(self.file_path)
+        self.result = None
 
-    def set_icon(self, icon_path: Union[str, Path]):
-        """Sets the executable icon (.ico)."""
-        self._editor.set_icon(str(Path(icon_path).absolute()))
+    def with_file(self, file_path: Union[str, Path]) -> "MetadataEditor":
+        """
+        Returns a new editor with this one's configuration and `file_path` as its target,
+        e.g. base.with_file("a.exe").apply(); base.with_file("b.exe").apply().
+        """
+        editor = MetadataEditor.__new__(MetadataEditor)
+        editor.file_path = str(Path(file_path).absolute())
+        editor._editor = self._editor.with_file(editor.file_path)
+        editor.result = None
+        return editor
+
+    def set_icon(self, icon: Union[str, Path, BinaryIO]):
+        """
+        Sets the executable icon from a path or a binary file-like object (anything with
+        `.read()`). Raises IconError if the file does not exist or isn't a valid image.
+        """
+        if hasattr(icon, "read"):
+            data = icon.read()
+            if not isinstance(data, (bytes, bytearray)):
+                raise IconError("icon file object must be opened in binary mode")
+            self._editor.set_icon_bytes(bytes(data))
+        else:
+            self._editor.set_icon(str(Path(icon).absolute()))
+        return self
+
+    def set_icon_from_dir(self, directory: Union[str, Path]):
+        """
+        Uses a pre-rendered icon set: PNGs named by size ('16.png' or '16x16.png'). Each
+        is embedded as-is for its size; sizes the set lacks are resized from the largest.
+        Raises IconError if a file's dimensions don't match its name or none are found.
+        """
+        self._editor.set_icon_from_dir(str(directory))
+        return self
+
+    def set_platform_icon(self, platform: str, icon: Union[str, Path]):
+        """
+        Sets the icon for one platform ('windows', 'macos' or 'linux'), e.g. an .ico for
+        Windows and an .icns for macOS. Platforms without one fall back to set_icon().
+        """
+        self._editor.set_platform_icon(platform, str(Path(icon).absolute()))
+        return self
+
+    def set_use_default_icon(self, enabled: bool = True):
+        """Falls back to a generic placeholder icon when no icon is set. Off by default."""
+        self._editor.set_use_default_icon(enabled)
         return self
 
     def set_version(self, version: str):
-        """Sets both File and Product version (e.g., '1.2.3.4')."""
+        """
+        Sets both File and Product version (e.g., '1.2.3.4'). A FileVersion or
+        ProductVersion string set with set_string() takes precedence for that string,
+        whichever is called first; the numeric version fields still use this value.
+        """
         self._editor.set_version(version)
         return self
 
+    def bump_version(self, part: str = "revision") -> str:
+        """
+        Reads the target's current version, increments 'major', 'minor', 'build' or
+        'revision', sets it for the next apply() and returns the new version string.
+        """
+        return self._editor.bump_version(part)
+
+    def set_copyright(self, owner: str, year: Optional[int] = None):
+        """
+        Sets LegalCopyright (and NSHumanReadableCopyright on macOS) to '© <year> <owner>'.
+        The year defaults to the current one, or SOURCE_DATE_EPOCH's when that is set.
+        """
+        self._editor.set_copyright(owner, year)
+        return self
+
+    def set_build_id(self, build_id: Optional[str] = None):
+        """
+        Stamps a build identifier (e.g. a git SHA): BuildID on Windows and macOS, X-BuildID
+        on Linux. Without an argument a random UUID is used; see `build_id`.
+        """
+        self._editor.set_build_id(build_id)
+        return self
+
+    @property
+    def build_id(self) -> Optional[str]:
+        """The build id set with set_build_id, if any."""
+        return self._editor.get_build_id()
+
+    def set_product_version(self, version: str):
+        """Sets only the Product version, e.g. a suite version that differs from the FileVersion."""
+        self._editor.set_product_version(version)
+        return self
+
     def set_string(self, key: str, value: str):
-        """Sets a version string (e.g., 'CompanyName', 'FileDescription')."""
+        """
+        Sets a version string (e.g., 'CompanyName', 'FileDescription'). 'FileVersion'
+        and 'ProductVersion' override the strings set_version() would write.
+        """
         self._editor.set_string(key, value)
         return self
 
+    def set_string_from_file(self, key: str, path: Union[str, Path]):
+        """
+        Sets a version string to a UTF-8 text file's contents (minus one trailing newline),
+        e.g. a long LegalCopyright notice. Warns if the text spans several lines.
+        """
+        self._editor.set_string_from_file(key, str(path))
+        return self
+
+    def set_locale(self, locale: str):
+        """
+        Sets the metadata language (e.g. 'de-DE') for all platforms: the Windows
+        VERSIONINFO string table, Linux `Name[de_DE]` keys and macOS CFBundleDevelopmentRegion.
+        """
+        self._editor.set_locale(locale)
+        return self
+
+    def set_string_table_key(self, key: str):
+        """
+        Windows: writes version strings to the table with this key, 8 hex digits of
+        language id and codepage (e.g. '040704B0'). Raises MetaEditError otherwise.
+        """
+        self._editor.set_string_table_key(key)
+        return self
+
+    def set_icon_bit_depth(self, bit_depth: int):
+        """
+        Sets the bit depth of the small BMP frames in generated ICOs: 32 (default),
+        8 (256 colors) or 4 (16 colors) for legacy Windows targets. Every BMP frame also
+        carries its 1-bit AND mask, which Windows needs wherever alpha can't be used
+        (cursors, DI_MASK drawing, remote sessions), so there is no option to omit it.
+        """
+        self._editor.set_icon_bit_depth(bit_depth)
+        return self
+
+    def set_ico_sizes(self, sizes: list):
+        """
+        Windows only: frame sizes (1-256) of generated ICOs, replacing the quality
+        preset's list. 32 and 48 are always added (with a warning) unless
+        set_shell_icon_sizes(False).
+        """
+        self._editor.set_ico_sizes(list(sizes))
+        return self
+
+    def set_shell_icon_sizes(self, enabled: bool = True):
+        """
+        Windows only: keeps the 32 and 48 px frames Explorer and the taskbar rely on in
+        every generated ICO, even for small sources. On by default.
+        """
+        self._editor.set_shell_icon_sizes(enabled)
+        return self
+
+    def set_png_compression(self, level: str):
+        """Sets PNG compression for the large ICO frames: 'fast', 'default' or 'best'."""
+        self._editor.set_png_compression(level)
+        return self
+
+    def set_icon_quality(self, preset: str):
+        """
+        One dial for icon generation on all platforms: 'fast' (skips the largest sizes,
+        quick resize and compression), 'balanced' (default) or 'high' (extra sizes, best
+        compression). set_png_compression still overrides the compression level.
+        """
+        self._editor.set_icon_quality(preset)
+        return self
+
+    def set_icon_background(self, color: Optional[Tuple[int, int, int, int]] = None):
+        """
+        Fills the padding around non-square icons with an RGBA color, e.g. (255, 255, 255, 255).
+        None keeps the padding transparent.
+        """
+        self._editor.set_icon_background(color)
+        return self
+
+    def set_max_icon_dimension(self, max_dimension: int):
+        """
+        Rejects icon sources wider or taller than `max_dimension` (default 16384) with
+        IconError, before their pixels are decoded.
+        """
+        self._editor.set_max_icon_dimension(max_dimension)
+        return self
+
+    def set_install_icon_theme(self, enabled: bool = True):
+        """
+        Linux only: installs the icon as PNGs into ~/.local/share/icons/hicolor and
+        writes `Icon=<name>` instead of the raw icon path.
+        """
+        self._editor.set_install_icon_theme(enabled)
+        return self
+
+    def set_desktop_filename(self, name: str):
+        """Linux only: names the .desktop file (must end in '.desktop')."""
+        self._editor.set_desktop_filename(name)
+        return self
+
+    def set_output_dir(self, output_dir: Optional[Union[str, Path]] = None):
+        """Linux only: writes the .desktop file into `output_dir`, creating it if needed."""
+        self._editor.set_output_dir(str(output_dir) if output_dir is not None else None)
+        return self
+
+    def set_desktop_bool(self, key: str, value: bool = True):
+        """
+        Linux only: writes a boolean desktop entry key as 'true'/'false'. Accepts
+        DBusActivatable, SingleMainWindow, PrefersNonDefaultGPU, Terminal, NoDisplay and
+        StartupNotify; raises MetaEditError for other keys and TypeError for non-bools.
+        """
+        self._editor.set_desktop_bool(key, value)
+        return self
+
+    def set_hidden(self, hidden: bool = True):
+        """Linux only: writes Hidden=true to the .desktop file."""
+        self._editor.set_hidden(hidden)
+        return self
+
+    def set_only_show_in(self, desktops: list):
+        """Linux only: shows the launcher only in these desktops, e.g. ['GNOME', 'XFCE']."""
+        self._editor.set_only_show_in(list(desktops))
+        return self
+
+    def set_not_show_in(self, desktops: list):
+        """Linux only: hides the launcher in these desktops, e.g. ['KDE']."""
+        self._editor.set_not_show_in(list(desktops))
+        return self
+
+    def set_update_electron_package(self, enabled: bool = True):
+        """
+        Electron apps: also writes the version into resources/app/package.json next to the
+        binary. A packed app.asar can't be edited and only triggers a warning.
+        """
+        self._editor.set_update_electron_package(enabled)
+        return self
+
+    def set_verify_after_write(self, enabled: bool = True):
+        """
+        Re-reads the output after each apply() and raises MetaEditError if the written
+        version strings, plist keys or desktop fields don't match. Off by default;
+        recommended in CI.
+        """
+        self._editor.set_verify_after_write(enabled)
+        return self
+
+    def set_executable(self, enabled: bool = True):
+        """Linux only: makes the binary executable (chmod +x). The macOS bundle copy always is."""
+        self._editor.set_executable(enabled)
+        return self
+
+    def set_appdir(self, appdir: Optional[str] = None):
+        """
+        Linux only: lays the icon and .desktop file out in an AppDir, including the
+        256px `.DirIcon` shown for the built AppImage. Pass None to turn it off.
+        """
+        self._editor.set_appdir(appdir)
+        return self
+
+    def set_file_type(self, file_type: str):
+        """Sets the VERSIONINFO file type: 'app', 'dll', 'driver' or 'static_lib'."""
+        self._editor.set_file_type(file_type)
+        return self
+
+    def set_binary_placement(self, placement: str):
+        """
+        macOS only: 'copy' (default), 'move' or 'hardlink' the binary into the .app,
+        avoiding a second copy of large executables.
+        """
+        self._editor.set_binary_placement(placement)
+        return self
+
+    def set_create_shortcut(self, enabled: bool = True, path: Optional[Union[str, Path]] = None):
+        """
+        Windows only: apply() also writes a .lnk shortcut to the binary, using its icon and
+        FileDescription (or ProductName) as the comment. It goes next to the binary as
+        '<name>.lnk' unless path is given.
+        """
+        self._editor.set_create_shortcut(enabled, str(path) if path is not None else None)
+        return self
+
+    def set_bundle_name(self, name: str):
+        """
+        macOS only: names the .app folder (e.g. 'My App' -> My App.app) independently of
+        the binary's file name; CFBundleExecutable still points at the binary.
+        """
+        self._editor.set_bundle_name(name)
+        return self
+
+    def set_icon_name(self, name: str):
+        """macOS only: names the bundle icon (e.g. 'AppIcon' -> AppIcon.icns) and CFBundleIconFile."""
+        self._editor.set_icon_name(name)
+        return self
+
+    def set_development_region(self, region: str):
+        """macOS only: sets CFBundleDevelopmentRegion (default 'en', or the locale if set)."""
+        self._editor.set_development_region(region)
+        return self
+
+    def set_info_dictionary_version(self, version: str):
+        """
+        macOS only: overrides CFBundleInfoDictionaryVersion. New bundles get "6.0";
+        edit_bundle() only writes it when set here.
+        """
+        self._editor.set_info_dictionary_version(version)
+        return self
+
+    def set_category(self, category: str):
+        """
+        macOS only: sets LSApplicationCategoryType, e.g. 'public.app-category.developer-tools'
+        or just 'developer-tools'. Raises MetaEditError for anything outside Apple's list.
+        """
+        self._editor.set_category(category)
+        return self
+
+    def set_localized_name(self, region: str, name: str, display_name: Optional[str] = None):
+        """
+        macOS only: writes `<region>.lproj/InfoPlist.strings` with a localized CFBundleName
+        and CFBundleDisplayName (defaults to `name`).
+        """
+        self._editor.set_localized_name(region, name, display_name)
+        return self
+
+    def set_dmg_staging(self, staging_dir: Optional[Union[str, Path]] = None):
+        """
+        macOS only: copies the built .app into `staging_dir` next to an Applications
+        symlink, ready for `hdiutil create -srcfolder`. Pass None to turn it off.
+        """
+        self._editor.set_dmg_staging(str(staging_dir) if staging_dir is not None else None)
+        return self
+
+    def set_plist_bool(self, key: str, value: bool):
+        """macOS only: sets an Info.plist boolean, e.g. set_plist_bool('LSUIElement', True)."""
+        self._editor.set_plist_bool(key, value)
+        return self
+
+    def set_plist_int(self, key: str, value: int):
+        """macOS only: sets an Info.plist integer."""
+        self._editor.set_plist_int(key, value)
+        return self
+
+    def set_plist_array(self, key: str, values: list):
+        """macOS only: sets an Info.plist array of strings."""
+        self._editor.set_plist_array(key, list(values))
+        return self
+
+    def set_architecture_priority(self, architectures: list):
+        """
+        macOS only: sets LSArchitecturePriority, e.g. ['arm64', 'x86_64'].
+        Raises MetaEditError for anything other than arm64 and x86_64.
+        """
+        self._editor.set_architecture_priority(list(architectures))
+        return self
+
+    def set_requires_native_execution(self, enabled: bool = True):
+        """macOS only: sets LSRequiresNativeExecution, which keeps the app out of Rosetta."""
+        self._editor.set_requires_native_execution(enabled)
+        return self
+
+    def set_bitmap(self, id: int, image_path: Union[str, Path]):
+        """
+        Windows only: replaces (or adds) the RT_BITMAP resource `id`, e.g. an about-box or
+        splash image, with `image_path` encoded as a DIB.
+        """
+        self._editor.set_bitmap(id, str(Path(image_path).absolute()))
+        return self
+
+    def set_dpi_awareness(self, mode: str):
+        """
+        Windows only: sets the manifest DPI awareness to 'unaware', 'system',
+        'per_monitor' or 'per_monitor_v2'. A manifest is created if the exe has none.
+        """
+        self._editor.set_dpi_awareness(mode)
+        return self
+
+    def get_string(self, key: str, lang: Optional[str] = None) -> Optional[str]:
+        """
+        Reads a version string (Windows only). `lang` picks the string table, e.g. '0x0409',
+        '040704b0' or 'de-DE'; defaults to the first table. Returns None if missing.
+        """
+        return self._editor.get_string(key, lang)
+
+    def get_version_info(self, lang: Optional[str] = None) -> Optional[dict]:
+        """Reads a whole version string table (Windows only), selected like get_string."""
+        return self._editor.get_version_info(lang)
+
+    def get_string_table(self, block_id: int) -> Optional[list]:
+        """
+        Windows only: returns the 16 strings of RT_STRING block `block_id` (string id
+        = (block_id - 1) * 16 + index), or None if the block doesn't exist.
+        """
+        return self._editor.get_string_table(block_id)
+
+    def set_string_entry(self, block_id: int, index: int, value: str):
+        """Windows only: replaces string `index` (0-15) of RT_STRING block `block_id`."""
+        self._editor.set_string_entry(block_id, index, value)
+        return self
+
+    def get_fixed_file_info(self) -> dict:
+        """Reads the numeric VS_FIXEDFILEINFO fields (Windows only)."""
+        return self._editor.get_fixed_file_info()
+
+    def list_version_languages(self) -> list:
+        """
+        Windows only: returns the lang/codepage keys of the version string tables, e.g.
+        ['040904b0', '040704b0']. Empty if the file has no version resource.
+        """
+        return self._editor.list_version_languages()
+
+    def estimate_icon_size(self, icon_path: Union[str, Path]) -> int:
+        """
+        Windows only: builds the ICO that apply() would embed for icon_path, using this
+        editor's sizes, quality and PNG compression, and returns its size in bytes without
+        writing anything. Useful for deciding whether to trim the size list.
+        """
+        return self._editor.estimate_icon_size(str(icon_path))
+
+    def get_manifest_info(self) -> dict:
+        """
+        Windows only: reads the embedded application manifest and returns its assembly
+        identity ('name', 'version', 'processor_architecture', 'type'), 'execution_level'
+        and 'ui_access', and 'dependencies' (e.g. 'Microsoft.Windows.Common-Controls
+        6.0.0.0'). Empty when the file has no manifest.
+        """
+        return self._editor.get_manifest_info()
+
+    def version_consistency(self) -> dict:
+        """
+        Windows only: reports where the FileVersion/ProductVersion strings disagree with
+        the numeric FixedFileInfo versions, e.g. {'file_version': "FileVersion string is
+        '2.0' but FixedFileInfo says 1.0.0.0"}. Empty when consistent.
+        """
+        return self._editor.version_consistency()
+
+    def list_resources(self) -> list:
+        """Windows only: returns (type, id, size) for every resource, e.g. ('RT_ICON', 1, 1128)."""
+        return self._editor.list_resources()
+
+    def plan_resources(self):
+        """
+        Windows only: dry run of apply(). Returns a ResourceDiff listing the `added`,
+        `removed` and `modified` resources (e.g. 'RT_ICON/1/1033') and the changed version
+        `strings` as (key, old, new). The file is not modified.
+        """
+        return self._editor.plan_resources()
+
+    def export_rc(self, output_path: Union[str, Path]):
+        """
+        Writes a Windows .rc script with the configured version info and icon instead of
+        patching the binary, for compiling resources in at link time.
+        """
+        self._editor.export_rc(str(output_path))
+        return self
+
+    def export_wix_fragment(self, output_path: Union[str, Path]):
+        """
+        Writes a WiX include (.wxi) defining ProductName, ProductVersion and Manufacturer
+        from the configured metadata. Use it with <?include?> and $(var.ProductName).
+        """
+        self._editor.export_wix_fragment(str(output_path))
+        return self
+
+    def extract_icon_frames(self, output_dir: Union[str, Path]) -> list:
+        """
+        Windows only: writes each frame of the main icon to `<output_dir>/icon_<w>x<h>.png`
+        and returns the written paths. BMP frames are converted to PNG.
+        """
+        return self._editor.extract_icon_frames(str(output_dir))
+
+    def extract_icon_bytes(self, size: Optional[int] = None) -> bytes:
+        """
+        Windows only: returns the `size`x`size` frame of the main icon (or the largest
+        frame) as PNG bytes, e.g. for PIL.Image.open(io.BytesIO(data)).
+        """
+        return self._editor.extract_icon_bytes(size)
+
+    def extract_icon(self, output_path: Union[str, Path]):
+        """
+        Windows only: exports the main icon, encoded by extension. '.ico' keeps every frame;
+        '.png' and '.bmp' write the largest frame (deepest color depth on ties).
+        Other extensions raise IconError.
+        """
+        self._editor.extract_icon(str(output_path))
+        return self
+
+    def get_characteristics(self) -> list:
+        """Returns the COFF Characteristics flags set in the PE, e.g. ['EXECUTABLE_IMAGE', 'DLL']."""
+        return self._editor.get_characteristics()
+
+    def inspect(self) -> dict:
+        """
+        Returns a summary: 'format' ('windows', 'macos', 'linux' or 'unknown')
+        and, for PE files, 'signed', 'checksum_valid' and 'has_overlay' as 'true'/'false'.
+        """
+        return self._editor.inspect()
+
+    def get_optional_header(self) -> dict:
+        """
+        Returns key PE Optional Header fields as ints, e.g. 'size_of_image',
+        'size_of_headers', 'subsystem' and 'number_of_rva_and_sizes'.
+        """
+        return self._editor.get_optional_header()
+
+    def is_checksum_valid(self) -> bool:
+        """
+        Returns whether the PE's stored CheckSum matches its contents; False usually means
+        a tool edited the file without updating it. A zero (unset) checksum counts as valid.
+        """
+        return self._editor.is_checksum_valid()
+
+    def set_characteristic(self, flag: str, enabled: bool = True):
+        """
+        Windows only: sets or clears a COFF Characteristics flag on apply(), e.g.
+        set_characteristic('DLL'). Accepts names with or without the 'IMAGE_FILE_' prefix.
+        """
+        self._editor.set_characteristic(flag, enabled)
+        return self
+
+    def is_signed(self) -> bool:
+        """
+        Returns True if the PE carries an Authenticode signature.
+        Raises PEParseError if the PE has fewer than 5 data directories (no Security entry).
+        """
+        return self._editor.is_signed()
+
+    def remove_signature(self):
+        """
+        Removes an Authenticode signature in place: clears the Security directory entry and
+        drops the certificate table when it ends the file. Unsigned files are left untouched.
+        """
+        self._editor.remove_signature()
+        return self
+
+    def read_overlay(self) -> bytes:
+        """Returns the bytes appended after the PE image and its certificate table, if any."""
+        return self._editor.read_overlay()
+
+    def append_overlay(self, data: bytes):
+        """
+        Appends `data` to the end of the PE (after any existing overlay), e.g. a config
+        blob read at runtime. A signature is handled per set_signed_policy().
+        """
+        self._editor.append_overlay(bytes(data))
+        return self
+
+    def strip_overlay(self) -> int:
+        """
+        Removes data appended after the PE image (and its certificate table) in place,
+        returning the number of bytes removed. Raises MetaEditError if the overlay sits in
+        front of a certificate table that is still referenced; call remove_signature() first.
+        """
+        return self._editor.strip_overlay()
+
     def update(self, metadata: dict):
         """Updates multiple metadata fields from a dictionary."""
         for key, value in metadata.items():
@@ -36,14 +576,77 @@ class MetadataEditor:
                 self.set_string(key, value)
         return self
 
-    def apply(self):
-        """Saves changes to the file."""
-        self._editor.apply()
+    def validate_for(self, platform: str) -> list:
+        """
+        Checks this configuration against a target ('windows', 'macos' or 'linux') without
+        touching any file. Returns warnings for settings that target ignores (e.g. a plist
+        key on Windows) and for values it needs but lacks; empty when all is well.
+        """
+        return self._editor.validate_for(platform)
+
+    def apply_all(self, targets: list) -> dict:
+        """
+        Produces the artifact for each target ('windows', 'macos', 'linux') and returns
+        target -> output path. If the editor's path is a directory, each target uses the
+        matching binary in it. The .app bundle and .desktop launcher are generated on any
+        host; editing the PE needs a Windows build, so 'windows' raises MetaEditError elsewhere.
+        """
+        return self._editor.apply_all(list(targets))
+
+    def edit_bundle(self, bundle_path: Union[str, Path], only_if_changed: bool = False):
+        """
+        macOS only: updates an existing .app in place. The configured keys are merged into
+        its Info.plist and the icon goes into Contents/Resources; the executable is never
+        copied or moved and no directories are created. Raises MetaEditError if the bundle
+        has no Contents/Info.plist. The outcome is stored in `self.result`.
+        """
+        self.result = self._editor.edit_bundle(str(bundle_path), only_if_changed)
+        return self
+
+    def batch_apply_iter(self, paths: Iterable[Union[str, Path]], only_if_changed: bool = False,
+                         stop_on_error: bool = True) -> Iterator[tuple]:
+        """
+        Applies this editor's configuration to each path as the iterable produces it (a
+        generator, a file of paths, sys.stdin), yielding (path, ApplyResult) per file.
+        Paths are consumed lazily, so batches of any size run in constant memory. With
+        stop_on_error=False, a failing file yields (path, exception) and the batch goes on.
+        """
+        for path in paths:
+            try:
+                result = self.with_file(path).apply(only_if_changed).result
+            except (MetaEditError, OSError) as e:
+                if stop_on_error:
+                    raise
+                yield str(path), e
+                continue
+            yield str(path), result
+
+    def apply(self, only_if_changed: bool = False, report_path: Optional[Union[str, Path]] = None):
+        """
+        Saves changes to the file. With only_if_changed=True, files that already carry
+        the requested metadata are not rewritten. The outcome is stored in `self.result`;
+        `self.result.to_json()` gives it as JSON, and report_path also writes that JSON
+        to a file.
+        """
+        self.result = self._editor.apply(only_if_changed, str(report_path) if report_path is not None else None)
         return self
 
-def edit(file_path: Union[str, Path], metadata: Optional[dict] = None) -> MetadataEditor:
-    """Quick helper to start editing. Optionally apply a dictionary of metadata."""
+    def save_as(self, output_path: Union[str, Path], content_hash: bool = False) -> str:
+        """
+        Windows only: writes the edited file to output_path, leaving the original alone.
+        With content_hash=True, a short hash of the final bytes is added to the name
+        (e.g. app.1a2b3c4d.exe) for content-addressed caches. Returns the path written.
+        """
+        return self._editor.save_as(str(output_path), content_hash)
+
+def edit(file_path: Union[str, Path], metadata: Optional[dict] = None, use_default_icon: bool = False) -> MetadataEditor:
+    """
+    Quick helper to start editing. Optionally apply a dictionary of metadata.
+    With use_default_icon=True, a placeholder icon is used when none is given.
+    """
     editor = MetadataEditor(file_path)
+    if use_default_icon:
+        editor.set_use_default_icon()
     if metadata:
         editor.update(metadata)
     return editor
@@ -51,3 +654,62 @@ def edit(file_path: Union[str, Path], metadata: Optional[dict] = None) -> Metada
 def update(file_path: Union[str, Path], **metadata):
     """One-shot function to update metadata and apply immediately."""
     return edit(file_path, metadata).apply()
+
+def detect_target(file_path: Union[str, Path]) -> str:
+    """Returns the platform a binary targets: 'windows', 'macos', 'linux' or 'unknown'."""
+    return _detect_target(str(file_path))
+
+def validate_icon(path: Union[str, Path]) -> dict:
+    """
+    Checks an icon source before use. Returns its 'format', 'width', 'height' and
+    'has_alpha' (as strings), or raises IconError if it can't be decoded.
+    """
+    return _validate_icon(str(path))
+
+def icon_dominant_color(path: Union[str, Path]) -> Tuple[int, int, int]:
+    """
+    Returns the icon's dominant color as (r, g, b), ignoring transparent pixels, e.g. to
+    theme a splash screen or installer around it. Raises IconError if it can't be decoded.
+    """
+    return _icon_dominant_color(str(path))
+
+def set_defaults(metadata: dict):
+    """
+    Sets metadata (e.g. {'CompanyName': ..., 'LegalCopyright': ...}) that every editor
+    created afterwards starts with. Values given per file override the defaults.
+    Replaces any earlier defaults; clear_defaults() removes them.
+    """
+    _set_defaults(dict(metadata))
+
+def set_temp_dir(path: Optional[Union[str, Path]] = None):
+    """
+    Creates the intermediate file of each write in `path` instead of next to the target,
+    e.g. when the target directory is read-only. A directory on another filesystem can't
+    be renamed from, so the file is then copied next to the target first, with a warning.
+    None restores the default.
+    """
+    _set_temp_dir(str(path) if path is not None else None)
+
+def repair(file_path: Union[str, Path], backup_path: Optional[Union[str, Path]] = None,
+           dry_run: bool = False) -> list:
+    """
+    Recovers a PE left damaged by a failed edit and returns a description of each fix.
+    With backup_path, restores the file from that known-good copy. Otherwise clears a
+    certificate table entry that points at no certificate, cuts certificate bytes no entry
+    points at any more and recomputes a stale checksum. A file whose header can't be read
+    is restored from <file>.bak, which every in-place PE edit refreshes before writing.
+    dry_run reports the fixes without writing.
+    """
+    return _repair(str(file_path), str(backup_path) if backup_path is not None else None, dry_run)
+
+def inspect_dir(root: Union[str, Path], recursive: bool = True) -> dict:
+    """
+    Audits a release folder: runs MetadataEditor.inspect() on every supported binary
+    under root and returns {path: report}. Other files are skipped; unreadable files
+    and directories produce a warning instead of stopping the walk.
+    """
+    return _inspect_dir(str(root), recursive)
+
+def set_quiet(quiet: bool = True):
+    """Suppresses informational output. Setting METAEDIT_QUIET=1 does the same."""
+    _set_quiet(quiet)
@@ -1,17 +1,28 @@
+import sys
 import click
-from . import MetadataEditor
+from . import MetadataEditor, set_quiet
 
 @click.command()
-@click.argument('exe_path', type=click.Path(exists=True))
+@click.argument('exe_path', type=click.Path(exists=True), required=False)
 @click.option('--icon', help='Path to .ico file')
 @click.option('--version', help='Version string (e.g. 1.0.0.0)')
 @click.option('--company', help='Company Name')
 @click.option('--description', help='File Description')
 @click.option('--product', help='Product Name')
 @click.option('--copyright', help='Legal Copyright')
-def main(exe_path, icon, version, company, description, product, copyright):
+@click.option('--set', 'strings', multiple=True, metavar='KEY=VALUE',
+              help='Set a version string; a value of @path reads it from a UTF-8 file. Repeatable.')
+@click.option('--stdin', 'from_stdin', is_flag=True, help='Read the files to edit from stdin, one path per line')
+@click.option('--quiet', '-q', is_flag=True, help='Suppress informational output')
+def main(exe_path, icon, version, company, description, product, copyright, strings, from_stdin, quiet):
     """Simple CLI to edit PE metadata."""
-    editor = MetadataEditor(exe_path)
+    if bool(exe_path) == from_stdin:
+        raise click.UsageError("Give either EXE_PATH or --stdin")
+    if quiet:
+        set_quiet()
+
+    # With --stdin this is only a template; each path read gets a copy of it
+    editor = MetadataEditor(exe_path or "-")
     
     if icon:
         editor.set_icon(icon)
@@ -25,9 +36,29 @@ def main(exe_path, icon, version, company, description, product, copyright):
         editor.set_string("ProductName", product)
     if copyright:
         editor.set_string("LegalCopyright", copyright)
-        
+    for assignment in strings:
+        key, sep, value = assignment.partition("=")
+        if not sep or not key:
+            raise click.BadParameter(f"expected KEY=VALUE, got '{assignment}'", param_hint="--set")
+        if value.startswith("@"):
+            editor.set_string_from_file(key, value[1:])
+        else:
+            editor.set_string(key, value)
+
+    if from_stdin:
+        paths = (line.strip() for line in sys.stdin if line.strip())
+        failed = 0
+        for path, result in editor.batch_apply_iter(paths, stop_on_error=False):
+            if isinstance(result, Exception):
+                failed += 1
+                click.echo(f"{path}: error: {result}", err=True)
+            elif not quiet:
+                click.echo(f"{path}: {result.message}")
+        sys.exit(1 if failed else 0)
+
     editor.apply()
-    click.echo(f"Successfully updated metadata for {exe_path}")
+    if not quiet:
+        click.echo(f"Successfully updated metadata for {exe_path}")
 
 if __name__ == "__main__":
     main()
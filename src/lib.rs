@@ -2,388 +2,4740 @@ use pyo3::prelude::*;
 use std::path::Path;
 use std::collections::HashMap;
 use std::fs;
+use std::ffi::CString;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
 use pyo3::create_exception;
+use indexmap::IndexMap;
 
 #[cfg(target_os = "macos")]
 use plist::Value;
 
-// Define custom exceptions
-create_exception!(_metaedit, MetaEditError, pyo3::exceptions::PyException);
-create_exception!(_metaedit, PEParseError, MetaEditError);
-create_exception!(_metaedit, IconError, MetaEditError);
-// create_exception!(_metaedit, SigningError, MetaEditError);
+// Define custom exceptions. The module path must be the importable one so that pickle
+// can find the classes again, e.g. when a worker process raises one.
+create_exception!(metaedit._metaedit, MetaEditError, pyo3::exceptions::PyException);
+create_exception!(metaedit._metaedit, PEParseError, MetaEditError);
+create_exception!(metaedit._metaedit, IconError, MetaEditError);
+// create_exception!(metaedit._metaedit, SigningError, MetaEditError);
+
+// Windows LCIDs for the locales metaedit knows how to map. Language-only tags resolve
+// to the language's primary region.
+const LOCALE_LCIDS: &[(&str, u16)] = &[
+    ("ar-SA", 0x0401), ("cs-CZ", 0x0405), ("da-DK", 0x0406), ("de-DE", 0x0407),
+    ("de-AT", 0x0c07), ("de-CH", 0x0807), ("el-GR", 0x0408), ("en-US", 0x0409),
+    ("en-GB", 0x0809), ("en-AU", 0x0c09), ("en-CA", 0x1009), ("es-ES", 0x0c0a),
+    ("es-MX", 0x080a), ("fi-FI", 0x040b), ("fr-FR", 0x040c), ("fr-CA", 0x0c0c),
+    ("fr-CH", 0x100c), ("he-IL", 0x040d), ("hu-HU", 0x040e), ("it-IT", 0x0410),
+    ("ja-JP", 0x0411), ("ko-KR", 0x0412), ("nl-NL", 0x0413), ("nb-NO", 0x0414),
+    ("pl-PL", 0x0415), ("pt-BR", 0x0416), ("pt-PT", 0x0816), ("ro-RO", 0x0418),
+    ("ru-RU", 0x0419), ("sk-SK", 0x041b), ("sv-SE", 0x041d), ("th-TH", 0x041e),
+    ("tr-TR", 0x041f), ("uk-UA", 0x0422), ("vi-VN", 0x042a), ("zh-TW", 0x0404),
+    ("zh-CN", 0x0804),
+];
+
+// Resource type ids
+#[allow(dead_code)]
+const RT_ICON: u32 = 3;
+#[allow(dead_code)]
+const RT_GROUP_ICON: u32 = 14;
+#[allow(dead_code)]
+const RT_VERSION: u32 = 16;
+#[allow(dead_code)]
+const RT_STRING: u32 = 6;
+#[allow(dead_code)]
+const RT_BITMAP: u32 = 2;
+
+// VS_FIXEDFILEINFO dwFileType / dwFileSubtype values
+const VFT_APP: u32 = 0x1;
+const VFT_DLL: u32 = 0x2;
+const VFT_DRV: u32 = 0x3;
+const VFT_STATIC_LIB: u32 = 0x7;
+#[allow(dead_code)]
+const VFT2_DRV_SYSTEM: u32 = 0x7;
 
 #[pyclass]
 #[derive(Clone)]
 pub struct MetadataEditor {
     file_path: String,
     icon_path: Option<String>,
+    /// Icon supplied in memory (e.g. from a Python file object); takes the place of `icon_path`
+    icon_bytes: Option<Vec<u8>>,
+    /// Fall back to the built-in placeholder icon when none is set
+    use_default_icon: bool,
     version: Option<String>,
+    /// ProductVersion when it differs from `version`
+    product_version: Option<String>,
     strings: HashMap<String, String>,
+    file_type: Option<u32>,
+    install_icon_theme: bool,
+    /// Linux: root of an AppDir to lay the icon and .desktop file out in
+    appdir: Option<String>,
+    /// Linux: .desktop file name and directory, overriding `<stem>.desktop` next to the binary
+    desktop_filename: Option<String>,
+    output_dir: Option<String>,
+    /// Linux: `Hidden=` and the `OnlyShowIn=` / `NotShowIn=` desktop environment lists
+    hidden: bool,
+    only_show_in: Vec<String>,
+    not_show_in: Vec<String>,
+    /// Linux: boolean desktop entry keys from `DESKTOP_BOOL_KEYS`, e.g. DBusActivatable
+    desktop_bools: IndexMap<String, bool>,
+    /// Linux: chmod +x the referenced binary
+    make_executable: bool,
+    locale: Option<String>,
+    /// Windows: explicit VERSIONINFO string table key, overriding the one derived from `locale`
+    table_key: Option<String>,
+    icon_bit_depth: u16,
+    /// Icon per target ("windows", "macos", "linux"), preferred over `icon_path` / `icon_bytes`
+    platform_icons: IndexMap<String, String>,
+    /// Pre-rendered frames by pixel size (from `set_icon_from_dir`), used instead of resizing
+    icon_frames: IndexMap<u32, String>,
+    /// Windows: custom ICO frame sizes, replacing the quality preset's list
+    ico_sizes: Option<Vec<u32>>,
+    /// Windows: always include the 32 and 48 px frames Explorer and the taskbar use
+    shell_icon_sizes: bool,
+    png_compression: Option<CompressionType>,
+    /// RT_BITMAP replacements: resource id -> image path
+    bitmaps: IndexMap<u16, String>,
+    /// Build identifier: `BuildID` on Windows and macOS, `X-BuildID` on Linux
+    build_id: Option<String>,
+    /// COFF File Header Characteristics bits to set (true) or clear (false)
+    characteristics: IndexMap<u16, bool>,
+    /// Also write the version into an unpacked Electron app's `resources/app/package.json`
+    update_electron_package: bool,
+    /// Re-read the output after apply() and fail if the written fields don't match
+    verify_after_write: bool,
+    /// Icon generation preset: "fast", "balanced" (default) or "high"
+    icon_quality: String,
+    icon_background: Option<(u8, u8, u8, u8)>,
+    /// Largest width/height an icon source may declare before it is decoded
+    max_icon_dimension: u32,
+    /// RT_STRING edits keyed by (block id, index within the block)
+    string_entries: IndexMap<(u32, u8), String>,
+    /// Manifest DPI awareness mode, one of `DPI_AWARENESS_MODES`
+    dpi_awareness: Option<String>,
+    /// macOS: how the binary gets into the bundle: "copy" (default), "move" or "hardlink"
+    binary_placement: String,
+    /// Windows: also write a `.lnk` shortcut to the binary, at `shortcut_path` or `<stem>.lnk`
+    create_shortcut: bool,
+    shortcut_path: Option<String>,
+    /// macOS: `.app` folder name (without the extension), instead of the binary's stem
+    bundle_name: Option<String>,
+    /// macOS: base name of the bundle icon in Resources (default "app")
+    icon_name: Option<String>,
+    /// macOS: folder to stage the bundle in for `hdiutil`, next to an /Applications link
+    dmg_staging: Option<String>,
+    /// macOS: non-string Info.plist keys, written after the standard ones
+    plist_values: IndexMap<String, PlistEntry>,
+    /// macOS: CFBundleDevelopmentRegion, overriding the locale (default "en")
+    development_region: Option<String>,
+    info_dictionary_version: Option<String>,
+    /// macOS: `LSApplicationCategoryType`, one of `APP_CATEGORIES`
+    app_category: Option<String>,
+    /// macOS: region -> (CFBundleName, CFBundleDisplayName) for `<region>.lproj/InfoPlist.strings`
+    localized_names: IndexMap<String, (String, Option<String>)>,
+}
+
+/// A typed Info.plist value. Kept independent of the `plist` crate, which is macOS-only,
+/// so bundles can be generated on any host.
+#[derive(Clone)]
+enum PlistEntry {
+    String(String),
+    Bool(bool),
+    Int(i64),
+    Array(Vec<String>),
 }
 
 #[cfg(target_os = "windows")]
-use editpe::{Image, VersionStringTable};
-#[cfg(target_os = "windows")]
-use image::{ImageReader, imageops::FilterType, ExtendedColorType};
+use editpe::{Image, ResourceDirectory, ResourceEntry, ResourceEntryName, ResourceTable, VersionInfo, VersionStringTable};
+use image::{ImageReader, imageops::FilterType};
+use image::codecs::png::CompressionType;
+use image::{ExtendedColorType, ImageEncoder};
+use image::codecs::png::{PngEncoder, FilterType as PngFilterType};
 #[cfg(target_os = "windows")]
 use image::codecs::ico::{IcoEncoder, IcoFrame};
-#[cfg(target_os = "windows")]
 use std::io::Cursor;
 
+/// Outcome of `apply()`.
+#[pyclass]
+#[derive(Clone)]
+pub struct ApplyResult {
+    #[pyo3(get)]
+    changed: bool,
+    #[pyo3(get)]
+    message: String,
+    /// The output still carries its original signature, which no longer matches (policy "keep")
+    #[pyo3(get)]
+    signature_invalid: bool,
+    #[pyo3(get)]
+    file: String,
+    /// Detected input format: "windows", "macos", "linux" or "unknown"
+    #[pyo3(get)]
+    format: String,
+    #[pyo3(get)]
+    output: String,
+    /// What happened to an Authenticode signature: "none", "stripped" or "kept"
+    #[pyo3(get)]
+    signature: String,
+    /// (field, old value, new value) for each metadata field that changed, sorted by field
+    #[pyo3(get)]
+    changes: Vec<(String, Option<String>, String)>,
+}
+
+impl ApplyResult {
+    fn new(changed: bool) -> Self {
+        let message = if changed { "metadata updated" } else { "no change needed" };
+        ApplyResult {
+            changed,
+            message: message.to_string(),
+            signature_invalid: false,
+            file: String::new(),
+            format: "unknown".to_string(),
+            output: String::new(),
+            signature: "none".to_string(),
+            changes: Vec::new(),
+        }
+    }
+
+    fn with_invalid_signature(mut self) -> Self {
+        self.signature_invalid = true;
+        self.message.push_str("; original signature kept but no longer valid");
+        self
+    }
+}
+
+#[pymethods]
+impl ApplyResult {
+    fn __repr__(&self) -> String {
+        format!("ApplyResult(changed={}, message='{}')", if self.changed { "True" } else { "False" }, self.message)
+    }
+
+    /// The result as JSON with a fixed key order, so reports from successive builds diff cleanly.
+    pub fn to_json(&self) -> String {
+        let changes: Vec<String> = self.changes.iter().map(|(field, old, new)| {
+            format!(
+                "    {{\"field\": {}, \"old\": {}, \"new\": {}}}",
+                json_string(field),
+                old.as_deref().map_or("null".to_string(), json_string),
+                json_string(new),
+            )
+        }).collect();
+        let changes = if changes.is_empty() { "[]".to_string() } else { format!("[\n{}\n  ]", changes.join(",\n")) };
+        format!(
+            "{{\n  \"file\": {},\n  \"format\": {},\n  \"output\": {},\n  \"changed\": {},\n  \"signature\": {},\n  \"signature_invalid\": {},\n  \"changes\": {}\n}}\n",
+            json_string(&self.file),
+            json_string(&self.format),
+            json_string(&self.output),
+            self.changed,
+            json_string(&self.signature),
+            self.signature_invalid,
+            changes,
+        )
+    }
+}
+
+/// What `apply()` would change in a PE's resource tree, from `plan_resources()`.
+/// Resources are labelled "<type>/<id>/<language>", e.g. "RT_ICON/1/1033".
+#[cfg(target_os = "windows")]
+#[pyclass]
+#[derive(Clone)]
+pub struct ResourceDiff {
+    #[pyo3(get)]
+    added: Vec<String>,
+    #[pyo3(get)]
+    removed: Vec<String>,
+    #[pyo3(get)]
+    modified: Vec<String>,
+    /// (key, old value, new value) for each changed version string
+    #[pyo3(get)]
+    strings: Vec<(String, Option<String>, Option<String>)>,
+}
+
+#[cfg(target_os = "windows")]
+#[pymethods]
+impl ResourceDiff {
+    fn __repr__(&self) -> String {
+        format!(
+            "ResourceDiff(added={}, removed={}, modified={}, strings={})",
+            self.added.len(), self.removed.len(), self.modified.len(), self.strings.len()
+        )
+    }
+}
+
 #[pymethods]
 impl MetadataEditor {
+    /// Starts from the module-level defaults (see `set_defaults`); anything set on the
+    /// editor afterwards overrides them.
     #[new]
     pub fn new(file_path: String) -> Self {
-        MetadataEditor {
+        let mut editor = MetadataEditor {
             file_path,
             icon_path: None,
+            icon_bytes: None,
+            use_default_icon: false,
             version: None,
+            product_version: None,
             strings: HashMap::new(),
+            file_type: None,
+            install_icon_theme: false,
+            appdir: None,
+            desktop_filename: None,
+            output_dir: None,
+            hidden: false,
+            only_show_in: Vec::new(),
+            not_show_in: Vec::new(),
+            desktop_bools: IndexMap::new(),
+            make_executable: false,
+            locale: None,
+            table_key: None,
+            icon_bit_depth: 32,
+            platform_icons: IndexMap::new(),
+            icon_frames: IndexMap::new(),
+            ico_sizes: None,
+            shell_icon_sizes: true,
+            png_compression: None,
+            icon_quality: "balanced".to_string(),
+            update_electron_package: false,
+            verify_after_write: false,
+            characteristics: IndexMap::new(),
+            build_id: None,
+            bitmaps: IndexMap::new(),
+            icon_background: None,
+            max_icon_dimension: DEFAULT_MAX_ICON_DIMENSION,
+            string_entries: IndexMap::new(),
+            dpi_awareness: None,
+            binary_placement: "copy".to_string(),
+            create_shortcut: false,
+            shortcut_path: None,
+            bundle_name: None,
+            icon_name: None,
+            dmg_staging: None,
+            plist_values: IndexMap::new(),
+            development_region: None,
+            info_dictionary_version: None,
+            app_category: None,
+            localized_names: IndexMap::new(),
+        };
+        for (k, v) in DEFAULTS.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            editor.set_metadata(k.clone(), v.clone());
         }
+        editor
+    }
+
+    /// A copy of this editor's configuration aimed at another file, for stamping many
+    /// files with the same metadata.
+    pub fn with_file(&self, file_path: String) -> Self {
+        MetadataEditor { file_path, ..self.clone() }
     }
 
-    pub fn set_icon(mut sli: PyRefMut<'_, Self>, icon_path: String) -> PyRefMut<'_, Self> {
+    pub fn set_icon(mut sli: PyRefMut<'_, Self>, icon_path: String) -> PyResult<PyRefMut<'_, Self>> {
+        // Fail fast instead of surfacing a missing icon only at apply() time
+        if !Path::new(&icon_path).is_file() {
+            return Err(PyErr::new::<IconError, _>(format!("icon not found: {}", icon_path)));
+        }
         sli.icon_path = Some(icon_path);
+        sli.icon_bytes = None;
+        sli.icon_frames.clear();
+        Ok(sli)
+    }
+
+    /// Uses a pre-rendered icon set: a directory of PNGs named by size (`16.png` or
+    /// `16x16.png`). Each file is used as-is for its size in the ICO / icns / theme icons;
+    /// other sizes are resized from the largest file.
+    pub fn set_icon_from_dir(mut sli: PyRefMut<'_, Self>, dir: String) -> PyResult<PyRefMut<'_, Self>> {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| PyErr::new::<IconError, _>(format!("Failed to read icon directory {}: {}", dir, e)))?;
+        let mut frames = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| PyErr::new::<IconError, _>(format!("Failed to read icon directory {}: {}", dir, e)))?
+                .path();
+            if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png")) {
+                continue;
+            }
+            let Some(size) = path.file_stem().and_then(|stem| stem.to_str()).and_then(icon_file_size) else { continue };
+            let (width, height) = image::image_dimensions(&path)
+                .map_err(|e| PyErr::new::<IconError, _>(format!("Failed to read {}: {}", path.display(), e)))?;
+            if width != size || height != size {
+                return Err(PyErr::new::<IconError, _>(format!(
+                    "{} is {}x{}, but its name says {}x{}", path.display(), width, height, size, size
+                )));
+            }
+            frames.push((size, path.to_string_lossy().into_owned()));
+        }
+        frames.sort_unstable_by_key(|&(size, _)| std::cmp::Reverse(size));
+        let Some((_, largest)) = frames.first() else {
+            return Err(PyErr::new::<IconError, _>(format!("no size-named PNGs (e.g. 32.png) in {}", dir)));
+        };
+        sli.icon_path = Some(largest.clone());
+        sli.icon_bytes = None;
+        sli.icon_frames = frames.into_iter().collect();
+        Ok(sli)
+    }
+
+    /// Sets the icon for one target ("windows", "macos" or "linux"), e.g. an .ico for
+    /// Windows and an .icns for macOS in the same editor. Targets without one use `set_icon`.
+    pub fn set_platform_icon(mut sli: PyRefMut<'_, Self>, platform: String, icon_path: String) -> PyResult<PyRefMut<'_, Self>> {
+        let platform = platform.to_lowercase();
+        if !["windows", "macos", "linux"].contains(&platform.as_str()) {
+            return Err(PyErr::new::<MetaEditError, _>(format!(
+                "Unknown platform '{}' (expected windows, macos or linux)", platform
+            )));
+        }
+        if !Path::new(&icon_path).is_file() {
+            return Err(PyErr::new::<IconError, _>(format!("icon not found: {}", icon_path)));
+        }
+        sli.platform_icons.insert(platform, icon_path);
+        Ok(sli)
+    }
+
+    /// Sets the icon from raw image bytes instead of a path.
+    pub fn set_icon_bytes(mut sli: PyRefMut<'_, Self>, data: Vec<u8>) -> PyResult<PyRefMut<'_, Self>> {
+        if image::guess_format(&data).is_err() && !data.starts_with(b"icns") {
+            return Err(PyErr::new::<IconError, _>("icon data is not a recognised image format"));
+        }
+        sli.icon_bytes = Some(data);
+        sli.icon_path = None;
+        sli.icon_frames.clear();
+        Ok(sli)
+    }
+
+    /// Uses a generic placeholder icon when no icon is set, so launchers and bundles are never
+    /// blank during development. Off by default.
+    #[pyo3(signature = (enabled=true))]
+    pub fn set_use_default_icon(mut sli: PyRefMut<'_, Self>, enabled: bool) -> PyRefMut<'_, Self> {
+        sli.use_default_icon = enabled;
         sli
     }
 
+    /// Sets the file and product version: the numeric FixedFileInfo fields and the
+    /// FileVersion / ProductVersion strings. A string set with `set_string` under either key
+    /// wins over this, in any call order; the numeric fields still come from here.
     pub fn set_version(mut sli: PyRefMut<'_, Self>, version: String) -> PyRefMut<'_, Self> {
         sli.version = Some(version);
         sli
     }
 
+    /// Reads the current version from the target (FixedFileInfo, CFBundleVersion or the
+    /// .desktop `Version=`), increments `part` ("major", "minor", "build" or "revision"),
+    /// sets it as the version to write and returns it. Bumping major or minor zeroes the
+    /// components below it. A target without a version starts from 0.0.0.0.
+    pub fn bump_version(mut sli: PyRefMut<'_, Self>, part: String) -> PyResult<String> {
+        let index = match part.to_lowercase().as_str() {
+            "major" => 0,
+            "minor" => 1,
+            "build" => 2,
+            "revision" => 3,
+            _ => return Err(PyErr::new::<MetaEditError, _>(format!(
+                "Unknown version part '{}' (expected major, minor, build or revision)", part
+            ))),
+        };
+
+        let mut parts = match sli.current_version()? {
+            Some(current) => parse_version(current)?.0,
+            None => Vec::new(),
+        };
+        parts.resize(4, 0);
+        parts[index] = parts[index].checked_add(1)
+            .ok_or_else(|| PyErr::new::<MetaEditError, _>(format!("Cannot bump {}: version component overflow", part)))?;
+        if index < 2 {
+            parts[index + 1..].fill(0);
+        }
+
+        let version = parts.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(".");
+        sli.version = Some(version.clone());
+        Ok(version)
+    }
+
+    /// Sets ProductVersion independently of `set_version`, which otherwise writes both.
+    pub fn set_product_version(mut sli: PyRefMut<'_, Self>, version: String) -> PyRefMut<'_, Self> {
+        sli.product_version = Some(version);
+        sli
+    }
+
+    /// Sets a version string. "FileVersion" and "ProductVersion" override the strings
+    /// `set_version` would write, e.g. to show "1.2 (beta)" while FixedFileInfo says 1.2.0.0.
     pub fn set_string(mut sli: PyRefMut<'_, Self>, key: String, value: String) -> PyRefMut<'_, Self> {
         sli.strings.insert(key, value);
         sli
     }
 
-    #[cfg(target_os = "windows")]
-    pub fn remove_signature(&self) -> PyResult<()> {
-        let path = Path::new(&self.file_path);
-        if !path.exists() {
-            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
-                format!("File not found: {}", self.file_path),
-            ));
+    /// Sets a version string to the contents of a UTF-8 text file, e.g. a long copyright
+    /// notice. One trailing line break is dropped; any others are kept with a warning,
+    /// since many version info viewers show only the first line.
+    pub fn set_string_from_file(mut sli: PyRefMut<'_, Self>, key: String, path: String) -> PyResult<PyRefMut<'_, Self>> {
+        let data = fs::read(&path)
+            .map_err(|e| PyErr::new::<MetaEditError, _>(format!("Failed to read {}: {}", path, e)))?;
+        let text = String::from_utf8(data)
+            .map_err(|_| PyErr::new::<MetaEditError, _>(format!("{} is not valid UTF-8", path)))?;
+        let text = text.strip_suffix('\n').map(|t| t.strip_suffix('\r').unwrap_or(t)).unwrap_or(&text).to_string();
+        if text.contains('\n') {
+            warn(&format!("{} from {} spans several lines; version info viewers may show only the first", key, path))?;
         }
+        sli.strings.insert(key, text);
+        Ok(sli)
+    }
 
-        let mut data = fs::read(path)?;
-        if strip_pe_signature(&mut data) {
-            fs::write(path, data)?;
+    /// Sets LegalCopyright to "© <year> <owner>", which macOS bundles also get as
+    /// NSHumanReadableCopyright. `year` defaults to the current year (or that of
+    /// `SOURCE_DATE_EPOCH`, for reproducible builds).
+    #[pyo3(signature = (owner, year=None))]
+    pub fn set_copyright(mut sli: PyRefMut<'_, Self>, owner: String, year: Option<u32>) -> PyResult<PyRefMut<'_, Self>> {
+        let owner = owner.trim();
+        if owner.is_empty() {
+            return Err(PyErr::new::<MetaEditError, _>("Copyright owner must not be empty"));
         }
-        Ok(())
+        let year = match year {
+            Some(year) => year,
+            None => current_year()?,
+        };
+        sli.strings.insert("LegalCopyright".to_string(), format!("\u{a9} {} {}", year, owner));
+        Ok(sli)
     }
 
-    pub fn apply(&self) -> PyResult<()> {
-        let path = Path::new(&self.file_path);
-        if !path.exists() {
-            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
-                format!("File not found: {}", self.file_path),
-            ));
+    /// Tags the build with `id` (e.g. a git SHA) for support correlation: a `BuildID` version
+    /// string on Windows, a `BuildID` Info.plist key on macOS and `X-BuildID=` in the Linux
+    /// desktop file. Without `id` a random UUID is generated; read it back with `get_build_id`.
+    #[pyo3(signature = (id=None))]
+    pub fn set_build_id(mut sli: PyRefMut<'_, Self>, id: Option<String>) -> PyResult<PyRefMut<'_, Self>> {
+        let id = id.unwrap_or_else(random_uuid);
+        if id.is_empty() || id.chars().any(char::is_control) {
+            return Err(PyErr::new::<MetaEditError, _>(format!("Invalid build id '{}'", id.escape_debug())));
         }
+        sli.build_id = Some(id);
+        Ok(sli)
+    }
 
-        #[cfg(target_os = "windows")]
-        {
-            self.apply_windows()?;
-        }
+    pub fn get_build_id(&self) -> Option<String> {
+        self.build_id.clone()
+    }
 
-        #[cfg(target_os = "macos")]
-        {
-            self.apply_macos()?;
+    /// Sets VS_FIXEDFILEINFO.dwFileType: "app", "dll", "driver" or "static_lib".
+    pub fn set_file_type(mut sli: PyRefMut<'_, Self>, file_type: String) -> PyResult<PyRefMut<'_, Self>> {
+        let vft = match file_type.to_lowercase().as_str() {
+            "app" => VFT_APP,
+            "dll" => VFT_DLL,
+            "driver" => VFT_DRV,
+            "static_lib" => VFT_STATIC_LIB,
+            _ => return Err(PyErr::new::<MetaEditError, _>(format!(
+                "Unknown file type '{}' (expected app, dll, driver or static_lib)", file_type
+            ))),
+        };
+        sli.file_type = Some(vft);
+        Ok(sli)
+    }
+
+    /// Windows: sets the manifest's DPI awareness to "unaware", "system", "per_monitor" or
+    /// "per_monitor_v2", editing the existing RT_MANIFEST or synthesizing one if absent.
+    pub fn set_dpi_awareness(mut sli: PyRefMut<'_, Self>, mode: String) -> PyResult<PyRefMut<'_, Self>> {
+        let mode = mode.to_lowercase();
+        if !DPI_AWARENESS_MODES.contains(&mode.as_str()) {
+            return Err(PyErr::new::<MetaEditError, _>(format!(
+                "Unknown DPI awareness '{}' (expected unaware, system, per_monitor or per_monitor_v2)", mode
+            )));
         }
+        sli.dpi_awareness = Some(mode);
+        Ok(sli)
+    }
 
-        #[cfg(target_os = "linux")]
-        {
-            self.apply_linux()?;
+    /// macOS: how the loose binary is placed in the bundle. "copy" (default) leaves the
+    /// original alone, "move" renames it into the bundle and "hardlink" shares its data,
+    /// so large executables aren't stored twice. Both fall back to a copy across filesystems.
+    pub fn set_binary_placement(mut sli: PyRefMut<'_, Self>, placement: String) -> PyResult<PyRefMut<'_, Self>> {
+        let placement = placement.to_lowercase();
+        if !["copy", "move", "hardlink"].contains(&placement.as_str()) {
+            return Err(PyErr::new::<MetaEditError, _>(format!(
+                "Unknown binary placement '{}' (expected copy, move or hardlink)", placement
+            )));
         }
+        sli.binary_placement = placement;
+        Ok(sli)
+    }
 
-        Ok(())
+    /// Windows: on apply, also writes a `.lnk` shortcut to the binary with its icon and
+    /// FileDescription (else ProductName) as the comment; at `path`, or `<stem>.lnk` next
+    /// to the binary.
+    #[pyo3(signature = (enabled=true, path=None))]
+    pub fn set_create_shortcut(mut sli: PyRefMut<'_, Self>, enabled: bool, path: Option<String>) -> PyResult<PyRefMut<'_, Self>> {
+        if path.as_deref().is_some_and(|p| !p.to_lowercase().ends_with(".lnk")) {
+            return Err(PyErr::new::<MetaEditError, _>(format!("Shortcut path must end in .lnk: {}", path.unwrap_or_default())));
+        }
+        sli.create_shortcut = enabled;
+        sli.shortcut_path = path;
+        Ok(sli)
     }
-}
 
-impl MetadataEditor {
-    #[cfg(target_os = "windows")]
-    fn process_icon_windows(&self, icon_path: &str) -> PyResult<Vec<u8>> {
-        let path = Path::new(icon_path);
+    /// macOS: name of the `.app` folder (e.g. "My App" -> My App.app), instead of the binary's
+    /// file stem. CFBundleExecutable still names the binary. Ignored for an existing bundle.
+    pub fn set_bundle_name(mut sli: PyRefMut<'_, Self>, name: String) -> PyResult<PyRefMut<'_, Self>> {
+        let name = name.strip_suffix(".app").unwrap_or(&name).to_string();
+        if name.trim().is_empty() || name == "." || name == ".." || name.contains(['/', '\\', '\0']) {
+            return Err(PyErr::new::<MetaEditError, _>(format!("Invalid bundle name '{}'", name)));
+        }
+        sli.bundle_name = Some(name);
+        Ok(sli)
+    }
 
-        // Try decoding as image to see if we can generate a better ICO
-        if let Ok(reader) = ImageReader::open(path) {
-            if let Ok(img) = reader.decode() {
-                // Generate multi-size ICO
-                // Windows prefers 256x256 PNG, others using BMP format for crispness at low res.
-                // Standard sizes: 256, 128, 64, 48, 32, 24, 16.
-                let sizes = vec![256, 128, 64, 48, 32, 24, 16];
-                let mut frames = Vec::new();
-                
-                for size in sizes {
-                    let resized = img.resize(size, size, FilterType::Lanczos3);
-                    let width = resized.width();
-                    let height = resized.height();
-                    
-                    if size >= 128 {
-                        // Use PNG for large icons (Vista+ support)
-                        let buf = resized.clone().into_rgba8().into_vec();
-                        if let Ok(frame) = IcoFrame::as_png(&buf, width, height, ExtendedColorType::Rgba8) {
-                            frames.push(frame);
-                        }
-                    } else {
-                        // Use manually constructed BMP for smaller icons to avoid artifacting
-                        if let Ok(bmp_data) = create_ico_bmp_data(&resized, width, height) {
-                             if let Ok(frame) = IcoFrame::with_encoded(bmp_data, width, height, ExtendedColorType::Rgba8) {
-                                frames.push(frame);
-                            }
-                        }
-                    }
-                }
-                
-                if !frames.is_empty() {
-                    let mut out_buffer = Vec::new();
-                    let mut cursor = Cursor::new(&mut out_buffer);
-                    let encoder = IcoEncoder::new(&mut cursor);
-                    if encoder.encode_images(&frames).is_ok() {
-                        return Ok(out_buffer);
-                    }
-                }
-            }
+    /// macOS: name of the icon file in `Contents/Resources` (e.g. "AppIcon" -> AppIcon.icns),
+    /// also written to CFBundleIconFile. Defaults to "app".
+    pub fn set_icon_name(mut sli: PyRefMut<'_, Self>, name: String) -> PyResult<PyRefMut<'_, Self>> {
+        let name = name.strip_suffix(".icns").unwrap_or(&name).to_string();
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return Err(PyErr::new::<IconError, _>(format!("Invalid icon name '{}'", name)));
         }
+        sli.icon_name = Some(name);
+        Ok(sli)
+    }
 
-        // Fallback: read file directly
-        fs::read(path).map_err(|e| PyErr::new::<IconError, _>(format!("Failed to read icon file: {:?}", e)))
+    /// macOS: sets CFBundleDevelopmentRegion (e.g. "en", "pt-BR"). Takes precedence over
+    /// the locale; "en" is written when neither is set.
+    pub fn set_development_region(mut sli: PyRefMut<'_, Self>, region: String) -> PyResult<PyRefMut<'_, Self>> {
+        sli.development_region = Some(validate_lproj_region(&region)?);
+        Ok(sli)
     }
 
-    #[cfg(target_os = "windows")]
-    fn apply_windows(&self) -> PyResult<()> {
-        let data = fs::read(&self.file_path)?;
-        let mut image = Image::parse(&data).map_err(|e| PyErr::new::<PEParseError, _>(format!("PE Parse error: {:?}", e)))?;
-        
-        let mut resources = image.resource_directory().cloned().unwrap_or_default();
-        
-        println!("Rust (Windows): Patching PE Resources in {}", self.file_path);
-        
-        // 1. Set Icon
-        if let Some(icon_path) = &self.icon_path {
-            let icon_data = self.process_icon_windows(icon_path)?;
-            resources.set_main_icon(icon_data).map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to set icon: {:?}", e)))?;
+    /// macOS: overrides `CFBundleInfoDictionaryVersion`, which new bundles get as "6.0".
+    pub fn set_info_dictionary_version(mut sli: PyRefMut<'_, Self>, version: String) -> PyResult<PyRefMut<'_, Self>> {
+        if version.trim().is_empty() {
+            return Err(PyErr::new::<MetaEditError, _>("Info dictionary version must not be empty"));
         }
+        sli.info_dictionary_version = Some(version);
+        Ok(sli)
+    }
 
-        // 2. Set Version Strings
-        if !self.strings.is_empty() || self.version.is_some() {
-            let mut version_info = resources.get_version_info().map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to get version info: {:?}", e)))?.unwrap_or_default();
-            
-            if let Some(v) = &self.version {
-                // FixedFileInfo version is numeric (Major.Minor)
-                // We'll attempt to parse if possible, or leave as default for now as editpe uses VersionU32
-                // Most users care about the string entries which we handle below
-            }
-            
-            if let Some(table) = version_info.strings.get_mut(0) {
-                if let Some(v) = &self.version {
-                    table.strings.insert("FileVersion".to_string(), v.clone());
-                    table.strings.insert("ProductVersion".to_string(), v.clone());
-                }
-                for (k, v) in &self.strings {
-                    table.strings.insert(k.clone(), v.clone());
-                }
-            } else {
-                // If no table exists, create one (040904b0 is US English)
-                let mut strings = indexmap::IndexMap::default();
-                if let Some(v) = &self.version {
-                    strings.insert("FileVersion".to_string(), v.clone());
-                    strings.insert("ProductVersion".to_string(), v.clone());
-                }
-                for (k, v) in &self.strings {
-                    strings.insert(k.clone(), v.clone());
-                }
-                version_info.strings.push(VersionStringTable {
-                    key: "040904b0".to_string(),
-                    strings,
-                });
-            }
-            
-            resources.set_version_info(&version_info).map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to set version info: {:?}", e)))?;
+    /// macOS: `LSApplicationCategoryType`, which the App Store and Launchpad sort by, e.g.
+    /// "public.app-category.developer-tools". The prefix may be left off ("developer-tools").
+    pub fn set_category(mut sli: PyRefMut<'_, Self>, category: String) -> PyResult<PyRefMut<'_, Self>> {
+        let name = category.trim().to_lowercase();
+        let name = name.strip_prefix("public.app-category.").unwrap_or(&name);
+        if !APP_CATEGORIES.contains(&name) {
+            return Err(PyErr::new::<MetaEditError, _>(format!(
+                "Unknown app category '{}' (expected a public.app-category.* type such as developer-tools)", category
+            )));
         }
+        sli.app_category = Some(format!("public.app-category.{}", name));
+        Ok(sli)
+    }
 
-        // 3. Re-insert and Write back
-        image.set_resource_directory(resources).map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to set resources: {:?}", e)))?;
-        let mut final_data = image.data().to_vec();
-        
-        // Strip signature to prevent corruption errors (hash mismatch)
-        strip_pe_signature(&mut final_data);
+    /// macOS: adds `<region>.lproj/InfoPlist.strings` localizing CFBundleName and
+    /// CFBundleDisplayName (which defaults to `name`).
+    #[pyo3(signature = (region, name, display_name=None))]
+    pub fn set_localized_name(mut sli: PyRefMut<'_, Self>, region: String, name: String, display_name: Option<String>) -> PyResult<PyRefMut<'_, Self>> {
+        let region = validate_lproj_region(&region)?;
+        sli.localized_names.insert(region, (name, display_name));
+        Ok(sli)
+    }
 
-        fs::write(&self.file_path, final_data)?;
+    /// macOS: after building the bundle, copy it into `staging_dir` next to an `Applications`
+    /// symlink, ready for `hdiutil create -srcfolder`. `None` turns staging off.
+    #[pyo3(signature = (staging_dir=None))]
+    pub fn set_dmg_staging(mut sli: PyRefMut<'_, Self>, staging_dir: Option<String>) -> PyRefMut<'_, Self> {
+        sli.dmg_staging = staging_dir;
+        sli
+    }
 
-        Ok(())
+    /// Windows: replaces string `index` (0-15) of RT_STRING block `block_id`, creating the
+    /// block if needed. Applied with the other edits on `apply()`.
+    pub fn set_string_entry(mut sli: PyRefMut<'_, Self>, block_id: u32, index: u8, value: String) -> PyResult<PyRefMut<'_, Self>> {
+        if index > 15 {
+            return Err(PyErr::new::<MetaEditError, _>(format!("String table index {} out of range (0-15)", index)));
+        }
+        if block_id == 0 || block_id > 4096 {
+            return Err(PyErr::new::<MetaEditError, _>(format!("String table block id {} out of range (1-4096)", block_id)));
+        }
+        sli.string_entries.insert((block_id, index), value);
+        Ok(sli)
     }
 
-    #[cfg(target_os = "macos")]
-    fn apply_macos(&self) -> PyResult<()> {
-        let path = Path::new(&self.file_path);
-        let bundle_path = if self.file_path.ends_with(".app") {
-            path.to_path_buf()
-        } else {
-            let parent = path.parent().unwrap_or(Path::new("."));
-            let name = path.file_stem().unwrap().to_str().unwrap();
-            parent.join(format!("{}.app", name))
-        };
+    /// macOS: sets an Info.plist key to a `<true/>` / `<false/>` boolean (e.g. `LSUIElement`).
+    pub fn set_plist_bool(mut sli: PyRefMut<'_, Self>, key: String, value: bool) -> PyRefMut<'_, Self> {
+        sli.plist_values.insert(key, PlistEntry::Bool(value));
+        sli
+    }
 
-        let contents = bundle_path.join("Contents");
-        let macos_dir = contents.join("MacOS");
-        let resources_dir = contents.join("Resources");
-        
-        fs::create_dir_all(&macos_dir)?;
-        fs::create_dir_all(&resources_dir)?;
+    /// macOS: sets an Info.plist key to an `<integer>`.
+    pub fn set_plist_int(mut sli: PyRefMut<'_, Self>, key: String, value: i64) -> PyRefMut<'_, Self> {
+        sli.plist_values.insert(key, PlistEntry::Int(value));
+        sli
+    }
 
-        if path.is_file() {
-            let target_bin = macos_dir.join(path.file_name().unwrap());
-            fs::copy(path, target_bin)?;
-        }
+    /// macOS: sets an Info.plist key to an `<array>` of `<string>`s.
+    pub fn set_plist_array(mut sli: PyRefMut<'_, Self>, key: String, values: Vec<String>) -> PyRefMut<'_, Self> {
+        sli.plist_values.insert(key, PlistEntry::Array(values));
+        sli
+    }
 
-        let mut dict = HashMap::new();
-        dict.insert("CFBundleExecutable".to_string(), Value::String(path.file_name().unwrap().to_str().unwrap().to_string()));
-        
-        if let Some(ver) = &self.version {
-            dict.insert("CFBundleShortVersionString".to_string(), Value::String(ver.clone()));
-            dict.insert("CFBundleVersion".to_string(), Value::String(ver.clone()));
+    /// macOS: architectures in launch preference order (`LSArchitecturePriority`), e.g.
+    /// ["arm64", "x86_64"] to prefer the native slice of a universal binary.
+    pub fn set_architecture_priority(mut sli: PyRefMut<'_, Self>, architectures: Vec<String>) -> PyResult<PyRefMut<'_, Self>> {
+        if architectures.is_empty() {
+            return Err(PyErr::new::<MetaEditError, _>("Architecture priority needs at least one architecture"));
         }
-
-        if let Some(title) = self.strings.get("ProductName") {
-            dict.insert("CFBundleName".to_string(), Value::String(title.clone()));
+        for (i, arch) in architectures.iter().enumerate() {
+            if !MACOS_ARCHITECTURES.contains(&arch.as_str()) {
+                return Err(PyErr::new::<MetaEditError, _>(format!(
+                    "Unknown architecture '{}' (expected {})", arch, MACOS_ARCHITECTURES.join(" or ")
+                )));
+            }
+            if architectures[..i].contains(arch) {
+                return Err(PyErr::new::<MetaEditError, _>(format!("Architecture '{}' is listed twice", arch)));
+            }
         }
+        sli.plist_values.insert("LSArchitecturePriority".to_string(), PlistEntry::Array(architectures));
+        Ok(sli)
+    }
 
-        let plist_path = contents.join("Info.plist");
-        plist::to_file_xml(plist_path, &dict).map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+    /// macOS: `LSRequiresNativeExecution`; true keeps an Intel/ARM universal app from
+    /// being launched under Rosetta.
+    #[pyo3(signature = (enabled=true))]
+    pub fn set_requires_native_execution(mut sli: PyRefMut<'_, Self>, enabled: bool) -> PyRefMut<'_, Self> {
+        sli.plist_values.insert("LSRequiresNativeExecution".to_string(), PlistEntry::Bool(enabled));
+        sli
+    }
 
-        if let Some(icon) = &self.icon_path {
-            let icon_source = Path::new(icon);
-            if icon_source.exists() {
-                let icon_dest = resources_dir.join("app.icns");
-                fs::copy(icon_source, icon_dest)?;
-            }
-        }
+    /// Sets the language metadata is written in (e.g. "de-DE"). Selects the matching
+    /// VERSIONINFO string table on Windows, writes localized `Name[..]` keys on Linux and
+    /// sets CFBundleDevelopmentRegion on macOS. Defaults to US English when unset.
+    pub fn set_locale(mut sli: PyRefMut<'_, Self>, locale: String) -> PyResult<PyRefMut<'_, Self>> {
+        let locale = normalize_locale(&locale)?;
+        sli.locale = Some(locale);
+        Ok(sli)
+    }
 
-        Ok(())
+    /// Windows: writes VERSIONINFO strings to the table with this key, 8 hex digits of
+    /// language id and codepage (e.g. "040704B0"). Stored lowercase, like generated keys.
+    pub fn set_string_table_key(mut sli: PyRefMut<'_, Self>, key: String) -> PyResult<PyRefMut<'_, Self>> {
+        sli.table_key = Some(normalize_table_key(&key)?);
+        Ok(sli)
     }
 
-    #[cfg(target_os = "linux")]
-    fn apply_linux(&self) -> PyResult<()> {
-        let path = Path::new(&self.file_path);
-        let parent = path.parent().unwrap_or(Path::new("."));
-        let name = path.file_stem().unwrap().to_str().unwrap();
-        let desktop_path = parent.join(format!("{}.desktop", name));
+    /// Bit depth of the BMP frames (< 128px) in generated ICOs: 32 (default, BGRA),
+    /// 8 (256 colors) or 4 (16 colors) for very old Windows targets. Large frames stay PNG.
+    pub fn set_icon_bit_depth(mut sli: PyRefMut<'_, Self>, bit_depth: u16) -> PyResult<PyRefMut<'_, Self>> {
+        if ![4, 8, 32].contains(&bit_depth) {
+            return Err(PyErr::new::<IconError, _>(format!("Unsupported icon bit depth {} (expected 4, 8 or 32)", bit_depth)));
+        }
+        sli.icon_bit_depth = bit_depth;
+        Ok(sli)
+    }
 
-        let mut content = String::from("[Desktop Entry]\nType=Application\n");
-        content.push_str(&format!("Name={}\n", self.strings.get("ProductName").unwrap_or(&name.to_string())));
-        
-        if let Some(ver) = &self.version {
-            content.push_str(&format!("Version={}\n", ver));
+    /// Windows: the frame sizes of generated ICOs (1-256 px), replacing the quality preset's
+    /// list. 32 and 48 are added back unless `set_shell_icon_sizes(False)`.
+    pub fn set_ico_sizes(mut sli: PyRefMut<'_, Self>, sizes: Vec<u32>) -> PyResult<PyRefMut<'_, Self>> {
+        if sizes.is_empty() {
+            return Err(PyErr::new::<IconError, _>("ICO sizes need at least one size"));
+        }
+        if let Some(size) = sizes.iter().find(|&&size| size == 0 || size > 256) {
+            return Err(PyErr::new::<IconError, _>(format!("Unsupported ICO size {} (expected 1-256)", size)));
         }
+        let mut sizes = sizes;
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        sizes.dedup();
+        sli.ico_sizes = Some(sizes);
+        Ok(sli)
+    }
+
+    /// Windows: whether generated ICOs always carry the 32 and 48 px frames Explorer and
+    /// the taskbar rely on, even when the size list or a small source would drop them.
+    /// On by default; without them the shell scales another frame and the icon blurs.
+    #[pyo3(signature = (enabled=true))]
+    pub fn set_shell_icon_sizes(mut sli: PyRefMut<'_, Self>, enabled: bool) -> PyRefMut<'_, Self> {
+        sli.shell_icon_sizes = enabled;
+        sli
+    }
 
-        content.push_str(&format!("Exec=./{}\n", path.file_name().unwrap().to_str().unwrap()));
-        content.push_str("Terminal=false\n");
+    /// PNG compression for the large (>= 128px) ICO frames: "fast", "default" or "best".
+    /// When unset the encoder's standard settings are used.
+    pub fn set_png_compression(mut sli: PyRefMut<'_, Self>, level: String) -> PyResult<PyRefMut<'_, Self>> {
+        let compression = match level.to_lowercase().as_str() {
+            "fast" => CompressionType::Fast,
+            "default" => CompressionType::Default,
+            "best" => CompressionType::Best,
+            _ => return Err(PyErr::new::<IconError, _>(format!(
+                "Unknown PNG compression '{}' (expected fast, default or best)", level
+            ))),
+        };
+        sli.png_compression = Some(compression);
+        Ok(sli)
+    }
 
-        if let Some(icon) = &self.icon_path {
-            content.push_str(&format!("Icon={}\n", icon));
+    /// One dial for icon generation on every platform. "fast" skips the largest sizes
+    /// (256px ICO frames, 512/1024px ICNS slots, 256px theme icons), resizes with a triangle
+    /// filter and compresses PNGs quickly. "balanced" (default) is the standard output.
+    /// "high" adds the in-between sizes (e.g. 20, 40 and 96px ICO frames) with best PNG
+    /// compression. Lanczos3 is used for both. `set_png_compression` still takes precedence.
+    pub fn set_icon_quality(mut sli: PyRefMut<'_, Self>, preset: String) -> PyResult<PyRefMut<'_, Self>> {
+        let preset = preset.to_lowercase();
+        if !["fast", "balanced", "high"].contains(&preset.as_str()) {
+            return Err(PyErr::new::<IconError, _>(format!(
+                "Unknown icon quality '{}' (expected fast, balanced or high)", preset
+            )));
         }
+        sli.icon_quality = preset;
+        Ok(sli)
+    }
 
-        fs::write(desktop_path, content)?;
-        Ok(())
+    /// RGBA fill for the padding added when a non-square icon is fitted onto a square
+    /// canvas. `None` (the default) keeps the padding transparent.
+    #[pyo3(signature = (color=None))]
+    pub fn set_icon_background(mut sli: PyRefMut<'_, Self>, color: Option<(u8, u8, u8, u8)>) -> PyRefMut<'_, Self> {
+        sli.icon_background = color;
+        sli
     }
-}
 
-#[pyfunction]
-#[pyo3(signature = (file_path, metadata=None))]
-fn edit(file_path: String, metadata: Option<HashMap<String, String>>) -> MetadataEditor {
-    let mut editor = MetadataEditor::new(file_path);
-    if let Some(meta) = metadata {
-        for (k, v) in meta {
-            match k.as_str() {
-                "icon" => { editor.icon_path = Some(v); },
-                "version" => { editor.version = Some(v); },
-                _ => { editor.strings.insert(k, v); }
-            }
+    /// Largest width or height an icon source may declare (default 16384). Checked from the
+    /// image header before decoding, so oversized or decompression-bomb inputs raise `IconError`
+    /// without allocating their pixels.
+    pub fn set_max_icon_dimension(mut sli: PyRefMut<'_, Self>, max: u32) -> PyResult<PyRefMut<'_, Self>> {
+        if max == 0 {
+            return Err(PyErr::new::<IconError, _>("Maximum icon dimension must be positive"));
         }
+        sli.max_icon_dimension = max;
+        Ok(sli)
     }
-    editor
-}
 
-#[pyfunction]
-#[pyo3(signature = (file_path, **kwargs))]
-fn update(file_path: String, kwargs: Option<HashMap<String, String>>) -> PyResult<()> {
-    let mut editor = MetadataEditor::new(file_path);
-    if let Some(args) = kwargs {
-        for (k, v) in args {
-            match k.as_str() {
-                "icon" => { editor.icon_path = Some(v); },
-                "version" => { editor.version = Some(v); },
-                _ => { editor.strings.insert(k, v); }
-            }
+    /// Linux: install the icon into the user's hicolor theme and reference it by name
+    /// (`Icon=<name>`) instead of writing the raw icon path into the .desktop file.
+    pub fn set_install_icon_theme(mut sli: PyRefMut<'_, Self>, enabled: bool) -> PyRefMut<'_, Self> {
+        sli.install_icon_theme = enabled;
+        sli
+    }
+
+    /// Linux: names the generated launcher (e.g. "org.example.App.desktop") instead of
+    /// `<stem>.desktop`, for packaging tools that expect a particular file name.
+    pub fn set_desktop_filename(mut sli: PyRefMut<'_, Self>, name: String) -> PyResult<PyRefMut<'_, Self>> {
+        if !name.ends_with(".desktop") || name.len() == ".desktop".len() || name.contains(['/', '\\']) {
+            return Err(PyErr::new::<MetaEditError, _>(format!(
+                "Invalid desktop file name '{}' (expected a bare file name ending in .desktop)", name
+            )));
         }
+        sli.desktop_filename = Some(name);
+        Ok(sli)
     }
-    editor.apply()
-}
 
-#[pymodule]
-fn _metaedit(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
-    m.add_class::<MetadataEditor>()?;
-    m.add_function(wrap_pyfunction!(edit, m)?)?;
-    m.add_function(wrap_pyfunction!(update, m)?)?;
-    
-    m.add("MetaEditError", py.get_type::<MetaEditError>())?;
-    m.add("PEParseError", py.get_type::<PEParseError>())?;
-    m.add("IconError", py.get_type::<IconError>())?;
-    // m.add("SigningError", py.get_type::<SigningError>())?;
-    Ok(())
-}
+    /// Linux: directory to write the .desktop file to, created if needed. `Exec=` then
+    /// points at the binary by absolute path.
+    #[pyo3(signature = (output_dir=None))]
+    pub fn set_output_dir(mut sli: PyRefMut<'_, Self>, output_dir: Option<String>) -> PyRefMut<'_, Self> {
+        sli.output_dir = output_dir;
+        sli
+    }
 
-#[cfg(target_os = "windows")]
-fn create_ico_bmp_data(img: &image::DynamicImage, width: u32, height: u32) -> PyResult<Vec<u8>> {
-    let rgba = img.to_rgba8();
-    
-    // Each row in the AND mask must be a multiple of 4 bytes (32 bits)
-    // Formula: ((width + 31) / 32) * 4
-    let mask_row_size = ((width + 31) / 32) * 4;
-    let mask_size = mask_row_size * height;
-    
-    // Header (40) + XOR data (w*h*4) + AND mask
-    let data_size = 40 + (width * height * 4) + mask_size;
-    let mut data = Vec::with_capacity(data_size as usize);
+    /// Linux: writes `Hidden=true`, for background helpers that shouldn't appear in menus.
+    #[pyo3(signature = (hidden=true))]
+    pub fn set_hidden(mut sli: PyRefMut<'_, Self>, hidden: bool) -> PyRefMut<'_, Self> {
+        sli.hidden = hidden;
+        sli
+    }
 
-    // BITMAPINFOHEADER (40 bytes)
-    data.extend_from_slice(&(40u32).to_le_bytes()); // biSize
-    data.extend_from_slice(&(width as i32).to_le_bytes()); // biWidth
-    // ICO BMPs often use (height * 2) in the header to indicate XOR+AND combination
-    data.extend_from_slice(&((height * 2) as i32).to_le_bytes()); // biHeight
-    data.extend_from_slice(&(1u16).to_le_bytes()); // biPlanes
-    data.extend_from_slice(&(32u16).to_le_bytes()); // biBitCount (BGRA)
-    data.extend_from_slice(&(0u32).to_le_bytes()); // biCompression (BI_RGB)
-    data.extend_from_slice(&(0u32).to_le_bytes()); // biSizeImage (can be 0 for BI_RGB)
-    data.extend_from_slice(&(0u32).to_le_bytes()); // biXPelsPerMeter
-    data.extend_from_slice(&(0u32).to_le_bytes()); // biYPelsPerMeter
-    data.extend_from_slice(&(0u32).to_le_bytes()); // biClrUsed
-    data.extend_from_slice(&(0u32).to_le_bytes()); // biClrImportant
+    /// Linux: sets a boolean desktop entry key to `true` / `false`: one of DBusActivatable,
+    /// SingleMainWindow, PrefersNonDefaultGPU, Terminal, NoDisplay or StartupNotify.
+    /// DBusActivatable also needs the .desktop file named after the app's D-Bus name.
+    #[pyo3(signature = (key, value=true))]
+    pub fn set_desktop_bool(mut sli: PyRefMut<'_, Self>, key: String, value: bool) -> PyResult<PyRefMut<'_, Self>> {
+        let Some(&key) = DESKTOP_BOOL_KEYS.iter().find(|k| k.eq_ignore_ascii_case(&key)) else {
+            return Err(PyErr::new::<MetaEditError, _>(format!(
+                "'{}' is not a boolean desktop entry key (expected one of {})", key, DESKTOP_BOOL_KEYS.join(", ")
+            )));
+        };
+        sli.desktop_bools.insert(key.to_string(), value);
+        Ok(sli)
+    }
 
-    // XOR Mask (Pixel Data) - Stored Bottom-Up, BGRA format
-    for y in (0..height).rev() {
-        for x in 0..width {
-            let pixel = rgba.get_pixel(x, y);
-            data.push(pixel[2]); // B
-            data.push(pixel[1]); // G
-            data.push(pixel[0]); // R
-            data.push(pixel[3]); // A
+    /// Linux: restricts the launcher to these desktop environments (`OnlyShowIn=GNOME;KDE;`).
+    pub fn set_only_show_in(mut sli: PyRefMut<'_, Self>, desktops: Vec<String>) -> PyResult<PyRefMut<'_, Self>> {
+        validate_desktop_names(&desktops)?;
+        if !desktops.is_empty() && !sli.not_show_in.is_empty() {
+            return Err(PyErr::new::<MetaEditError, _>("OnlyShowIn and NotShowIn cannot both be set"));
         }
+        sli.only_show_in = desktops;
+        Ok(sli)
     }
 
-    // AND Mask (1-bit transparency) - Stored Bottom-Up
-    // 0 = Opaque, 1 = Transparent.
-    // Since we used Alpha channel in XOR mask (32-bit), this is technically redundant on modern Windows,
-    // but absolutely required for legacy compatibility and valid BMP structure in ICO.
-    for y in (0..height).rev() {
-        let mut row_bytes = vec![0u8; mask_row_size as usize];
+    /// Linux: hides the launcher in these desktop environments (`NotShowIn=KDE;`).
+    pub fn set_not_show_in(mut sli: PyRefMut<'_, Self>, desktops: Vec<String>) -> PyResult<PyRefMut<'_, Self>> {
+        validate_desktop_names(&desktops)?;
+        if !desktops.is_empty() && !sli.only_show_in.is_empty() {
+            return Err(PyErr::new::<MetaEditError, _>("OnlyShowIn and NotShowIn cannot both be set"));
+        }
+        sli.not_show_in = desktops;
+        Ok(sli)
+    }
+
+    /// Electron apps: also set `"version"` in `resources/app/package.json` next to the
+    /// binary, which is what the app's About dialog shows. Packed `app.asar` archives
+    /// can't be edited; those only get a warning.
+    #[pyo3(signature = (enabled=true))]
+    pub fn set_update_electron_package(mut sli: PyRefMut<'_, Self>, enabled: bool) -> PyRefMut<'_, Self> {
+        sli.update_electron_package = enabled;
+        sli
+    }
+
+    /// After each apply(), re-reads the output (PE version strings, Info.plist keys or
+    /// .desktop fields) and raises `MetaEditError` if a written field didn't round-trip.
+    /// Off by default since it costs a second read; worth enabling in CI.
+    #[pyo3(signature = (enabled=true))]
+    pub fn set_verify_after_write(mut sli: PyRefMut<'_, Self>, enabled: bool) -> PyRefMut<'_, Self> {
+        sli.verify_after_write = enabled;
+        sli
+    }
+
+    /// Linux: make the binary the .desktop file launches executable (chmod +x), since
+    /// launchers fail silently on a file without the bit. No-op on Windows.
+    #[pyo3(signature = (enabled=true))]
+    pub fn set_executable(mut sli: PyRefMut<'_, Self>, enabled: bool) -> PyRefMut<'_, Self> {
+        sli.make_executable = enabled;
+        sli
+    }
+
+    /// Linux: lay the .desktop file and icon out for an AppImage. The icon is installed under
+    /// `<appdir>/usr/share/icons/hicolor`, symlinked as `<appdir>/<name>.png`, and rendered as a
+    /// 256px `.DirIcon` at the AppDir root. `None` turns AppDir mode off.
+    #[pyo3(signature = (appdir=None))]
+    pub fn set_appdir(mut sli: PyRefMut<'_, Self>, appdir: Option<String>) -> PyResult<PyRefMut<'_, Self>> {
+        if let Some(dir) = &appdir {
+            if !Path::new(dir).is_dir() {
+                return Err(PyErr::new::<MetaEditError, _>(format!("AppDir not found: {}", dir)));
+            }
+        }
+        sli.appdir = appdir;
+        Ok(sli)
+    }
+
+    #[cfg(target_os = "windows")]
+    pub fn get_fixed_file_info(&self) -> PyResult<HashMap<String, String>> {
+        let mut result = HashMap::new();
+        if let Some(version_info) = self.read_version_info()? {
+            let info = &version_info.info;
+            let fv = &info.file_version;
+            let pv = &info.product_version;
+            result.insert("file_version".to_string(), format!("{}.{}.{}.{}", fv.major, fv.minor, fv.patch, fv.build));
+            result.insert("product_version".to_string(), format!("{}.{}.{}.{}", pv.major, pv.minor, pv.patch, pv.build));
+            result.insert("file_type".to_string(), file_type_name(info.file_type).to_string());
+            result.insert("file_subtype".to_string(), format!("0x{:x}", info.file_subtype));
+            result.insert("file_os".to_string(), format!("0x{:x}", info.file_os));
+            result.insert("file_flags".to_string(), format!("0x{:x}", info.file_flags));
+        }
+        Ok(result)
+    }
+
+    /// Keys of the VERSIONINFO string tables, in file order (e.g. ["040904b0", "040704b0"]),
+    /// for choosing a `set_locale` / `set_string_table_key` target. Empty without a version
+    /// resource.
+    #[cfg(target_os = "windows")]
+    pub fn list_version_languages(&self) -> PyResult<Vec<String>> {
+        Ok(self.read_version_info()?
+            .map(|info| info.strings.into_iter().map(|table| table.key.to_ascii_lowercase()).collect())
+            .unwrap_or_default())
+    }
+
+    /// Byte size of the ICO that `apply()` would embed for `icon_path`, with this editor's
+    /// size list, quality and compression settings. Nothing is written.
+    #[cfg(target_os = "windows")]
+    pub fn estimate_icon_size(&self, icon_path: String) -> PyResult<usize> {
+        if !Path::new(&icon_path).is_file() {
+            return Err(PyErr::new::<IconError, _>(format!("icon not found: {}", icon_path)));
+        }
+        let data = fs::read(&icon_path)?;
+        Ok(self.process_icon_windows(&data, &icon_path)?.len())
+    }
+
+    /// Structured view of the embedded manifest: the assembly identity ("name", "version",
+    /// "processor_architecture", "type"), "execution_level" and "ui_access" from
+    /// requestedExecutionLevel, and "dependencies" as "name version" entries joined by
+    /// "; ". Absent fields are omitted; empty without a manifest.
+    #[cfg(target_os = "windows")]
+    pub fn get_manifest_info(&self) -> PyResult<HashMap<String, String>> {
+        let mut info = HashMap::new();
+        let Some(manifest) = self.read_manifest()? else { return Ok(info) };
+
+        // Identities inside <dependency> describe dependent assemblies, not this one
+        let mut dependency_ranges = Vec::new();
+        let mut open = None;
+        for (pos, closing, _) in xml_tags(&manifest, "dependency") {
+            match (closing, open) {
+                (false, _) => open = Some(pos),
+                (true, Some(start)) => {
+                    dependency_ranges.push(start..pos);
+                    open = None;
+                }
+                (true, None) => {}
+            }
+        }
+        if let Some(start) = open {
+            dependency_ranges.push(start..manifest.len());
+        }
+
+        let mut dependencies = Vec::new();
+        for (pos, closing, tag) in xml_tags(&manifest, "assemblyIdentity") {
+            if closing {
+                continue;
+            }
+            if dependency_ranges.iter().any(|range| range.contains(&pos)) {
+                let name = xml_attr(tag, "name").unwrap_or_default();
+                dependencies.push(match xml_attr(tag, "version") {
+                    Some(version) => format!("{} {}", name, version),
+                    None => name,
+                });
+            } else if !info.contains_key("name") {
+                for (attr, key) in [("name", "name"), ("version", "version"), ("processorArchitecture", "processor_architecture"), ("type", "type")] {
+                    if let Some(value) = xml_attr(tag, attr) {
+                        info.insert(key.to_string(), value);
+                    }
+                }
+            }
+        }
+        if !dependencies.is_empty() {
+            info.insert("dependencies".to_string(), dependencies.join("; "));
+        }
+
+        if let Some((_, _, tag)) = xml_tags(&manifest, "requestedExecutionLevel").into_iter().find(|(_, closing, _)| !closing) {
+            if let Some(level) = xml_attr(tag, "level") {
+                info.insert("execution_level".to_string(), level);
+            }
+            if let Some(ui_access) = xml_attr(tag, "uiAccess") {
+                info.insert("ui_access".to_string(), ui_access);
+            }
+        }
+        Ok(info)
+    }
+
+    /// QA check: compares the FileVersion/ProductVersion strings with the numeric
+    /// FixedFileInfo versions and returns a description per disagreeing field
+    /// ("file_version", "product_version"). Empty when they agree or are absent.
+    #[cfg(target_os = "windows")]
+    pub fn version_consistency(&self) -> PyResult<HashMap<String, String>> {
+        let mut report = HashMap::new();
+        let Some(version_info) = self.read_version_info()? else { return Ok(report) };
+        let strings = self.string_table_index(&version_info.strings)
+            .map(|index| &version_info.strings[index].strings);
+        let checks = [
+            ("file_version", "FileVersion", &version_info.info.file_version),
+            ("product_version", "ProductVersion", &version_info.info.product_version),
+        ];
+        for (field, key, fixed) in checks {
+            let Some(string) = strings.and_then(|s| s.get(key)) else { continue };
+            let fixed_quad = [fixed.major, fixed.minor, fixed.patch, fixed.build];
+            let fixed_text = format!("{}.{}.{}.{}", fixed.major, fixed.minor, fixed.patch, fixed.build);
+            match parse_version(string.clone()) {
+                Ok((parts, _)) => {
+                    let mut quad = [0u32; 4];
+                    quad[..parts.len()].copy_from_slice(&parts);
+                    if quad.iter().zip(fixed_quad).any(|(a, b)| *a != b as u32) {
+                        report.insert(field.to_string(), format!("{} string is '{}' but FixedFileInfo says {}", key, string, fixed_text));
+                    }
+                }
+                Err(_) => {
+                    report.insert(field.to_string(), format!("{} string '{}' is not a version (FixedFileInfo says {})", key, string, fixed_text));
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Reads one VERSIONINFO string. `lang` selects the table ("0x0409", "040904b0" or a
+    /// locale like "de-DE"); without it the first table is used. Returns None when the
+    /// key or the requested language is missing.
+    #[cfg(target_os = "windows")]
+    #[pyo3(signature = (key, lang=None))]
+    pub fn get_string(&self, key: String, lang: Option<String>) -> PyResult<Option<String>> {
+        Ok(self.get_version_info(lang)?.and_then(|mut strings| strings.remove(&key)))
+    }
+
+    /// Reads RT_STRING block `block_id` as its 16 strings (string id = (block_id - 1) * 16 + index).
+    /// Uses the configured locale's language when present, else the first. None if missing.
+    #[cfg(target_os = "windows")]
+    pub fn get_string_table(&self, block_id: u32) -> PyResult<Option<Vec<String>>> {
+        let data = fs::read(&self.file_path)?;
+        let image = Image::parse(&data).map_err(|e| PyErr::new::<PEParseError, _>(format!("PE Parse error: {:?}", e)))?;
+        let langs = match image.resource_directory().map(|r| r.root()) {
+            Some(root) => match root.get(ResourceEntryName::ID(RT_STRING)) {
+                Some(ResourceEntry::Table(blocks)) => match blocks.get(ResourceEntryName::ID(block_id)) {
+                    Some(ResourceEntry::Table(langs)) => langs,
+                    _ => return Ok(None),
+                },
+                _ => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+        match self.resource_language(langs).and_then(|lang| langs.get(lang)) {
+            Some(ResourceEntry::Data(block)) => Ok(Some(decode_string_block(block.data())?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Reads a whole VERSIONINFO string table as a dict, selected like `get_string`.
+    #[cfg(target_os = "windows")]
+    #[pyo3(signature = (lang=None))]
+    pub fn get_version_info(&self, lang: Option<String>) -> PyResult<Option<HashMap<String, String>>> {
+        let version_info = match self.read_version_info()? {
+            Some(version_info) => version_info,
+            None => return Ok(None),
+        };
+        let table = find_string_table(&version_info.strings, lang.as_deref())?;
+        Ok(table.map(|t| t.strings.iter().map(|(k, v)| (k.clone(), v.clone())).collect()))
+    }
+
+    /// Writes a Windows `.rc` script with the configured version info and icon, for build
+    /// systems that compile resources in at link time instead of patching the binary.
+    /// Icons that aren't already `.ico` files are converted to `<output stem>.ico` next to it.
+    pub fn export_rc(&self, output_path: String) -> PyResult<()> {
+        let output = Path::new(&output_path);
+        let icon_file = match (self.icon_path_for("windows"), self.icon_source_for("windows")?) {
+            (Some(icon_path), _) if icon_path.to_lowercase().ends_with(".ico") => Some(std::path::PathBuf::from(icon_path)),
+            (_, Some((icon, source))) => {
+                let ico_path = output.with_extension("ico");
+                write_atomic(&ico_path, &self.rc_icon_data(&icon, &source)?)?;
+                Some(ico_path)
+            }
+            _ => None,
+        };
+
+        write_atomic(output, self.render_rc(icon_file.as_deref()).as_bytes())
+    }
+
+    /// Writes a WiX include (`.wxi`) defining ProductName, ProductVersion and Manufacturer
+    /// from the configured metadata, for `<?include?>` and `$(var.ProductName)` in installer sources.
+    pub fn export_wix_fragment(&self, output_path: String) -> PyResult<()> {
+        write_atomic(Path::new(&output_path), self.render_wix()?.as_bytes())
+    }
+
+    /// Names of the COFF File Header Characteristics flags set in the PE, e.g.
+    /// ["EXECUTABLE_IMAGE", "LARGE_ADDRESS_AWARE"].
+    pub fn get_characteristics(&self) -> PyResult<Vec<String>> {
+        let data = fs::read(&self.file_path)?;
+        let offset = coff_characteristics_offset(&data)
+            .ok_or_else(|| PyErr::new::<PEParseError, _>(format!("Not a PE file: {}", self.file_path)))?;
+        let value = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        Ok(COFF_CHARACTERISTICS.iter()
+            .filter(|(_, bit)| value & bit != 0)
+            .map(|(name, _)| name.to_string())
+            .collect())
+    }
+
+    /// The overlay: bytes after the PE image and its certificate table, e.g. a payload
+    /// added with `append_overlay`. Empty when there is none.
+    pub fn read_overlay<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, pyo3::types::PyBytes>> {
+        let data = fs::read(&self.file_path)?;
+        let start = overlay_start(&data)
+            .ok_or_else(|| PyErr::new::<PEParseError, _>(format!("Not a PE file: {}", self.file_path)))?;
+        Ok(pyo3::types::PyBytes::new(py, data.get(start..).unwrap_or_default()))
+    }
+
+    /// Appends `data` to the end of the PE, after any existing overlay, for payloads read
+    /// back at runtime. That invalidates a signature, so signed input follows the signed
+    /// policy like apply(). A non-zero header checksum is recomputed.
+    pub fn append_overlay(&self, data: Vec<u8>) -> PyResult<()> {
+        let path = Path::new(&self.file_path);
+        if !path.exists() {
+            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                format!("File not found: {}", self.file_path),
+            ));
+        }
+
+        let mut image = fs::read(path)?;
+        if pe_image_end(&image).is_none() {
+            return Err(PyErr::new::<PEParseError, _>(format!("Not a PE file: {}", self.file_path)));
+        }
+        let signed = pe_signed(&image, &self.file_path)?;
+        match SIGNED_POLICY.load(Ordering::Relaxed) {
+            SIGNED_REFUSE if signed => {
+                return Err(PyErr::new::<MetaEditError, _>(format!(
+                    "refusing to edit signed file {} (signed policy is \"refuse\")", self.file_path
+                )));
+            }
+            SIGNED_KEEP if signed => warn(&format!(
+                "{} keeps its original signature, which no longer matches the edited file; re-sign it before distribution",
+                self.file_path
+            ))?,
+            _ if signed => {
+                strip_pe_signature(&mut image);
+            }
+            _ => {}
+        }
+
+        image.extend_from_slice(&data);
+        update_pe_checksum(&mut image);
+        save_pe_backup(path)?;
+        write_atomic(path, &image)
+    }
+
+    /// Key Optional Header fields for diagnostics: magic, entry point, image base,
+    /// alignments, SizeOfImage, SizeOfHeaders, checksum, subsystem, DLL characteristics,
+    /// loader flags and NumberOfRvaAndSizes.
+    pub fn get_optional_header(&self) -> PyResult<HashMap<String, u64>> {
+        let data = fs::read(&self.file_path)?;
+        let fields = optional_header_fields(&data)
+            .ok_or_else(|| PyErr::new::<PEParseError, _>(format!("Not a PE file: {}", self.file_path)))?;
+        Ok(fields.into_iter().map(|(name, value)| (name.to_string(), value)).collect())
+    }
+
+    /// Whether the stored Optional Header CheckSum matches the file's contents. A zero
+    /// checksum means none was recorded, which counts as valid rather than a mismatch.
+    ///
+    pub fn is_checksum_valid(&self) -> PyResult<bool> {
+        let data = fs::read(&self.file_path)?;
+        if optional_header_fields(&data).is_none() {
+            return Err(PyErr::new::<PEParseError, _>(format!("Not a PE file: {}", self.file_path)));
+        }
+        Ok(pe_checksum_valid(&data))
+    }
+
+    /// One-call summary of the file: "format" (as `detect_target`), plus for PEs the
+    /// security-relevant status "signed", "checksum_valid" and "has_overlay" as
+    /// "true"/"false".
+    pub fn inspect(&self) -> PyResult<HashMap<String, String>> {
+        let path = Path::new(&self.file_path);
+        if !path.is_file() {
+            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                format!("File not found: {}", self.file_path),
+            ));
+        }
+        let mut report = HashMap::new();
+        report.insert("format".to_string(), detect_target(self.file_path.clone())?);
+
+        let data = fs::read(path)?;
+        if let Some(overlay) = overlay_start(&data).filter(|_| optional_header_fields(&data).is_some()) {
+            let signed = matches!(read_security_dir(&data), Some((_, offset, size)) if offset != 0 && size != 0);
+            report.insert("signed".to_string(), signed.to_string());
+            report.insert("checksum_valid".to_string(), pe_checksum_valid(&data).to_string());
+            report.insert("has_overlay".to_string(), (overlay < data.len()).to_string());
+        }
+        Ok(report)
+    }
+
+    /// Windows: replaces RT_BITMAP resource `id` (e.g. an about-box or splash image) with
+    /// `image_path`, stored as a DIB. A missing id is created in the language of the
+    /// existing bitmaps, or US English.
+    pub fn set_bitmap(mut sli: PyRefMut<'_, Self>, id: u16, image_path: String) -> PyResult<PyRefMut<'_, Self>> {
+        if id == 0 {
+            return Err(PyErr::new::<MetaEditError, _>("Bitmap resource ids start at 1"));
+        }
+        if !Path::new(&image_path).is_file() {
+            return Err(PyErr::new::<IconError, _>(format!("bitmap image not found: {}", image_path)));
+        }
+        sli.bitmaps.insert(id, image_path);
+        Ok(sli)
+    }
+
+    /// Windows: sets or clears a COFF Characteristics flag on apply, e.g.
+    /// `set_characteristic("DLL", true)`. Names may carry the `IMAGE_FILE_` prefix.
+    pub fn set_characteristic(mut sli: PyRefMut<'_, Self>, flag: String, enabled: bool) -> PyResult<PyRefMut<'_, Self>> {
+        let name = flag.to_uppercase();
+        let name = name.strip_prefix("IMAGE_FILE_").unwrap_or(&name);
+        let bit = COFF_CHARACTERISTICS.iter().find(|(n, _)| *n == name).map(|(_, bit)| *bit)
+            .ok_or_else(|| PyErr::new::<MetaEditError, _>(format!("Unknown COFF characteristic '{}'", flag)))?;
+        sli.characteristics.insert(bit, enabled);
+        Ok(sli)
+    }
+
+    /// True when the PE's Security Directory points at a certificate table. A PE with fewer
+    /// than 5 data directories has no such entry and raises PEParseError.
+    pub fn is_signed(&self) -> PyResult<bool> {
+        let path = Path::new(&self.file_path);
+        if !path.exists() {
+            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                format!("File not found: {}", self.file_path),
+            ));
+        }
+
+        let data = fs::read(path)?;
+        pe_signed(&data, &self.file_path)
+    }
+
+    /// Truncates the overlay: anything appended past the last section, the COFF symbol
+    /// table and the certificate table (installer payloads, protector data). Returns the
+    /// number of bytes removed. Refuses if the overlay sits in front of a certificate table
+    /// that is still referenced; call `remove_signature` first in that case.
+    pub fn strip_overlay(&self) -> PyResult<u64> {
+        let path = Path::new(&self.file_path);
+        if !path.exists() {
+            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                format!("File not found: {}", self.file_path),
+            ));
+        }
+
+        let mut data = fs::read(path)?;
+        let end = overlay_start(&data)
+            .ok_or_else(|| PyErr::new::<PEParseError, _>(format!("Not a PE file: {}", self.file_path)))?;
+        // A certificate table past the image is part of the overlay; cutting it would leave
+        // the Security directory pointing past the end of the file
+        if read_security_dir(&data).is_some_and(|(_, offset, size)| offset != 0 && size != 0 && offset as usize >= end) {
+            return Err(PyErr::new::<MetaEditError, _>(format!(
+                "{} has overlay data in front of its certificate table; call remove_signature() before strip_overlay()",
+                self.file_path
+            )));
+        }
+        if end >= data.len() {
+            return Ok(0);
+        }
+
+        let removed = (data.len() - end) as u64;
+        data.truncate(end);
+        update_pe_checksum(&mut data);
+        save_pe_backup(path)?;
+        write_atomic(path, &data)?;
+        info(&format!("Removed {} byte overlay from {}", removed, self.file_path));
+        Ok(removed)
+    }
+
+    /// Lists every resource as (type, id, size in bytes), with types shown by their RT_* name.
+    /// Named resources report id 0.
+    #[cfg(target_os = "windows")]
+    pub fn list_resources(&self) -> PyResult<Vec<(String, u16, usize)>> {
+        let data = fs::read(&self.file_path)?;
+        let image = Image::parse(&data).map_err(|e| PyErr::new::<PEParseError, _>(format!("PE Parse error: {:?}", e)))?;
+        let resources = match image.resource_directory() {
+            Some(resources) => resources,
+            None => return Ok(Vec::new()),
+        };
+
+        Ok(resource_leaves(resources.root()).into_iter().map(|(path, size)| {
+            let kind = match path.first() {
+                Some(ResourceEntryName::ID(id)) => resource_type_name(*id),
+                Some(ResourceEntryName::Name(name)) => name.clone(),
+                None => String::new(),
+            };
+            let id = match path.get(1) {
+                Some(ResourceEntryName::ID(id)) => *id as u16,
+                _ => 0,
+            };
+            (kind, id, size)
+        }).collect())
+    }
+
+    /// Dry run for Windows: applies the configured edits to an in-memory copy of the resource
+    /// directory and reports which resources and version strings would change. Writes nothing.
+    #[cfg(target_os = "windows")]
+    pub fn plan_resources(&self) -> PyResult<ResourceDiff> {
+        let data = fs::read(&self.file_path)?;
+        let image = Image::parse(&data).map_err(|e| PyErr::new::<PEParseError, _>(format!("PE Parse error: {:?}", e)))?;
+        let before = image.resource_directory().cloned().unwrap_or_default();
+        let mut after = before.clone();
+        self.edit_resources(&mut after)?;
+
+        let old_leaves = resource_leaf_map(before.root());
+        let new_leaves = resource_leaf_map(after.root());
+        let mut diff = ResourceDiff { added: Vec::new(), removed: Vec::new(), modified: Vec::new(), strings: Vec::new() };
+        for (path, data) in &new_leaves {
+            match old_leaves.get(path) {
+                None => diff.added.push(resource_label(path)),
+                Some(old) if old != data => diff.modified.push(resource_label(path)),
+                _ => {}
+            }
+        }
+        diff.removed = old_leaves.keys().filter(|path| !new_leaves.contains_key(*path)).map(|path| resource_label(path)).collect();
+
+        // Version strings, compared within the table edits land in
+        let read_tables = |resources: &ResourceDirectory| -> PyResult<Vec<VersionStringTable>> {
+            Ok(resources.get_version_info()
+                .map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to get version info: {:?}", e)))?
+                .map(|info| info.strings)
+                .unwrap_or_default())
+        };
+        let (old_tables, new_tables) = (read_tables(&before)?, read_tables(&after)?);
+        if let Some(new_table) = self.string_table_index(&new_tables).map(|i| &new_tables[i]) {
+            let old_strings = old_tables.iter().find(|t| t.key.eq_ignore_ascii_case(&new_table.key)).map(|t| &t.strings);
+            let old_value = |key: &str| old_strings.and_then(|strings| strings.get(key)).cloned();
+            for (key, value) in &new_table.strings {
+                let old = old_value(key);
+                if old.as_ref() != Some(value) {
+                    diff.strings.push((key.clone(), old, Some(value.clone())));
+                }
+            }
+            for (key, value) in old_strings.into_iter().flatten() {
+                if !new_table.strings.contains_key(key) {
+                    diff.strings.push((key.clone(), Some(value.clone()), None));
+                }
+            }
+        }
+        Ok(diff)
+    }
+
+    /// Writes every frame of the main icon group to `<output_dir>/icon_<w>x<h>.png`,
+    /// converting BMP frames to PNG. Returns the written paths in group order.
+    #[cfg(target_os = "windows")]
+    pub fn extract_icon_frames(&self, output_dir: String) -> PyResult<Vec<String>> {
+        let frames = self.icon_frames()?;
+        let out_dir = Path::new(&output_dir);
+        fs::create_dir_all(out_dir).map_err(|e| write_error(out_dir, e))?;
+
+        let mut written = Vec::new();
+        for (bit_count, id, img) in frames {
+            let mut dest = out_dir.join(format!("icon_{}x{}.png", img.width(), img.height()));
+            if written.contains(&dest.to_string_lossy().into_owned()) {
+                // Same size at several color depths
+                dest = out_dir.join(format!("icon_{}x{}_{}bit.png", img.width(), img.height(), bit_count));
+            }
+
+            write_atomic(&dest, &encode_frame(&img, id, image::ImageFormat::Png)?)?;
+            written.push(dest.to_string_lossy().into_owned());
+        }
+        Ok(written)
+    }
+
+    /// Windows only: returns one frame of the main icon as PNG bytes, the `size`x`size`
+    /// frame if given, otherwise the largest. Open it with `PIL.Image.open(io.BytesIO(...))`.
+    #[cfg(target_os = "windows")]
+    #[pyo3(signature = (size=None))]
+    pub fn extract_icon_bytes<'py>(&self, py: Python<'py>, size: Option<u32>) -> PyResult<Bound<'py, pyo3::types::PyBytes>> {
+        let frames = self.icon_frames()?;
+        let chosen = match size {
+            // Prefer the deepest color depth when a size appears more than once
+            Some(size) => frames.iter()
+                .filter(|(_, _, img)| img.width() == size && img.height() == size)
+                .max_by_key(|(bit_count, _, _)| *bit_count),
+            None => largest_frame(&frames),
+        };
+        let (_, id, img) = chosen.ok_or_else(|| match size {
+            Some(size) => {
+                let available: Vec<String> = frames.iter().map(|(_, _, img)| format!("{}x{}", img.width(), img.height())).collect();
+                PyErr::new::<IconError, _>(format!(
+                    "No {}x{} icon frame in {} (available: {})", size, size, self.file_path, available.join(", ")
+                ))
+            }
+            None => PyErr::new::<IconError, _>(format!("No icon frames in {}", self.file_path)),
+        })?;
+        Ok(pyo3::types::PyBytes::new(py, &encode_frame(img, *id, image::ImageFormat::Png)?))
+    }
+
+    /// Windows only: exports the main icon, encoded by `output_path`'s extension: `.ico`
+    /// rebuilds the whole multi-frame icon from the original frames, while `.png` and `.bmp`
+    /// write a single image: the largest frame, and at equal sizes the deepest color depth.
+    #[cfg(target_os = "windows")]
+    pub fn extract_icon(&self, output_path: String) -> PyResult<()> {
+        let output = Path::new(&output_path);
+        let extension = output.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let format = match extension.as_str() {
+            "ico" => {
+                let group = self.icon_group()?;
+                if group.is_empty() {
+                    return Err(PyErr::new::<IconError, _>(format!("No icon frames in {}", self.file_path)));
+                }
+                return write_atomic(output, &build_ico(&group));
+            }
+            "png" => image::ImageFormat::Png,
+            "bmp" => image::ImageFormat::Bmp,
+            _ => return Err(PyErr::new::<IconError, _>(format!(
+                "Unsupported icon output '{}' (expected .ico, .png or .bmp)", output_path
+            ))),
+        };
+
+        let frames = self.icon_frames()?;
+        let (_, id, img) = largest_frame(&frames)
+            .ok_or_else(|| PyErr::new::<IconError, _>(format!("No icon frames in {}", self.file_path)))?;
+        write_atomic(output, &encode_frame(img, *id, format)?)
+    }
+
+    /// Clears the Security directory entry and drops the certificate table if it ends the
+    /// file. Unsigned files are left as they are.
+    pub fn remove_signature(&self) -> PyResult<()> {
+        let path = Path::new(&self.file_path);
+        if !path.exists() {
+            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                format!("File not found: {}", self.file_path),
+            ));
+        }
+
+        check_input_size(path)?;
+        let mut data = fs::read(path)?;
+        if pe_signed(&data, &self.file_path)? && strip_pe_signature(&mut data) {
+            update_pe_checksum(&mut data);
+            save_pe_backup(path)?;
+            write_atomic(path, &data)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the edited PE to `output_path` instead of over the input, which is left
+    /// untouched. With `content_hash`, a short hash of the final bytes goes before the
+    /// extension (`app.exe` -> `app.1a2b3c4d.exe`), so identical inputs always produce the
+    /// same name. Returns the path written.
+    #[cfg(target_os = "windows")]
+    #[pyo3(signature = (output_path, content_hash=false))]
+    pub fn save_as(&self, output_path: String, content_hash: bool) -> PyResult<String> {
+        let path = Path::new(&self.file_path);
+        if !path.exists() {
+            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                format!("File not found: {}", self.file_path),
+            ));
+        }
+
+        check_input_size(path)?;
+        let data = fs::read(path)?;
+        let image = Image::parse(&data).map_err(|e| PyErr::new::<PEParseError, _>(format!("PE Parse error: {:?}", e)))?;
+        let resources = image.resource_directory().cloned().unwrap_or_default();
+        let final_data = self.edited_pe(&data, image, resources)?;
+
+        let output = if content_hash {
+            content_addressed_path(Path::new(&output_path), &final_data)
+        } else {
+            std::path::PathBuf::from(&output_path)
+        };
+        write_atomic(&output, &final_data)?;
+        Ok(output.to_string_lossy().into_owned())
+    }
+
+    // Builds without PE support keep the Windows-only methods, raising a clear error
+    // instead of an AttributeError.
+    #[cfg(not(target_os = "windows"))]
+    pub fn get_fixed_file_info(&self) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn version_consistency(&self) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn get_manifest_info(&self) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn list_version_languages(&self) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[allow(unused_variables)]
+    pub fn estimate_icon_size(&self, icon_path: String) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[pyo3(signature = (key, lang=None))]
+    #[allow(unused_variables)]
+    pub fn get_string(&self, key: String, lang: Option<String>) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[allow(unused_variables)]
+    pub fn get_string_table(&self, block_id: u32) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[pyo3(signature = (lang=None))]
+    #[allow(unused_variables)]
+    pub fn get_version_info(&self, lang: Option<String>) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn list_resources(&self) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn plan_resources(&self) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[allow(unused_variables)]
+    pub fn extract_icon_frames(&self, output_dir: String) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[pyo3(signature = (size=None))]
+    #[allow(unused_variables)]
+    pub fn extract_icon_bytes(&self, size: Option<u32>) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[allow(unused_variables)]
+    pub fn extract_icon(&self, output_path: String) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[pyo3(signature = (output_path, content_hash=false))]
+    #[allow(unused_variables)]
+    pub fn save_as(&self, output_path: String, content_hash: bool) -> PyResult<()> {
+        pe_unavailable()
+    }
+
+    /// Writes the configured metadata. With `only_if_changed`, outputs that already
+    /// carry exactly the requested metadata are left untouched. With `report_path`, the
+    /// result is also written there as JSON (see `ApplyResult.to_json`) for CI artifacts.
+    #[pyo3(signature = (only_if_changed=false, report_path=None))]
+    pub fn apply(&self, only_if_changed: bool, report_path: Option<String>) -> PyResult<ApplyResult> {
+        let path = Path::new(&self.file_path);
+        if !path.exists() {
+            return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+                format!("File not found: {}", self.file_path),
+            ));
+        }
+
+        if path.is_file() {
+            check_input_size(path)?;
+        }
+
+        // PE-only edits would otherwise be dropped without a word on other hosts
+        if cfg!(not(target_os = "windows"))
+            && (!self.string_entries.is_empty() || !self.bitmaps.is_empty() || !self.characteristics.is_empty() || self.dpi_awareness.is_some())
+        {
+            return pe_unavailable();
+        }
+
+        let format = if path.is_file() {
+            detect_target(self.file_path.clone())?
+        } else if self.file_path.ends_with(".app") {
+            "macos".to_string()
+        } else {
+            "unknown".to_string()
+        };
+        // Read before writing so the report can show old -> new
+        let before = self.current_fields().unwrap_or_default();
+        let was_signed = cfg!(target_os = "windows") && path.is_file() && self.is_signed()?;
+
+        let mut changed = self.apply_target(host_target(), only_if_changed)?;
+
+        if path.is_file() {
+            changed |= self.sync_electron_package(only_if_changed)?;
+        }
+        if self.verify_after_write {
+            self.verify_written()?;
+        }
+
+        let mut result = ApplyResult::new(changed);
+        if changed && was_signed {
+            if SIGNED_POLICY.load(Ordering::Relaxed) == SIGNED_KEEP {
+                result = result.with_invalid_signature();
+                result.signature = "kept".to_string();
+            } else {
+                result.signature = "stripped".to_string();
+            }
+        }
+        result.file = self.file_path.clone();
+        result.format = format;
+        result.output = self.target_output(host_target()).to_string_lossy().into_owned();
+        if changed {
+            let mut requested = self.reported_fields();
+            requested.sort();
+            result.changes = requested.into_iter()
+                .filter(|(field, value)| before.get(field) != Some(value))
+                .map(|(field, value)| {
+                    let old = before.get(&field).cloned();
+                    (field, old, value)
+                })
+                .collect();
+        }
+
+        if let Some(report_path) = report_path {
+            write_atomic(Path::new(&report_path), result.to_json().as_bytes())?;
+        }
+        Ok(result)
+    }
+
+    /// Inspects the configuration against `platform` ("windows", "macos" or "linux") and
+    /// returns a warning per setting that target ignores and per value it needs but lacks,
+    /// e.g. a plist key on a Windows build. Empty when everything set applies.
+    pub fn validate_for(&self, platform: String) -> PyResult<Vec<String>> {
+        let platform = platform.to_lowercase();
+        if !["windows", "macos", "linux"].contains(&platform.as_str()) {
+            return Err(PyErr::new::<MetaEditError, _>(format!(
+                "Unknown platform '{}' (expected windows, macos or linux)", platform
+            )));
+        }
+
+        // (setting, the only target that uses it)
+        let mut settings: Vec<(String, &str)> = Vec::new();
+        // ProductName is written everywhere, LegalCopyright on Windows and macOS
+        let mut strings: Vec<&String> = self.strings.keys().filter(|k| *k != "ProductName" && *k != "LegalCopyright").collect();
+        strings.sort();
+        settings.extend(strings.into_iter().map(|key| (format!("version string '{}'", key), "windows")));
+        let windows_only = [
+            (self.file_type.is_some(), "file type"),
+            (self.table_key.is_some(), "string table key"),
+            (!self.string_entries.is_empty(), "RT_STRING entries"),
+            (!self.bitmaps.is_empty(), "bitmap resources"),
+            (!self.characteristics.is_empty(), "COFF characteristics"),
+            (self.dpi_awareness.is_some(), "DPI awareness"),
+            (self.ico_sizes.is_some(), "ICO sizes"),
+            (self.icon_bit_depth != 32, "icon bit depth"),
+            (self.create_shortcut, "shortcut"),
+        ];
+        let macos_only = [
+            (self.binary_placement != "copy", "binary placement"),
+            (self.bundle_name.is_some(), "bundle name"),
+            (self.icon_name.is_some(), "icon name"),
+            (self.dmg_staging.is_some(), "DMG staging"),
+            (!self.plist_values.is_empty(), "Info.plist values"),
+            (self.development_region.is_some(), "development region"),
+            (self.info_dictionary_version.is_some(), "info dictionary version"),
+            (self.app_category.is_some(), "app category"),
+            (!self.localized_names.is_empty(), "localized names"),
+        ];
+        let linux_only = [
+            (self.install_icon_theme, "icon theme install"),
+            (self.appdir.is_some(), "AppDir"),
+            (self.desktop_filename.is_some(), "desktop file name"),
+            (self.hidden, "Hidden"),
+            (!self.only_show_in.is_empty(), "OnlyShowIn"),
+            (!self.not_show_in.is_empty(), "NotShowIn"),
+            (!self.desktop_bools.is_empty(), "desktop entry booleans"),
+            (self.make_executable, "executable bit"),
+        ];
+        for (target, flags) in [("windows", &windows_only[..]), ("macos", &macos_only[..]), ("linux", &linux_only[..])] {
+            settings.extend(flags.iter().filter(|(set, _)| *set).map(|(_, name)| (name.to_string(), target)));
+        }
+
+        let mut warnings: Vec<String> = settings.into_iter()
+            .filter(|(_, target)| *target != platform)
+            .map(|(setting, target)| format!("{} is only used on {}; {} ignores it", setting, target, platform))
+            .collect();
+        if platform == "linux" && self.strings.contains_key("LegalCopyright") {
+            warnings.push("version string 'LegalCopyright' is only used on windows and macos; linux ignores it".to_string());
+        }
+        if platform == "linux" && self.product_version.is_some() {
+            warnings.push("product version is not used on linux; the desktop entry carries only the version".to_string());
+        }
+
+        let has_icon = self.icon_path_for(&platform).is_some() || self.icon_bytes.is_some() || self.use_default_icon;
+        if platform == "macos" && self.version.is_none() {
+            warnings.push("no version set; the bundle will have no CFBundleVersion".to_string());
+        }
+        if platform != "windows" && !has_icon {
+            warnings.push(format!("no icon set; {} will show a generic icon", platform));
+        }
+        Ok(warnings)
+    }
+
+    /// Produces the artifact for each of `targets` ("windows", "macos", "linux") and returns
+    /// target -> output path. When the editor's path is a directory, each target's input is
+    /// the binary in it whose magic matches (or a `.app` for macOS); otherwise the one file
+    /// is used for every target. The `.app` and `.desktop` backends only generate files, so
+    /// they run on any host. Rewriting PE resources needs editpe, which only Windows builds
+    /// link, so elsewhere "windows" raises before anything is written.
+    pub fn apply_all(&self, targets: Vec<String>) -> PyResult<HashMap<String, String>> {
+        let mut inputs = Vec::new();
+        for target in &targets {
+            if !["windows", "macos", "linux"].contains(&target.as_str()) {
+                return Err(PyErr::new::<MetaEditError, _>(format!(
+                    "Unknown target '{}' (expected windows, macos or linux)", target
+                )));
+            }
+            if target == "windows" && cfg!(not(target_os = "windows")) {
+                return pe_unavailable();
+            }
+            inputs.push((target.clone(), self.target_input(target)?));
+        }
+
+        let mut outputs = HashMap::new();
+        for (target, input) in inputs {
+            let mut editor = self.clone();
+            editor.file_path = input.to_string_lossy().into_owned();
+            // The host's target gets everything apply() does (Electron sync, verification)
+            if target == host_target() {
+                editor.apply(false, None)?;
+            } else {
+                editor.apply_target(&target, false)?;
+            }
+            outputs.insert(target.clone(), editor.target_output(&target).to_string_lossy().into_owned());
+        }
+        Ok(outputs)
+    }
+
+    /// macOS: updates the metadata of an existing `.app` in place. Only the configured keys
+    /// are merged into its Info.plist (CFBundleExecutable, CFBundleIdentifier and everything
+    /// else stay), and the icon and localized names go into `Contents/Resources`. The
+    /// executable is never touched and no bundle structure is created.
+    #[cfg(target_os = "macos")]
+    #[pyo3(signature = (bundle_path, only_if_changed=false))]
+    pub fn edit_bundle(&self, bundle_path: String, only_if_changed: bool) -> PyResult<ApplyResult> {
+        let bundle = Path::new(&bundle_path);
+        let contents = bundle.join("Contents");
+        let plist_path = contents.join("Info.plist");
+        if !plist_path.is_file() {
+            return Err(PyErr::new::<MetaEditError, _>(format!("{} is not an app bundle (no Contents/Info.plist)", bundle_path)));
+        }
+        let mut dict = Value::from_file(&plist_path)
+            .map_err(|e| PyErr::new::<MetaEditError, _>(format!("Failed to read {}: {}", plist_path.display(), e)))?
+            .into_dictionary()
+            .ok_or_else(|| PyErr::new::<MetaEditError, _>(format!("{} is not a dictionary", plist_path.display())))?;
+
+        let icon = self.bundle_icon()?;
+        let resources_dir = contents.join("Resources");
+        if (icon.is_some() || !self.localized_names.is_empty()) && !resources_dir.is_dir() {
+            return Err(PyErr::new::<MetaEditError, _>(format!("{} has no Contents/Resources directory", bundle_path)));
+        }
+        let mut keys = IndexMap::new();
+        if let Some(version) = &self.info_dictionary_version {
+            keys.insert("CFBundleInfoDictionaryVersion".to_string(), PlistEntry::String(version.clone()));
+        }
+        let region = self.development_region.as_ref().or(self.locale.as_ref()).map(String::as_str);
+        self.insert_bundle_keys(&mut keys, region, icon.is_some());
+        for (key, entry) in &keys {
+            dict.insert(key.clone(), plist_value(entry));
+        }
+
+        let mut data = Vec::new();
+        Value::Dictionary(dict).to_writer_xml(&mut data).map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+        let mut changed = write_if_changed(&plist_path, &data, only_if_changed)?;
+        changed |= self.write_bundle_resources(&resources_dir, icon, only_if_changed)?;
+        let mut result = ApplyResult::new(changed);
+        result.file = bundle_path.clone();
+        result.output = bundle_path;
+        result.format = "macos".to_string();
+        Ok(result)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[pyo3(signature = (bundle_path, only_if_changed=false))]
+    #[allow(unused_variables)]
+    pub fn edit_bundle(&self, bundle_path: String, only_if_changed: bool) -> PyResult<ApplyResult> {
+        Err(PyErr::new::<MetaEditError, _>("edit_bundle needs the macOS backend; run it on a macOS machine"))
+    }
+}
+
+impl MetadataEditor {
+    /// The metadata fields `apply` writes, as (key, value) in the target's own keys:
+    /// VERSIONINFO strings on Windows.
+    #[cfg(target_os = "windows")]
+    fn reported_fields(&self) -> Vec<(String, String)> {
+        self.version_strings()
+    }
+
+    /// The metadata fields `apply` writes, as (key, value) in the target's own keys:
+    /// Info.plist keys on macOS.
+    #[cfg(target_os = "macos")]
+    fn reported_fields(&self) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+        if let Some(ver) = self.product_version.as_ref().or(self.version.as_ref()) {
+            fields.push(("CFBundleShortVersionString".to_string(), ver.clone()));
+        }
+        if let Some(ver) = &self.version {
+            fields.push(("CFBundleVersion".to_string(), ver.clone()));
+        }
+        if let Some(title) = self.strings.get("ProductName") {
+            fields.push(("CFBundleName".to_string(), title.clone()));
+        }
+        if let Some(copyright) = self.strings.get("LegalCopyright") {
+            fields.push(("NSHumanReadableCopyright".to_string(), copyright.clone()));
+        }
+        if let Some(id) = &self.build_id {
+            fields.push(("BuildID".to_string(), id.clone()));
+        }
+        fields
+    }
+
+    /// The metadata fields `apply` writes, as (key, value) in the target's own keys:
+    /// .desktop keys on Linux.
+    #[cfg(target_os = "linux")]
+    fn reported_fields(&self) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+        if let Some(title) = self.strings.get("ProductName") {
+            fields.push(("Name".to_string(), desktop_value(title)));
+        }
+        if let Some(ver) = &self.version {
+            fields.push(("Version".to_string(), desktop_value(ver)));
+        }
+        if let Some(id) = &self.build_id {
+            fields.push(("X-BuildID".to_string(), desktop_value(id)));
+        }
+        fields
+    }
+
+    /// Current values of the fields `reported_fields` covers: the VERSIONINFO string table
+    /// edits land in.
+    #[cfg(target_os = "windows")]
+    fn current_fields(&self) -> PyResult<HashMap<String, String>> {
+        let tables = self.read_version_info()?.map(|info| info.strings).unwrap_or_default();
+        Ok(self.string_table_index(&tables)
+            .map(|index| tables[index].strings.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default())
+    }
+
+    /// Current values of the fields `reported_fields` covers: the bundle's Info.plist.
+    #[cfg(target_os = "macos")]
+    fn current_fields(&self) -> PyResult<HashMap<String, String>> {
+        let plist_path = self.target_output("macos").join("Contents").join("Info.plist");
+        if !plist_path.exists() {
+            return Ok(HashMap::new());
+        }
+        let info = Value::from_file(&plist_path)
+            .map_err(|e| PyErr::new::<MetaEditError, _>(format!("Failed to read {}: {}", plist_path.display(), e)))?;
+        Ok(info.as_dictionary()
+            .map(|dict| dict.iter().filter_map(|(k, v)| Some((k.clone(), v.as_string()?.to_string()))).collect())
+            .unwrap_or_default())
+    }
+
+    /// Current values of the fields `reported_fields` covers: the keys of the .desktop
+    /// file's main group.
+    #[cfg(target_os = "linux")]
+    fn current_fields(&self) -> PyResult<HashMap<String, String>> {
+        let content = fs::read_to_string(self.desktop_path()).unwrap_or_default();
+        Ok(desktop_entry_lines(&content)
+            .filter_map(|line| line.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect())
+    }
+
+    /// Re-reads the output and checks every field `reported_fields` covers came out as written.
+    fn verify_written(&self) -> PyResult<()> {
+        let current = self.current_fields()?;
+        let mismatches: Vec<String> = self.reported_fields().into_iter()
+            .filter(|(field, value)| current.get(field) != Some(value))
+            .map(|(field, value)| match current.get(&field) {
+                Some(found) => format!("{} is '{}', expected '{}'", field, found, value),
+                None => format!("{} is missing, expected '{}'", field, value),
+            })
+            .collect();
+        if mismatches.is_empty() {
+            return Ok(());
+        }
+        Err(PyErr::new::<MetaEditError, _>(format!(
+            "Verification after write failed for {}: {}", self.file_path, mismatches.join("; ")
+        )))
+    }
+
+    /// Numeric FileVersion currently in the PE, if it has one.
+    #[cfg(target_os = "windows")]
+    fn current_version(&self) -> PyResult<Option<String>> {
+        Ok(self.read_version_info()?.map(|version_info| {
+            let v = &version_info.info.file_version;
+            format!("{}.{}.{}.{}", v.major, v.minor, v.patch, v.build)
+        }))
+    }
+
+    /// CFBundleVersion of the bundle's existing Info.plist, if any.
+    #[cfg(target_os = "macos")]
+    fn current_version(&self) -> PyResult<Option<String>> {
+        let plist_path = self.target_output("macos").join("Contents").join("Info.plist");
+        if !plist_path.exists() {
+            return Ok(None);
+        }
+        let info = Value::from_file(&plist_path)
+            .map_err(|e| PyErr::new::<MetaEditError, _>(format!("Failed to read {}: {}", plist_path.display(), e)))?;
+        Ok(info.as_dictionary()
+            .and_then(|dict| dict.get("CFBundleVersion"))
+            .and_then(|v| v.as_string())
+            .map(str::to_string))
+    }
+
+    /// `Version=` of the existing .desktop file, if any.
+    #[cfg(target_os = "linux")]
+    fn current_version(&self) -> PyResult<Option<String>> {
+        let desktop_path = self.target_output("linux");
+        let content = match fs::read_to_string(&desktop_path) {
+            Ok(content) => content,
+            Err(_) => return Ok(None),
+        };
+        let version = desktop_entry_lines(&content).find_map(|line| line.strip_prefix("Version=")).map(str::to_string);
+        Ok(version)
+    }
+
+    /// Where `apply_linux` writes the launcher: `output_dir`, else the AppDir root (which
+    /// AppImage tooling expects), else next to the binary.
+    fn desktop_path(&self) -> std::path::PathBuf {
+        let path = Path::new(&self.file_path);
+        let parent = path.parent().unwrap_or(Path::new("."));
+        let dir = self.output_dir.as_deref().or(self.appdir.as_deref()).map(Path::new).unwrap_or(parent);
+        match &self.desktop_filename {
+            Some(file_name) => dir.join(file_name),
+            None => dir.join(format!("{}.desktop", path.file_stem().and_then(|n| n.to_str()).unwrap_or("app"))),
+        }
+    }
+
+    /// Runs the backend for `target` ("windows", "macos" or "linux"). Returns whether
+    /// anything was written.
+    fn apply_target(&self, target: &str, only_if_changed: bool) -> PyResult<bool> {
+        match target {
+            #[cfg(target_os = "windows")]
+            "windows" => Ok(self.apply_windows(only_if_changed)? | self.write_shortcut(only_if_changed)?),
+            #[cfg(not(target_os = "windows"))]
+            "windows" => pe_unavailable(),
+            "macos" => self.apply_macos(only_if_changed),
+            _ => self.apply_linux(only_if_changed),
+        }
+    }
+
+    /// Input binary for `target` in `apply_all`.
+    fn target_input(&self, target: &str) -> PyResult<std::path::PathBuf> {
+        let path = Path::new(&self.file_path);
+        if !path.is_dir() || (target == "macos" && self.file_path.ends_with(".app")) {
+            return Ok(path.to_path_buf());
+        }
+
+        let mut entries: Vec<_> = fs::read_dir(path)?.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        entries.sort();
+        for entry in entries {
+            let matches = if entry.is_dir() {
+                target == "macos" && entry.extension().is_some_and(|ext| ext == "app")
+            } else {
+                detect_target(entry.to_string_lossy().into_owned())? == target
+            };
+            if matches {
+                return Ok(entry);
+            }
+        }
+        Err(PyErr::new::<MetaEditError, _>(format!("No {} binary found in {}", target, self.file_path)))
+    }
+
+    /// The artifact `apply()` produces for `target`: the PE itself, the `.app` bundle or the
+    /// `.desktop` file.
+    fn target_output(&self, target: &str) -> std::path::PathBuf {
+        let path = Path::new(&self.file_path);
+        let parent = path.parent().unwrap_or(Path::new("."));
+        let name = path.file_stem().and_then(|n| n.to_str()).unwrap_or("app");
+        match target {
+            "macos" if self.file_path.ends_with(".app") => path.to_path_buf(),
+            "macos" => parent.join(format!("{}.app", self.bundle_name.as_deref().unwrap_or(name))),
+            "linux" => self.desktop_path(),
+            _ => path.to_path_buf(),
+        }
+    }
+
+    /// Decodes every frame of the main icon group as (bit count, RT_ICON id, image).
+    #[cfg(target_os = "windows")]
+    fn icon_frames(&self) -> PyResult<Vec<(u16, u32, image::DynamicImage)>> {
+        self.icon_group()?.into_iter().map(|(entry, frame)| {
+            let bit_count = u16::from_le_bytes([entry[6], entry[7]]);
+            let id = u16::from_le_bytes([entry[12], entry[13]]) as u32;
+            let img = decode_icon_frame(&frame)
+                .ok_or_else(|| PyErr::new::<IconError, _>(format!("Failed to decode icon frame {}", id)))?;
+            Ok((bit_count, id, img))
+        }).collect()
+    }
+
+    /// Raw main icon group: each 14-byte GRPICONDIRENTRY with its RT_ICON frame data.
+    #[cfg(target_os = "windows")]
+    fn icon_group(&self) -> PyResult<Vec<([u8; 14], Vec<u8>)>> {
+        let data = fs::read(&self.file_path)?;
+        let image = Image::parse(&data).map_err(|e| PyErr::new::<PEParseError, _>(format!("PE Parse error: {:?}", e)))?;
+        let root = image.resource_directory()
+            .ok_or_else(|| PyErr::new::<IconError, _>(format!("No resources in {}", self.file_path)))?
+            .root();
+
+        let group = root.get(ResourceEntryName::ID(RT_GROUP_ICON)).and_then(first_resource_data)
+            .ok_or_else(|| PyErr::new::<IconError, _>(format!("No icon group in {}", self.file_path)))?;
+        let icons = match root.get(ResourceEntryName::ID(RT_ICON)) {
+            Some(ResourceEntry::Table(table)) => table,
+            _ => return Err(PyErr::new::<IconError, _>(format!("No icon frames in {}", self.file_path))),
+        };
+
+        // GRPICONDIR: 6-byte header, then 14-byte entries ending in the RT_ICON id
+        let count = group.get(4..6).map_or(0, |c| u16::from_le_bytes([c[0], c[1]]) as usize);
+        let mut frames = Vec::new();
+        for entry in group.get(6..).unwrap_or_default().chunks_exact(14).take(count) {
+            let id = u16::from_le_bytes([entry[12], entry[13]]) as u32;
+            if let Some(frame) = icons.get(ResourceEntryName::ID(id)).and_then(first_resource_data) {
+                frames.push((entry.try_into().unwrap(), frame.to_vec()));
+            }
+        }
+        Ok(frames)
+    }
+
+    #[cfg(target_os = "windows")]
+    fn process_icon_windows(&self, data: &[u8], source: &str) -> PyResult<Vec<u8>> {
+        // Try decoding as image to see if we can generate a better ICO
+        if let Some(img) = decode_icon_image(data, source, self.max_icon_dimension)? {
+            // Generate multi-size ICO
+            // Windows prefers 256x256 PNG, others using BMP format for crispness at low res.
+            // Standard sizes: 256, 128, 64, 48, 32, 24, 16.
+            let mut sizes = match (&self.ico_sizes, self.icon_quality.as_str()) {
+                (Some(sizes), _) => sizes.clone(),
+                (None, "fast") => vec![128, 64, 48, 32, 24, 16],
+                (None, "high") => vec![256, 128, 96, 64, 48, 40, 32, 24, 20, 16],
+                (None, _) => vec![256, 128, 64, 48, 32, 24, 16],
+            };
+            let shell_sizes: &[u32] = if self.shell_icon_sizes { &SHELL_ICON_SIZES } else { &[] };
+            let missing: Vec<String> = shell_sizes.iter().filter(|s| !sizes.contains(s)).map(|s| s.to_string()).collect();
+            if !missing.is_empty() {
+                warn(&format!(
+                    "ICO sizes omit {}px, which Explorer and the taskbar use; adding them (set_shell_icon_sizes(False) to opt out)",
+                    missing.join(" and ")
+                ))?;
+                let added: Vec<u32> = shell_sizes.iter().copied().filter(|s| !sizes.contains(s)).collect();
+                sizes.extend(added);
+                sizes.sort_unstable_by(|a, b| b.cmp(a));
+            }
+            let sizes = native_icon_sizes(&sizes, img.width().max(img.height()), shell_sizes)?;
+            let mut frames = Vec::new();
+            
+            for size in sizes {
+                let resized = self.icon_frame(&img, size, "windows")?;
+                let width = resized.width();
+                let height = resized.height();
+                
+                if size >= 128 {
+                    // Use PNG for large icons (Vista+ support)
+                    let buf = resized.clone().into_rgba8().into_vec();
+                    let frame = match self.effective_png_compression() {
+                        Some(compression) => {
+                            let mut png = Vec::new();
+                            PngEncoder::new_with_quality(&mut png, compression, PngFilterType::Adaptive)
+                                .write_image(&buf, width, height, ExtendedColorType::Rgba8)
+                                .map_err(|e| PyErr::new::<IconError, _>(format!("Failed to encode {}px frame: {}", size, e)))?;
+                            IcoFrame::with_encoded(png, width, height, ExtendedColorType::Rgba8)
+                        }
+                        None => IcoFrame::as_png(&buf, width, height, ExtendedColorType::Rgba8),
+                    };
+                    frames.push(frame.map_err(|e| PyErr::new::<IconError, _>(format!("Failed to encode {}px frame: {}", size, e)))?);
+                } else {
+                    // Use manually constructed BMP for smaller icons to avoid artifacting.
+                    // IcoFrame only derives the directory entry's bits-per-pixel from the color
+                    // type and never looks at the encoded bytes, and `image` has no palette
+                    // color type, so L4 / L8 stand in for 4- and 8-bit paletted BMPs.
+                    let color_type = match self.icon_bit_depth {
+                        4 => ExtendedColorType::L4,
+                        8 => ExtendedColorType::L8,
+                        _ => ExtendedColorType::Rgba8,
+                    };
+                    let bmp_data = create_ico_bmp_data(&resized, width, height, self.icon_bit_depth)?;
+                    let frame = IcoFrame::with_encoded(bmp_data, width, height, color_type)
+                        .map_err(|e| PyErr::new::<IconError, _>(format!("Failed to encode {}px frame: {}", size, e)))?;
+                    frames.push(frame);
+                }
+            }
+            
+            if !frames.is_empty() {
+                let mut out_buffer = Vec::new();
+                let mut cursor = Cursor::new(&mut out_buffer);
+                let encoder = IcoEncoder::new(&mut cursor);
+                encoder.encode_images(&frames)
+                    .map_err(|e| PyErr::new::<IconError, _>(format!("Failed to encode ICO for {}: {}", source, e)))?;
+                // Windows silently shows a blank icon for a bad frame table, so catch it here
+                check_ico(&out_buffer)
+                    .map_err(|e| PyErr::new::<IconError, _>(format!("generated ICO for {} is malformed: {}", source, e)))?;
+                return Ok(out_buffer);
+            }
+        }
+
+        // Fallback: embed the file directly, but only if it already is an ICO.
+        // Anything else would produce a broken icon resource.
+        if !data.starts_with(&[0, 0, 1, 0]) {
+            return Err(PyErr::new::<IconError, _>(format!("Unsupported icon format: {}", source)));
+        }
+        Ok(data.to_vec())
+    }
+
+    /// Resizes `img` to fit a `size` x `size` square, centering it and filling the padding
+    /// with the configured background (transparent by default).
+    fn fit_icon(&self, img: &image::DynamicImage, size: u32) -> image::DynamicImage {
+        let filter = if self.icon_quality == "fast" { FilterType::Triangle } else { FilterType::Lanczos3 };
+        let resized = img.resize(size, size, filter);
+        if resized.width() == size && resized.height() == size {
+            return resized;
+        }
+
+        let (r, g, b, a) = self.icon_background.unwrap_or((0, 0, 0, 0));
+        let mut canvas = image::RgbaImage::from_pixel(size, size, image::Rgba([r, g, b, a]));
+        let x = (size - resized.width()) / 2;
+        let y = (size - resized.height()) / 2;
+        image::imageops::replace(&mut canvas, &resized.to_rgba8(), x as i64, y as i64);
+        image::DynamicImage::ImageRgba8(canvas)
+    }
+
+    /// The `size` x `size` frame: the pre-rendered file for that size if `set_icon_from_dir`
+    /// provided one (and no `target` platform icon replaces the set), else `img` fitted to it.
+    fn icon_frame(&self, img: &image::DynamicImage, size: u32, target: &str) -> PyResult<image::DynamicImage> {
+        match self.icon_frames.get(&size).filter(|_| !self.platform_icons.contains_key(target)) {
+            Some(path) => {
+                let data = fs::read(path).map_err(|e| PyErr::new::<IconError, _>(format!("Failed to read icon file {}: {}", path, e)))?;
+                decode_icon_image(&data, path, self.max_icon_dimension)?
+                    .ok_or_else(|| PyErr::new::<IconError, _>(format!("Unsupported icon format: {}", path)))
+            }
+            None => Ok(self.fit_icon(img, size)),
+        }
+    }
+
+    /// Explicit `set_png_compression`, else the icon quality preset's level. `None` keeps
+    /// the encoder defaults.
+    fn effective_png_compression(&self) -> Option<CompressionType> {
+        self.png_compression.or(match self.icon_quality.as_str() {
+            "fast" => Some(CompressionType::Fast),
+            "high" => Some(CompressionType::Best),
+            _ => None,
+        })
+    }
+
+    /// Encodes `img` as PNG at the effective compression level.
+    fn png_bytes(&self, img: &image::DynamicImage, source: &str) -> PyResult<Vec<u8>> {
+        let encode_error = |e: image::ImageError| PyErr::new::<IconError, _>(format!("Failed to encode icon {} as PNG: {}", source, e));
+        let mut png = Vec::new();
+        match self.effective_png_compression() {
+            Some(compression) => {
+                let rgba = img.to_rgba8();
+                PngEncoder::new_with_quality(&mut png, compression, PngFilterType::Adaptive)
+                    .write_image(&rgba, rgba.width(), rgba.height(), ExtendedColorType::Rgba8)
+                    .map_err(encode_error)?;
+            }
+            None => img.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png).map_err(encode_error)?,
+        }
+        Ok(png)
+    }
+
+    /// Decodes an icon and re-encodes it as PNG, optionally fitted to `size` x `size`.
+    fn encode_png(&self, data: &[u8], source: &str, size: Option<u32>) -> PyResult<Vec<u8>> {
+        let img = decode_icon_image(data, source, self.max_icon_dimension)?
+            .ok_or_else(|| PyErr::new::<IconError, _>(format!("Unsupported icon format: {}", source)))?;
+        let img = match size {
+            Some(size) => self.icon_frame(&img, size, "linux")?,
+            None => img,
+        };
+        self.png_bytes(&img, source)
+    }
+
+    /// Builds an `.icns` with a PNG for every slot in `ICNS_SLOTS`, each fitted to the slot's
+    /// exact square size. Finder shows a generic icon for a malformed slot without any error,
+    /// so sizes are never left to chance. Existing `.icns` data is passed through unchanged.
+    fn build_icns(&self, data: &[u8], source: &str) -> PyResult<Vec<u8>> {
+        if data.starts_with(b"icns") {
+            return Ok(data.to_vec());
+        }
+
+        let img = decode_icon_image(data, source, self.max_icon_dimension)?
+            .ok_or_else(|| PyErr::new::<IconError, _>(format!("Unsupported icon format: {}", source)))?;
+        let slots: Vec<(&[u8; 4], u32)> = ICNS_SLOTS.iter().copied()
+            .filter(|(_, size)| self.icon_quality != "fast" || *size <= 256)
+            .collect();
+        let source_dim = img.width().max(img.height());
+        let upscaled: Vec<String> = slots.iter()
+            .filter(|(_, size)| *size > source_dim)
+            .map(|(_, size)| size.to_string())
+            .collect();
+        if !upscaled.is_empty() {
+            warn(&format!("icon source is only {}px; upscaling for icns sizes: {}", source_dim, upscaled.join(", ")))?;
+        }
+
+        let mut body = Vec::new();
+        for (kind, size) in slots {
+            let png = self.png_bytes(&self.icon_frame(&img, size, "macos")?, source)?;
+            body.extend_from_slice(kind);
+            body.extend_from_slice(&(png.len() as u32 + 8).to_be_bytes());
+            body.extend_from_slice(&png);
+        }
+
+        let mut icns = Vec::with_capacity(body.len() + 8);
+        icns.extend_from_slice(b"icns");
+        icns.extend_from_slice(&(body.len() as u32 + 8).to_be_bytes());
+        icns.extend_from_slice(&body);
+        Ok(icns)
+    }
+
+    /// Renders `icon` as PNGs into `<data_home>/icons/hicolor/<size>x<size>/apps/<name>.png`.
+    fn install_theme_icons(&self, icon: &[u8], source: &str, name: &str, data_home: &Path, only_if_changed: bool) -> PyResult<bool> {
+        let theme_dir = data_home.join("icons").join("hicolor");
+
+        let mut changed = false;
+        for &size in self.theme_icon_sizes() {
+            let apps_dir = theme_dir.join(format!("{0}x{0}", size)).join("apps");
+            fs::create_dir_all(&apps_dir).map_err(|e| write_error(&apps_dir, e))?;
+
+            let png = self.encode_png(icon, source, Some(size))?;
+            changed |= write_if_changed(&apps_dir.join(format!("{}.png", name)), &png, only_if_changed)?;
+        }
+        Ok(changed)
+    }
+
+    /// Hicolor sizes for the icon quality preset, ascending.
+    fn theme_icon_sizes(&self) -> &'static [u32] {
+        match self.icon_quality.as_str() {
+            "fast" => &THEME_ICON_SIZES[..5],
+            "high" => &[16, 22, 24, 32, 48, 64, 96, 128, 256, 512],
+            _ => &THEME_ICON_SIZES,
+        }
+    }
+
+    /// Populates an AppDir: themed icons under `usr/share`, a `<name>.png` symlink to the
+    /// largest one, and `.DirIcon`, which file managers show for the built AppImage.
+    fn install_appdir_icons(&self, appdir: &Path, icon: &[u8], source: &str, name: &str, only_if_changed: bool) -> PyResult<bool> {
+        let mut changed = self.install_theme_icons(icon, source, name, &appdir.join("usr/share"), only_if_changed)?;
+
+        let link = appdir.join(format!("{}.png", name));
+        let largest = self.theme_icon_sizes().last().copied().unwrap_or(256);
+        let target = Path::new("usr/share/icons/hicolor").join(format!("{0}x{0}", largest)).join("apps").join(format!("{}.png", name));
+        if fs::read_link(&link).ok().as_deref() != Some(target.as_path()) {
+            if fs::symlink_metadata(&link).is_ok() {
+                fs::remove_file(&link).map_err(|e| write_error(&link, e))?;
+            }
+            symlink(&target, &link).map_err(|e| write_error(&link, e))?;
+            changed = true;
+        }
+
+        let dir_icon = self.encode_png(icon, source, Some(256))?;
+        changed |= write_if_changed(&appdir.join(".DirIcon"), &dir_icon, only_if_changed)?;
+        Ok(changed)
+    }
+
+    /// Electron keeps the version its UI shows in `resources/app.asar` (or an unpacked
+    /// `resources/app/package.json`), which native metadata edits don't touch. Warns when
+    /// such a layout sits next to the binary, or updates the unpacked package.json if asked.
+    fn sync_electron_package(&self, only_if_changed: bool) -> PyResult<bool> {
+        let resources = Path::new(&self.file_path).parent().unwrap_or(Path::new(".")).join("resources");
+        let package_json = resources.join("app").join("package.json");
+        let is_electron = ["app.asar", "electron.asar"].iter().any(|f| resources.join(f).is_file()) || package_json.is_file();
+        if !is_electron {
+            return Ok(false);
+        }
+
+        let version = self.product_version.as_ref().or(self.version.as_ref());
+        if !self.update_electron_package || !package_json.is_file() {
+            warn(&format!(
+                "{} looks like an Electron app; metaedit only updates the native metadata, not the version in resources/app.asar",
+                self.file_path
+            ))?;
+            return Ok(false);
+        }
+        let Some(version) = version else { return Ok(false) };
+
+        // package.json versions are semver: three numeric fields plus an optional label
+        let (mut parts, label) = parse_version(version.clone())?;
+        parts.resize(3, 0);
+        let mut semver = format!("{}.{}.{}", parts[0], parts[1], parts[2]);
+        if let Some(label) = label {
+            semver.push('-');
+            semver.push_str(&label);
+        }
+
+        let json = fs::read_to_string(&package_json)?;
+        let updated = set_json_string_field(&json, "version", &semver).ok_or_else(|| PyErr::new::<MetaEditError, _>(format!(
+            "{} has no top-level string \"version\" field", package_json.display()
+        )))?;
+        write_if_changed(&package_json, updated.as_bytes(), only_if_changed)
+    }
+
+    /// Routes a metadata dict entry: "icon" and "version" have their own fields, anything
+    /// else is a version string.
+    fn set_metadata(&mut self, key: String, value: String) {
+        match key.as_str() {
+            "icon" => {
+                self.icon_path = Some(value);
+                self.icon_bytes = None;
+                self.icon_frames.clear();
+            }
+            "version" => self.version = Some(value),
+            _ => {
+                self.strings.insert(key, value);
+            }
+        }
+    }
+
+    /// The icon path used for `target`: its platform icon, else `icon_path`.
+    fn icon_path_for(&self, target: &str) -> Option<&String> {
+        self.platform_icons.get(target).or(self.icon_path.as_ref())
+    }
+
+    /// The icon's bytes and a label for messages for `target`: its platform icon, else the
+    /// in-memory icon or `icon_path`.
+    fn icon_source_for(&self, target: &str) -> PyResult<Option<(Vec<u8>, String)>> {
+        if let Some(data) = self.icon_bytes.as_ref().filter(|_| !self.platform_icons.contains_key(target)) {
+            return Ok(Some((data.clone(), "<icon bytes>".to_string())));
+        }
+        match self.icon_path_for(target) {
+            Some(icon_path) => {
+                let data = fs::read(icon_path).map_err(|e| PyErr::new::<IconError, _>(format!("Failed to read icon file {}: {}", icon_path, e)))?;
+                Ok(Some((data, icon_path.clone())))
+            }
+            None if self.use_default_icon => Ok(Some((default_icon_png()?, "<default icon>".to_string()))),
+            None => Ok(None),
+        }
+    }
+
+    /// Language entry of a resource (an RT_STRING block, a bitmap) to read or edit: the
+    /// configured locale's, else the first.
+    #[cfg(target_os = "windows")]
+    fn resource_language(&self, langs: &ResourceTable) -> Option<ResourceEntryName> {
+        if let Some(lcid) = self.locale.as_deref().and_then(locale_lcid) {
+            let name = ResourceEntryName::ID(lcid as u32);
+            if langs.entries.contains_key(&name) {
+                return Some(name);
+            }
+        }
+        langs.entries.keys().next().cloned()
+    }
+
+    /// Writes the queued `set_string_entry` edits into the RT_STRING blocks of `resources`.
+    #[cfg(target_os = "windows")]
+    fn apply_string_entries(&self, resources: &mut ResourceDirectory) -> PyResult<()> {
+        let root = resources.root_mut();
+        let blocks = match root.entries.entry(ResourceEntryName::ID(RT_STRING)).or_insert_with(|| ResourceEntry::Table(ResourceTable::default())) {
+            ResourceEntry::Table(blocks) => blocks,
+            ResourceEntry::Data(_) => return Err(PyErr::new::<PEParseError, _>("Malformed RT_STRING resource directory")),
+        };
+        let lcid = self.locale.as_deref().and_then(locale_lcid).unwrap_or(0x0409) as u32;
+
+        for (&(block_id, index), value) in &self.string_entries {
+            let langs = match blocks.entries.entry(ResourceEntryName::ID(block_id)).or_insert_with(|| {
+                let mut langs = ResourceTable::default();
+                langs.insert(ResourceEntryName::ID(lcid), ResourceEntry::Data(encode_string_block(&vec![String::new(); 16]).into()));
+                ResourceEntry::Table(langs)
+            }) {
+                ResourceEntry::Table(langs) => langs,
+                ResourceEntry::Data(_) => return Err(PyErr::new::<PEParseError, _>("Malformed RT_STRING resource directory")),
+            };
+
+            let lang = match self.resource_language(langs) {
+                Some(lang) => lang,
+                None => continue,
+            };
+            if let Some(ResourceEntry::Data(block)) = langs.get_mut(lang) {
+                let mut strings = decode_string_block(block.data())?;
+                strings[index as usize] = value.clone();
+                block.set_data(encode_string_block(&strings));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the queued `set_bitmap` images into RT_BITMAP resources as DIBs.
+    #[cfg(target_os = "windows")]
+    fn apply_bitmaps(&self, resources: &mut ResourceDirectory) -> PyResult<()> {
+        let root = resources.root_mut();
+        let bitmaps = match root.entries.entry(ResourceEntryName::ID(RT_BITMAP)).or_insert_with(|| ResourceEntry::Table(ResourceTable::default())) {
+            ResourceEntry::Table(bitmaps) => bitmaps,
+            ResourceEntry::Data(_) => return Err(PyErr::new::<PEParseError, _>("Malformed RT_BITMAP resource directory")),
+        };
+        // New ids follow the language the file's other bitmaps use
+        let default_lang = bitmaps.entries.values()
+            .find_map(|entry| match entry {
+                ResourceEntry::Table(langs) => self.resource_language(langs),
+                ResourceEntry::Data(_) => None,
+            })
+            .unwrap_or(ResourceEntryName::ID(self.locale.as_deref().and_then(locale_lcid).unwrap_or(0x0409) as u32));
+
+        for (&id, image_path) in &self.bitmaps {
+            let data = fs::read(image_path).map_err(|e| PyErr::new::<IconError, _>(format!("Failed to read bitmap {}: {}", image_path, e)))?;
+            let img = decode_icon_image(&data, image_path, self.max_icon_dimension)?
+                .ok_or_else(|| PyErr::new::<IconError, _>(format!("Unsupported bitmap format: {}", image_path)))?;
+            let dib = encode_dib(&img);
+
+            let langs = match bitmaps.entries.entry(ResourceEntryName::ID(id as u32)).or_insert_with(|| ResourceEntry::Table(ResourceTable::default())) {
+                ResourceEntry::Table(langs) => langs,
+                ResourceEntry::Data(_) => return Err(PyErr::new::<PEParseError, _>("Malformed RT_BITMAP resource directory")),
+            };
+            let lang = self.resource_language(langs).unwrap_or_else(|| default_lang.clone());
+            langs.insert(lang, ResourceEntry::Data(dib.into()));
+        }
+        Ok(())
+    }
+
+    /// Parses the file's VS_VERSIONINFO resource, if any.
+    #[cfg(target_os = "windows")]
+    fn read_version_info(&self) -> PyResult<Option<VersionInfo>> {
+        let data = fs::read(&self.file_path)?;
+        let image = Image::parse(&data).map_err(|e| PyErr::new::<PEParseError, _>(format!("PE Parse error: {:?}", e)))?;
+        match image.resource_directory() {
+            Some(resources) => resources.get_version_info().map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to get version info: {:?}", e))),
+            None => Ok(None),
+        }
+    }
+
+    /// The embedded RT_MANIFEST text, if any.
+    #[cfg(target_os = "windows")]
+    fn read_manifest(&self) -> PyResult<Option<String>> {
+        let data = fs::read(&self.file_path)?;
+        let image = Image::parse(&data).map_err(|e| PyErr::new::<PEParseError, _>(format!("PE Parse error: {:?}", e)))?;
+        match image.resource_directory() {
+            Some(resources) => resources.get_manifest().map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to get manifest: {:?}", e))),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes the `.lnk` requested with `set_create_shortcut`. The icon is the configured
+    /// `.ico` file, else the binary's own (which `apply_windows` just set).
+    #[cfg(target_os = "windows")]
+    fn write_shortcut(&self, only_if_changed: bool) -> PyResult<bool> {
+        if !self.create_shortcut {
+            return Ok(false);
+        }
+        let path = Path::new(&self.file_path);
+        let lnk_path = match &self.shortcut_path {
+            Some(lnk) => std::path::PathBuf::from(lnk),
+            None => path.with_extension("lnk"),
+        };
+        // .lnk targets are absolute; drop the \\?\ prefix canonicalize adds
+        let absolute = |p: &Path| {
+            let p = fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf()).display().to_string();
+            p.strip_prefix(r"\\?\").map(str::to_string).unwrap_or(p)
+        };
+        let target = absolute(path);
+        let icon = match self.icon_path_for("windows") {
+            Some(icon) if icon.to_lowercase().ends_with(".ico") && Path::new(icon).is_file() => absolute(Path::new(icon)),
+            _ => target.clone(),
+        };
+        let description = self.strings.get("FileDescription").or(self.strings.get("ProductName"));
+        let lnk = shell_link(&target, description.map(String::as_str), &icon);
+        write_if_changed(&lnk_path, &lnk, only_if_changed)
+    }
+
+    /// The VERSIONINFO string entries this editor will write, in write order. An explicit
+    /// `set_string("FileVersion" / "ProductVersion", ...)` replaces the string derived from
+    /// the version, so each key appears once.
+    fn version_strings(&self) -> Vec<(String, String)> {
+        let mut entries = Vec::new();
+        if let Some(v) = self.version.as_ref().filter(|_| !self.strings.contains_key("FileVersion")) {
+            entries.push(("FileVersion".to_string(), v.clone()));
+        }
+        if let Some(v) = self.product_version.as_ref().or(self.version.as_ref()).filter(|_| !self.strings.contains_key("ProductVersion")) {
+            entries.push(("ProductVersion".to_string(), v.clone()));
+        }
+        for (k, v) in &self.strings {
+            entries.push((k.clone(), v.clone()));
+        }
+        if let Some(id) = &self.build_id {
+            entries.push(("BuildID".to_string(), id.clone()));
+        }
+        entries
+    }
+
+    /// Renders the `.rc` script for `export_rc`.
+    fn render_rc(&self, icon_file: Option<&Path>) -> String {
+        let mut rc = String::from("#include <winver.h>\n\n");
+        if let Some(icon_file) = icon_file {
+            let icon_file = fs::canonicalize(icon_file).unwrap_or_else(|_| icon_file.to_path_buf());
+            rc.push_str(&format!("1 ICON \"{}\"\n\n", rc_escape(&icon_file.to_string_lossy())));
+        }
+
+        let file_version = self.version.as_deref().map(rc_version_quad).unwrap_or_else(|| "0,0,0,0".to_string());
+        let product_version = self.product_version.as_deref().or(self.version.as_deref())
+            .map(rc_version_quad).unwrap_or_else(|| "0,0,0,0".to_string());
+        let file_type = self.file_type.unwrap_or(VFT_APP);
+        let file_subtype = if file_type == VFT_DRV { VFT2_DRV_SYSTEM } else { 0 };
+
+        rc.push_str("VS_VERSION_INFO VERSIONINFO\n");
+        rc.push_str(&format!(" FILEVERSION {}\n", file_version));
+        rc.push_str(&format!(" PRODUCTVERSION {}\n", product_version));
+        rc.push_str(" FILEFLAGSMASK VS_FFI_FILEFLAGSMASK\n");
+        rc.push_str(" FILEFLAGS 0x0L\n");
+        rc.push_str(" FILEOS VOS_NT_WINDOWS32\n");
+        rc.push_str(&format!(" FILETYPE 0x{:x}L\n", file_type));
+        rc.push_str(&format!(" FILESUBTYPE 0x{:x}L\n", file_subtype));
+        rc.push_str("BEGIN\n    BLOCK \"StringFileInfo\"\n    BEGIN\n");
+
+        let key = self.string_table_key();
+        rc.push_str(&format!("        BLOCK \"{}\"\n        BEGIN\n", key));
+        let mut strings = self.version_strings();
+        // `strings` is a HashMap; sort so the script is stable across runs
+        strings.sort();
+        for (k, v) in strings {
+            rc.push_str(&format!("            VALUE \"{}\", \"{}\"\n", rc_escape(&k), rc_escape(&v)));
+        }
+        rc.push_str("        END\n    END\n");
+
+        rc.push_str("    BLOCK \"VarFileInfo\"\n    BEGIN\n");
+        let codepage = u16::from_str_radix(&key[4..], 16).unwrap_or(1200);
+        rc.push_str(&format!("        VALUE \"Translation\", 0x{}, {}\n", &key[..4], codepage));
+        rc.push_str("    END\nEND\n");
+        rc
+    }
+
+    /// ICO bytes for an exported `.rc`: the same multi-size icon `apply()` would embed.
+    #[cfg(target_os = "windows")]
+    fn rc_icon_data(&self, data: &[u8], source: &str) -> PyResult<Vec<u8>> {
+        self.process_icon_windows(data, source)
+    }
+
+    /// ICO bytes for an exported `.rc`. Conversion needs the Windows icon pipeline, so only
+    /// icons that are already ICO data can be exported elsewhere.
+    #[cfg(not(target_os = "windows"))]
+    fn rc_icon_data(&self, data: &[u8], source: &str) -> PyResult<Vec<u8>> {
+        if data.starts_with(&[0, 0, 1, 0]) {
+            return Ok(data.to_vec());
+        }
+        Err(PyErr::new::<IconError, _>(format!("export_rc needs an .ico icon on this platform: {}", source)))
+    }
+
+    /// The `<Include>` body for `export_wix_fragment`.
+    fn render_wix(&self) -> PyResult<String> {
+        let mut defines = Vec::new();
+        let name = self.strings.get("ProductName").cloned()
+            .or_else(|| Path::new(&self.file_path).file_stem().map(|s| s.to_string_lossy().into_owned()));
+        if let Some(name) = name {
+            defines.push(("ProductName", name));
+        }
+        if let Some(version) = self.product_version.as_deref().or(self.version.as_deref()) {
+            defines.push(("ProductVersion", wix_version(version)?));
+        }
+        if let Some(company) = self.strings.get("CompanyName") {
+            defines.push(("Manufacturer", company.clone()));
+        }
+
+        let mut wxi = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<Include>\n");
+        for (key, value) in defines {
+            // Preprocessor values are raw text: no entity escaping, and `$(` would be expanded
+            if value.contains("?>") || value.contains("$(") || value.contains('"') {
+                return Err(PyErr::new::<MetaEditError, _>(format!(
+                    "{} '{}' can't be written as a WiX define (contains '\"', '?>' or '$(')", key, value
+                )));
+            }
+            wxi.push_str(&format!("  <?define {} = \"{}\" ?>\n", key, value));
+        }
+        wxi.push_str("</Include>\n");
+        Ok(wxi)
+    }
+
+    /// Key of the string table to create: the one from `set_string_table_key`, else
+    /// `<LCID><codepage>` for the configured locale.
+    fn string_table_key(&self) -> String {
+        if let Some(key) = &self.table_key {
+            return key.clone();
+        }
+        let lcid = self.locale.as_deref().and_then(locale_lcid).unwrap_or(0x0409);
+        format!("{:04x}04b0", lcid)
+    }
+
+    /// Index of the string table edits should land in: the one with the explicit key, the
+    /// one matching the configured locale, or else the existing table whatever its language
+    /// (a German-only binary keeps its 040704b0 table). None means a new table is needed.
+    #[cfg(target_os = "windows")]
+    fn string_table_index(&self, tables: &[VersionStringTable]) -> Option<usize> {
+        if let Some(key) = &self.table_key {
+            return tables.iter().position(|t| t.key.eq_ignore_ascii_case(key));
+        }
+        match self.locale.as_deref().and_then(locale_lcid) {
+            Some(lcid) => {
+                let prefix = format!("{:04x}", lcid);
+                tables.iter().position(|t| t.key.to_lowercase().starts_with(&prefix))
+            }
+            None => if tables.is_empty() { None } else { Some(0) },
+        }
+    }
+
+    /// (offset, current value, value with the configured flags applied) of the COFF
+    /// Characteristics field, or None if the header can't be found.
+    #[cfg(target_os = "windows")]
+    fn characteristics_update(&self, data: &[u8]) -> Option<(usize, u16, u16)> {
+        let offset = coff_characteristics_offset(data)?;
+        let old = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let new = self.characteristics.iter().fold(old, |value, (bit, enabled)| {
+            if *enabled { value | bit } else { value & !bit }
+        });
+        Some((offset, old, new))
+    }
+
+    /// True when the PE already carries every requested value, so a write can be skipped.
+    #[cfg(target_os = "windows")]
+    fn windows_is_up_to_date(&self, resources: &ResourceDirectory) -> PyResult<bool> {
+        if let Some((icon, source)) = self.icon_source_for("windows")? {
+            // The icon matches if every generated frame is already embedded as an RT_ICON
+            let icon_data = self.process_icon_windows(&icon, &source)?;
+            let mut existing = Vec::new();
+            if let Some(ResourceEntry::Table(table)) = resources.root().get(ResourceEntryName::ID(RT_ICON)) {
+                collect_resource_data(table, &mut existing);
+            }
+            if !ico_frame_data(&icon_data).iter().all(|frame| existing.contains(frame)) {
+                return Ok(false);
+            }
+        }
+
+        if !self.strings.is_empty() || self.version.is_some() || self.product_version.is_some() || self.file_type.is_some() || self.build_id.is_some() {
+            let version_info = match resources.get_version_info().map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to get version info: {:?}", e)))? {
+                Some(version_info) => version_info,
+                None => return Ok(false),
+            };
+            if let Some(vft) = self.file_type {
+                if version_info.info.file_type != vft {
+                    return Ok(false);
+                }
+            }
+            if let Some(v) = &self.version {
+                if version_info.info.file_version != fixed_version(v)? {
+                    return Ok(false);
+                }
+            }
+            if let Some(v) = self.product_version.as_ref().or(self.version.as_ref()) {
+                if version_info.info.product_version != fixed_version(v)? {
+                    return Ok(false);
+                }
+            }
+            let table = match self.string_table_index(&version_info.strings) {
+                Some(index) => &version_info.strings[index],
+                None => return Ok(self.version_strings().is_empty()),
+            };
+            for (k, v) in self.version_strings() {
+                if table.strings.get(&k) != Some(&v) {
+                    return Ok(false);
+                }
+            }
+        }
+
+        if !self.string_entries.is_empty() {
+            let mut edited = resources.clone();
+            self.apply_string_entries(&mut edited)?;
+            if resource_leaf_data(edited.root(), RT_STRING) != resource_leaf_data(resources.root(), RT_STRING) {
+                return Ok(false);
+            }
+        }
+
+        if !self.bitmaps.is_empty() {
+            let mut edited = resources.clone();
+            self.apply_bitmaps(&mut edited)?;
+            if resource_leaf_data(edited.root(), RT_BITMAP) != resource_leaf_data(resources.root(), RT_BITMAP) {
+                return Ok(false);
+            }
+        }
+
+        if let Some(mode) = &self.dpi_awareness {
+            let manifest = resources.get_manifest().map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to get manifest: {:?}", e)))?;
+            if manifest.as_deref() != Some(set_manifest_dpi_awareness(manifest.as_deref(), mode)?.as_str()) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Applies the configured icon, version info, string table and manifest edits to
+    /// `resources` in memory. Shared by `apply_windows` and `plan_resources`.
+    #[cfg(target_os = "windows")]
+    fn edit_resources(&self, resources: &mut ResourceDirectory) -> PyResult<()> {
+        // 1. Set Icon
+        if let Some((icon, source)) = self.icon_source_for("windows")? {
+            let icon_data = self.process_icon_windows(&icon, &source)?;
+            resources.set_main_icon(icon_data).map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to set icon: {:?}", e)))?;
+        }
+
+        // 2. Set Version Strings
+        if !self.strings.is_empty() || self.version.is_some() || self.product_version.is_some() || self.file_type.is_some() || self.build_id.is_some() {
+            let mut version_info = resources.get_version_info().map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to get version info: {:?}", e)))?.unwrap_or_default();
+            
+            // FixedFileInfo carries the numeric part; any "-beta" label only goes in the strings
+            if let Some(v) = &self.version {
+                version_info.info.file_version = fixed_version(v)?;
+            }
+            if let Some(v) = self.product_version.as_ref().or(self.version.as_ref()) {
+                version_info.info.product_version = fixed_version(v)?;
+            }
+
+            if let Some(vft) = self.file_type {
+                version_info.info.file_type = vft;
+                // Only drivers carry a meaningful subtype; everything else is VFT2_UNKNOWN
+                version_info.info.file_subtype = if vft == VFT_DRV { VFT2_DRV_SYSTEM } else { 0 };
+            }
+            
+            if let Some(index) = self.string_table_index(&version_info.strings) {
+                let table = &mut version_info.strings[index];
+                for (k, v) in self.version_strings() {
+                    table.strings.insert(k, v);
+                }
+            } else {
+                // Only reached with no tables at all, or none in the requested locale: create
+                // one keyed by the locale (040904b0, US English / Unicode, by default)
+                let strings = self.version_strings().into_iter().collect();
+                version_info.strings.push(VersionStringTable {
+                    key: self.string_table_key(),
+                    strings,
+                });
+            }
+            
+            resources.set_version_info(&version_info).map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to set version info: {:?}", e)))?;
+        }
+
+        // 3. RT_STRING entries
+        if !self.string_entries.is_empty() {
+            self.apply_string_entries(resources)?;
+        }
+
+        // 4. RT_BITMAP images
+        if !self.bitmaps.is_empty() {
+            self.apply_bitmaps(resources)?;
+        }
+
+        // 5. DPI awareness lives in the application manifest
+        if let Some(mode) = &self.dpi_awareness {
+            let manifest = resources.get_manifest().map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to get manifest: {:?}", e)))?;
+            let manifest = set_manifest_dpi_awareness(manifest.as_deref(), mode)?;
+            resources.set_manifest(&manifest).map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to set manifest: {:?}", e)))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    fn apply_windows(&self, only_if_changed: bool) -> PyResult<bool> {
+        let data = fs::read(&self.file_path)?;
+        let image = Image::parse(&data).map_err(|e| PyErr::new::<PEParseError, _>(format!("PE Parse error: {:?}", e)))?;
+        let resources = image.resource_directory().cloned().unwrap_or_default();
+
+        if only_if_changed && self.windows_is_up_to_date(&resources)?
+            && self.characteristics_update(&data).is_some_and(|(_, old, new)| old == new)
+        {
+            return Ok(false);
+        }
+        ensure_not_in_use(Path::new(&self.file_path))?;
+
+        let final_data = self.edited_pe(&data, image, resources)?;
+        if final_data != data {
+            save_pe_backup(Path::new(&self.file_path))?;
+        }
+        write_if_changed(Path::new(&self.file_path), &final_data, only_if_changed)
+    }
+
+    /// The bytes `apply_windows` would write: `data` (parsed as `image`) with `resources`
+    /// edited and put back, header fields patched and the signature handled per the signed policy.
+    #[cfg(target_os = "windows")]
+    fn edited_pe(&self, data: &[u8], mut image: Image<'_>, mut resources: ResourceDirectory) -> PyResult<Vec<u8>> {
+        let policy = SIGNED_POLICY.load(Ordering::Relaxed);
+        let signed = pe_signed(data, &self.file_path)?;
+        if signed && policy == SIGNED_REFUSE {
+            return Err(PyErr::new::<MetaEditError, _>(format!(
+                "refusing to edit signed file {} (signed policy is \"refuse\")", self.file_path
+            )));
+        }
+        
+        info(&format!("Rust (Windows): Patching PE Resources in {}", self.file_path));
+        
+        self.edit_resources(&mut resources)?;
+
+        let expected = resource_leaves(resources.root());
+        let strings_only = self.icon_path_for("windows").is_none() && self.icon_bytes.is_none() && !self.use_default_icon
+            && self.string_entries.is_empty() && self.bitmaps.is_empty() && self.dpi_awareness.is_none();
+        let patched = if strings_only {
+            resources.root().get(ResourceEntryName::ID(RT_VERSION)).and_then(first_resource_data)
+                .and_then(|blob| patch_version_resource(data, blob))
+        } else {
+            None
+        };
+        let mut final_data = match patched {
+            // Only RT_VERSION changed and the new blob fits in place: leave every other byte alone
+            Some(patched) => patched,
+            None => {
+                image.set_resource_directory(resources).map_err(|e| PyErr::new::<PEParseError, _>(format!("Failed to set resources: {:?}", e)))?;
+                let mut rewritten = image.data().to_vec();
+                // The security directory and everything after it are indexed through this count
+                let rva_count = |data: &[u8]| optional_header_fields(data)
+                    .and_then(|fields| fields.into_iter().find(|(name, _)| *name == "number_of_rva_and_sizes"));
+                if rva_count(&rewritten) != rva_count(data) {
+                    return Err(PyErr::new::<PEParseError, _>(format!(
+                        "Rewriting resources changed NumberOfRvaAndSizes of {}; refusing to write a corrupted header",
+                        self.file_path
+                    )));
+                }
+                if fix_resource_data_directory(&mut rewritten) {
+                    warn(&format!("{}: resource data directory did not point at the rewritten .rsrc; corrected it", self.file_path))?;
+                }
+                if !restore_dos_stub(data, &mut rewritten) {
+                    warn(&format!("{}: the rewrite moved the PE header; the DOS stub and Rich header could not be preserved", self.file_path))?;
+                }
+                rewritten
+            }
+        };
+
+        // Round-trip check: every resource we set must still resolve in the rewritten image
+        verify_resource_round_trip(&final_data, &expected)?;
+
+        let (offset, _, characteristics) = self.characteristics_update(&final_data)
+            .ok_or_else(|| PyErr::new::<PEParseError, _>(format!("Failed to locate the COFF header in {}", self.file_path)))?;
+        final_data[offset..offset + 2].copy_from_slice(&characteristics.to_le_bytes());
+        
+        // Strip signature to prevent corruption errors (hash mismatch), unless asked to keep it
+        if policy == SIGNED_KEEP {
+            if signed {
+                warn(&format!(
+                    "{} keeps its original signature, which no longer matches the edited file; re-sign it before distribution",
+                    self.file_path
+                ))?;
+            }
+        } else {
+            strip_pe_signature(&mut final_data);
+        }
+        update_pe_checksum(&mut final_data);
+
+        // The header patches above run after the round-trip check; re-parse the exact bytes
+        // about to be written so a working binary is never replaced by a corrupt one
+        if Image::parse(final_data.as_slice()).is_err() {
+            return Err(PyErr::new::<PEParseError, _>("edit produced an invalid PE; aborting write"));
+        }
+        Ok(final_data)
+    }
+
+    fn apply_macos(&self, only_if_changed: bool) -> PyResult<bool> {
+        let path = Path::new(&self.file_path);
+        let bundle_path = self.target_output("macos");
+
+        let contents = bundle_path.join("Contents");
+        let macos_dir = contents.join("MacOS");
+        let resources_dir = contents.join("Resources");
+        
+        fs::create_dir_all(&macos_dir).map_err(|e| write_error(&macos_dir, e))?;
+        fs::create_dir_all(&resources_dir).map_err(|e| write_error(&resources_dir, e))?;
+
+        let mut changed = false;
+
+        if path.is_file() {
+            let target_bin = macos_dir.join(path.file_name().unwrap());
+            changed |= place_binary(path, &target_bin, &self.binary_placement, only_if_changed)?;
+            // Launch Services won't start a bundle whose executable lacks +x
+            changed |= ensure_executable(&target_bin)?;
+        }
+
+        // An existing bundle keeps its executable; naming it after "Foo.app" would break launch
+        let executable = if path.is_file() {
+            path.file_name().unwrap().to_string_lossy().into_owned()
+        } else {
+            bundle_executable(&bundle_path)?
+        };
+
+        // IndexMap keeps insertion order, so the output is stable across runs
+        let mut dict = IndexMap::new();
+        dict.insert("CFBundleExecutable".to_string(), PlistEntry::String(executable));
+        let dictionary_version = self.info_dictionary_version.as_deref().unwrap_or(DEFAULT_INFO_DICTIONARY_VERSION);
+        dict.insert("CFBundleInfoDictionaryVersion".to_string(), PlistEntry::String(dictionary_version.to_string()));
+        dict.insert("CFBundlePackageType".to_string(), PlistEntry::String("APPL".to_string()));
+        let region = self.development_region.as_ref().or(self.locale.as_ref()).map_or("en", |r| r.as_str());
+        let icon = self.bundle_icon()?;
+        self.insert_bundle_keys(&mut dict, Some(region), icon.is_some());
+
+        let plist_path = contents.join("Info.plist");
+        changed |= write_if_changed(&plist_path, plist_xml(&dict).as_bytes(), only_if_changed)?;
+        changed |= self.write_bundle_resources(&resources_dir, icon, only_if_changed)?;
+
+        if let Some(staging) = &self.dmg_staging {
+            changed |= stage_dmg(&bundle_path, Path::new(staging), only_if_changed)?;
+        }
+
+        Ok(changed)
+    }
+
+    /// The icon to put in a bundle, or None (with a warning if the configured file is gone).
+    fn bundle_icon(&self) -> PyResult<Option<(Vec<u8>, String)>> {
+        match self.icon_path_for("macos") {
+            Some(icon) if !Path::new(icon).exists() => {
+                warn(&format!("icon not found, bundle will have no icon: {}", icon))?;
+                Ok(None)
+            }
+            _ => self.icon_source_for("macos"),
+        }
+    }
+
+    /// Adds the configured Info.plist keys to `dict`: versions, name, build id, the
+    /// development region if given, CFBundleIconFile when there's an icon, then typed keys.
+    fn insert_bundle_keys(&self, dict: &mut IndexMap<String, PlistEntry>, region: Option<&str>, has_icon: bool) {
+        // CFBundleShortVersionString is the user-facing (product) version
+        if let Some(ver) = self.product_version.as_ref().or(self.version.as_ref()) {
+            dict.insert("CFBundleShortVersionString".to_string(), PlistEntry::String(ver.clone()));
+        }
+        if let Some(ver) = &self.version {
+            dict.insert("CFBundleVersion".to_string(), PlistEntry::String(ver.clone()));
+        }
+
+        if let Some(title) = self.strings.get("ProductName") {
+            dict.insert("CFBundleName".to_string(), PlistEntry::String(title.clone()));
+        }
+        if let Some(copyright) = self.strings.get("LegalCopyright") {
+            dict.insert("NSHumanReadableCopyright".to_string(), PlistEntry::String(copyright.clone()));
+        }
+        if let Some(category) = &self.app_category {
+            dict.insert("LSApplicationCategoryType".to_string(), PlistEntry::String(category.clone()));
+        }
+        if let Some(id) = &self.build_id {
+            dict.insert("BuildID".to_string(), PlistEntry::String(id.clone()));
+        }
+
+        if let Some(region) = region {
+            dict.insert("CFBundleDevelopmentRegion".to_string(), PlistEntry::String(region.to_string()));
+        }
+
+        // Without CFBundleIconFile, Finder only finds the icon if it happens to use a default name
+        if has_icon {
+            let icon_name = self.icon_name.as_deref().unwrap_or("app");
+            dict.insert("CFBundleIconFile".to_string(), PlistEntry::String(icon_name.to_string()));
+        }
+
+        // Typed keys go last so they can override the generated ones
+        for (key, entry) in &self.plist_values {
+            dict.insert(key.clone(), entry.clone());
+        }
+    }
+
+    /// Writes the icon's `.icns` and the localized `InfoPlist.strings` into `Contents/Resources`.
+    fn write_bundle_resources(&self, resources_dir: &Path, icon: Option<(Vec<u8>, String)>, only_if_changed: bool) -> PyResult<bool> {
+        let mut changed = false;
+        if let Some((icon, source)) = icon {
+            let icon_name = self.icon_name.as_deref().unwrap_or("app");
+            let icon_dest = resources_dir.join(format!("{}.icns", icon_name));
+            changed |= write_if_changed(&icon_dest, &self.build_icns(&icon, &source)?, only_if_changed)?;
+        }
+
+        for (region, (name, display_name)) in &self.localized_names {
+            let lproj = resources_dir.join(format!("{}.lproj", region));
+            fs::create_dir_all(&lproj).map_err(|e| write_error(&lproj, e))?;
+            let strings = format!(
+                "\"CFBundleName\" = \"{}\";\n\"CFBundleDisplayName\" = \"{}\";\n",
+                strings_escape(name),
+                strings_escape(display_name.as_deref().unwrap_or(name)),
+            );
+            changed |= write_if_changed(&lproj.join("InfoPlist.strings"), strings.as_bytes(), only_if_changed)?;
+        }
+        Ok(changed)
+    }
+
+    fn apply_linux(&self, only_if_changed: bool) -> PyResult<bool> {
+        let path = Path::new(&self.file_path);
+        let parent = path.parent().unwrap_or(Path::new("."));
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let desktop_path = self.desktop_path();
+        if let Some(dir) = &self.output_dir {
+            fs::create_dir_all(dir).map_err(|e| write_error(Path::new(dir), e))?;
+        }
+
+        // (key, value, set by the user); when merging into an existing file, only user-set
+        // keys replace what's there, so a hand-edited Exec or Terminal survives
+        let mut entries: Vec<(String, String, bool)> = Vec::new();
+        let mut entry = |key: &str, value: String, user_set: bool| entries.push((key.to_string(), value, user_set));
+        entry("Type", "Application".to_string(), false);
+        entry("Name", self.strings.get("ProductName").cloned().unwrap_or_else(|| name.to_string()), self.strings.contains_key("ProductName"));
+        if let (Some(locale), Some(title)) = (&self.locale, self.strings.get("ProductName")) {
+            // Desktop entries use POSIX-style locale tags: Name[de] / Name[de_DE]
+            entry(&format!("Name[{}]", locale.replace('-', "_")), title.clone(), true);
+        }
+        
+        if let Some(ver) = &self.version {
+            entry("Version", ver.clone(), true);
+        }
+
+        if self.output_dir.is_some() {
+            let exec = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+            entry("Exec", exec.display().to_string(), false);
+        } else {
+            entry("Exec", format!("./{}", path.file_name().unwrap().to_str().unwrap()), false);
+        }
+        if !self.desktop_bools.contains_key("Terminal") {
+            entry("Terminal", "false".to_string(), false);
+        }
+        for (key, value) in &self.desktop_bools {
+            entry(key, value.to_string(), true);
+        }
+        if let Some(id) = &self.build_id {
+            entry("X-BuildID", id.clone(), true);
+        }
+        if self.hidden {
+            entry("Hidden", "true".to_string(), true);
+        }
+        // List values are ';'-terminated, including the last one
+        if !self.only_show_in.is_empty() {
+            entry("OnlyShowIn", format!("{};", self.only_show_in.join(";")), true);
+        }
+        if !self.not_show_in.is_empty() {
+            entry("NotShowIn", format!("{};", self.not_show_in.join(";")), true);
+        }
+
+        let mut changed = false;
+
+        if self.make_executable {
+            changed |= ensure_executable(path)?;
+        }
+
+        match self.icon_path_for("linux") {
+            Some(icon) if !Path::new(icon).exists() => {
+                warn(&format!("icon not found, launcher will show no icon: {}", icon))?;
+                entry("Icon", icon.clone(), true);
+            }
+            Some(icon) if !self.install_icon_theme && self.appdir.is_none() => {
+                entry("Icon", icon.clone(), true);
+            }
+            _ => {
+                if let Some((icon, source)) = self.icon_source_for("linux")? {
+                    if let Some(appdir) = &self.appdir {
+                        changed |= self.install_appdir_icons(Path::new(appdir), &icon, &source, name, only_if_changed)?;
+                        entry("Icon", name.to_string(), true);
+                    } else if self.install_icon_theme {
+                        changed |= self.install_theme_icons(&icon, &source, name, &xdg_data_home()?, only_if_changed)?;
+                        entry("Icon", name.to_string(), true);
+                    } else {
+                        // In-memory icons have no path to reference, so write one next to the binary
+                        let icon_dest = parent.join(format!("{}.png", name));
+                        changed |= write_if_changed(&icon_dest, &self.encode_png(&icon, &source, None)?, only_if_changed)?;
+                        entry("Icon", icon_dest.display().to_string(), true);
+                    }
+                }
+            }
+        }
+
+        let content = match fs::read_to_string(&desktop_path) {
+            Ok(existing) => merge_desktop_entry(&existing, &entries),
+            Err(_) => merge_desktop_entry("", &entries),
+        };
+        changed |= write_if_changed(&desktop_path, content.as_bytes(), only_if_changed)?;
+        Ok(changed)
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (file_path, metadata=None))]
+fn edit(file_path: String, metadata: Option<HashMap<String, String>>) -> MetadataEditor {
+    let mut editor = MetadataEditor::new(file_path);
+    if let Some(meta) = metadata {
+        for (k, v) in meta {
+            editor.set_metadata(k, v);
+        }
+    }
+    editor
+}
+
+#[pyfunction]
+#[pyo3(signature = (file_path, **kwargs))]
+fn update(file_path: String, kwargs: Option<HashMap<String, String>>) -> PyResult<()> {
+    let mut editor = MetadataEditor::new(file_path);
+    if let Some(args) = kwargs {
+        for (k, v) in args {
+            editor.set_metadata(k, v);
+        }
+    }
+    editor.apply(false, None).map(|_| ())
+}
+
+/// Metadata every new editor starts with, e.g. a shared CompanyName and LegalCopyright
+/// when stamping many binaries. Keys work like `edit()`'s dict ("icon", "version" or a
+/// version string name) and replace any previous defaults. Per-file values always win.
+#[pyfunction]
+fn set_defaults(metadata: HashMap<String, String>) {
+    let mut defaults = DEFAULTS.lock().unwrap_or_else(|e| e.into_inner());
+    *defaults = metadata.into_iter().collect();
+    // Stable order so "icon"/"version" handling doesn't depend on hashing
+    defaults.sort();
+}
+
+/// Removes all defaults set by `set_defaults`. Existing editors keep what they picked up.
+#[pyfunction]
+fn clear_defaults() {
+    DEFAULTS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Splits "1.2.3.4-beta" into its numeric parts and trailing label: `([1, 2, 3, 4], Some("beta"))`.
+/// A leading "v" is allowed. Empty components ("1..2"), more than four parts, a missing
+/// numeric prefix or a dangling separator ("1.2-") are rejected as ambiguous.
+#[pyfunction]
+fn parse_version(s: String) -> PyResult<(Vec<u32>, Option<String>)> {
+    let invalid = |reason: &str| PyErr::new::<MetaEditError, _>(format!("invalid version '{}': {}", s, reason));
+
+    let trimmed = s.trim();
+    let body = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+    let numeric_len = body.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(body.len());
+    let (numeric, rest) = body.split_at(numeric_len);
+    // "1.2.beta": the dot before the label is a separator, not an empty component
+    let (numeric, rest) = match numeric.strip_suffix('.') {
+        Some(numeric) if !rest.is_empty() => (numeric, &body[numeric.len()..]),
+        _ => (numeric, rest),
+    };
+
+    if numeric.is_empty() {
+        return Err(invalid("expected it to start with a number"));
+    }
+    let parts = numeric.split('.')
+        .map(|part| match part {
+            "" => Err(invalid("empty component")),
+            _ => part.parse::<u32>().map_err(|_| invalid("component out of range")),
+        })
+        .collect::<PyResult<Vec<u32>>>()?;
+    if parts.len() > 4 {
+        return Err(invalid("more than four components"));
+    }
+
+    let label = match rest.trim_start_matches(['-', '+', '_', '.', ' ']) {
+        "" if !rest.is_empty() => return Err(invalid("separator without a label")),
+        "" => None,
+        label => Some(label.to_string()),
+    };
+    Ok((parts, label))
+}
+
+/// Numeric FixedFileInfo version for a version string; missing parts are 0.
+#[cfg(target_os = "windows")]
+fn fixed_version(version: &str) -> PyResult<editpe::types::VersionU16> {
+    let (parts, _) = parse_version(version.to_string())?;
+    let mut quad = [0u16; 4];
+    for (slot, part) in quad.iter_mut().zip(&parts) {
+        *slot = u16::try_from(*part).map_err(|_| PyErr::new::<MetaEditError, _>(format!(
+            "invalid version '{}': Windows version components must be at most 65535", version
+        )))?;
+    }
+    Ok(editpe::types::VersionU16 { major: quad[0], minor: quad[1], patch: quad[2], build: quad[3] })
+}
+
+/// Module-wide handling of signed PE input: "strip" (default) removes the signature, "refuse"
+/// raises `MetaEditError` instead of editing, "keep" leaves the now-invalid signature in place.
+#[pyfunction]
+fn set_signed_policy(policy: String) -> PyResult<()> {
+    let value = match policy.to_lowercase().as_str() {
+        "strip" => SIGNED_STRIP,
+        "refuse" => SIGNED_REFUSE,
+        "keep" => SIGNED_KEEP,
+        _ => return Err(PyErr::new::<MetaEditError, _>(format!(
+            "Unknown signed policy '{}' (expected strip, refuse or keep)", policy
+        ))),
+    };
+    SIGNED_POLICY.store(value, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Pre-flights an icon source without building an editor: decodes it through the same
+/// path `apply()` uses and reports its format, width, height and whether it has alpha.
+/// ICO files also get their frame table checked and report "frames".
+#[pyfunction]
+fn validate_icon(path: String) -> PyResult<HashMap<String, String>> {
+    if !Path::new(&path).is_file() {
+        return Err(PyErr::new::<IconError, _>(format!("icon not found: {}", path)));
+    }
+    let data = fs::read(&path)?;
+    let format = image::guess_format(&data).ok();
+    let frames = if data.starts_with(&[0, 0, 1, 0]) {
+        Some(check_ico(&data).map_err(|e| PyErr::new::<IconError, _>(format!("malformed ICO {}: {}", path, e)))?)
+    } else {
+        None
+    };
+    let img = decode_icon_image(&data, &path, DEFAULT_MAX_ICON_DIMENSION)?
+        .ok_or_else(|| PyErr::new::<IconError, _>(format!("Unsupported icon format: {}", path)))?;
+
+    let mut report = HashMap::new();
+    let format_name = if data.starts_with(b"icns") {
+        "icns"
+    } else {
+        format.and_then(|f| f.extensions_str().first().copied()).unwrap_or("unknown")
+    };
+    report.insert("format".to_string(), format_name.to_string());
+    report.insert("width".to_string(), img.width().to_string());
+    report.insert("height".to_string(), img.height().to_string());
+    report.insert("has_alpha".to_string(), img.color().has_alpha().to_string());
+    if let Some(frames) = frames {
+        report.insert("frames".to_string(), frames.to_string());
+    }
+    Ok(report)
+}
+
+/// The icon's dominant color as (r, g, b), for theming UI around it: the most common color
+/// once channels are bucketed to 4 bits, averaged over that bucket. Transparent pixels don't count.
+#[pyfunction]
+fn icon_dominant_color(icon_path: String) -> PyResult<(u8, u8, u8)> {
+    if !Path::new(&icon_path).is_file() {
+        return Err(PyErr::new::<IconError, _>(format!("icon not found: {}", icon_path)));
+    }
+    let data = fs::read(&icon_path)?;
+    let img = decode_icon_image(&data, &icon_path, DEFAULT_MAX_ICON_DIMENSION)?
+        .ok_or_else(|| PyErr::new::<IconError, _>(format!("Unsupported icon format: {}", icon_path)))?;
+    // Plenty of pixels for a color estimate, and large icons stay cheap
+    let img = img.thumbnail(64, 64).to_rgba8();
+
+    // Per bucket: alpha-weighted (r, g, b) sums and total weight
+    let mut buckets: HashMap<u16, ([u64; 3], u64)> = HashMap::new();
+    for pixel in img.pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a == 0 {
+            continue;
+        }
+        let key = ((r as u16 >> 4) << 8) | ((g as u16 >> 4) << 4) | (b as u16 >> 4);
+        let entry = buckets.entry(key).or_default();
+        for (sum, channel) in entry.0.iter_mut().zip([r, g, b]) {
+            *sum += channel as u64 * a as u64;
+        }
+        entry.1 += a as u64;
+    }
+    // Ties go to the lower bucket so the answer doesn't depend on HashMap order
+    let (sums, weight) = buckets.into_iter()
+        .max_by_key(|&(key, (_, weight))| (weight, std::cmp::Reverse(key)))
+        .map(|(_, bucket)| bucket)
+        .ok_or_else(|| PyErr::new::<IconError, _>(format!("icon is fully transparent: {}", icon_path)))?;
+    let channel = |i: usize| ((sums[i] + weight / 2) / weight) as u8;
+    Ok((channel(0), channel(1), channel(2)))
+}
+
+/// Directory for the intermediate file of every write, for targets whose own directory
+/// can't hold one. `None` restores the default of writing next to the target, which is
+/// what keeps the final rename atomic.
+#[pyfunction]
+#[pyo3(signature = (path=None))]
+fn set_temp_dir(path: Option<String>) -> PyResult<()> {
+    if let Some(dir) = &path {
+        if !Path::new(dir).is_dir() {
+            return Err(PyErr::new::<MetaEditError, _>(format!("temp dir is not a directory: {}", dir)));
+        }
+    }
+    *TEMP_DIR.lock().unwrap_or_else(|e| e.into_inner()) = path.map(std::path::PathBuf::from);
+    Ok(())
+}
+
+/// Runs `inspect()` on every supported binary under `root` (descending into subdirectories
+/// with `recursive`) and returns path -> report. Files of unknown format are skipped; a file
+/// or directory that can't be read is reported with a warning and the walk goes on.
+/// Symlinked directories are not followed.
+#[pyfunction]
+#[pyo3(signature = (root, recursive=true))]
+fn inspect_dir(root: String, recursive: bool) -> PyResult<HashMap<String, HashMap<String, String>>> {
+    if !Path::new(&root).is_dir() {
+        return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+            format!("Directory not found: {}", root),
+        ));
+    }
+
+    let mut reports = HashMap::new();
+    let mut pending = vec![std::path::PathBuf::from(&root)];
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn(&format!("skipping {}: {}", dir.display(), e))?;
+                continue;
+            }
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                if recursive {
+                    pending.push(path);
+                }
+                continue;
+            }
+            if !path.is_file() {
+                continue;
+            }
+            let file_path = path.to_string_lossy().into_owned();
+            let report = match detect_target(file_path.clone()) {
+                Ok(format) if format == "unknown" => continue,
+                Ok(_) => MetadataEditor::new(file_path.clone()).inspect(),
+                Err(e) => Err(e),
+            };
+            match report {
+                Ok(report) => {
+                    reports.insert(file_path, report);
+                }
+                Err(e) => warn(&format!("skipping {}: {}", file_path, e))?,
+            }
+        }
+    }
+    Ok(reports)
+}
+
+/// Recovers a PE a failed edit left damaged. With `backup_path`, the file is replaced by
+/// that known-good copy (which must itself be a PE). Otherwise the self-inflicted damage
+/// an interrupted edit can leave is fixed in place: a certificate table entry that points
+/// past the end of the file or at something that isn't a WIN_CERTIFICATE (a partial
+/// signature strip) is cleared, certificate records left after the image with no entry
+/// pointing at them are cut, then a stale non-zero checksum is recomputed. A file whose
+/// header can't be read at all is restored from the `<file>.bak` metaedit keeps before
+/// each in-place PE edit. Returns a description of each fix; with `dry_run` nothing is written.
+#[pyfunction]
+#[pyo3(signature = (file_path, backup_path=None, dry_run=false))]
+fn repair(file_path: String, backup_path: Option<String>, dry_run: bool) -> PyResult<Vec<String>> {
+    let path = Path::new(&file_path);
+    if let Some(backup) = backup_path {
+        return restore_backup(path, Path::new(&backup), dry_run);
+    }
+
+    let mut data = fs::read(path)?;
+    if optional_header_fields(&data).is_none() {
+        let backup = pe_backup_path(path);
+        if backup.is_file() {
+            return restore_backup(path, &backup, dry_run);
+        }
+        return Err(PyErr::new::<PEParseError, _>(format!(
+            "{} has no readable PE header and there is no {} to restore it from", file_path, backup.display()
+        )));
+    }
+
+    let mut fixes = Vec::new();
+    if let Some((entry, offset, size)) = read_security_dir(&data).filter(|&(_, offset, size)| offset != 0 || size != 0) {
+        let (start, end) = (offset as usize, offset as usize + size as usize);
+        // WIN_CERTIFICATE: dwLength, then wRevision 0x0100 or 0x0200
+        let revision = data.get(start + 4..start + 6).map(|b| u16::from_le_bytes([b[0], b[1]]));
+        if offset == 0 || size == 0 || end > data.len() || !matches!(revision, Some(0x0100 | 0x0200)) {
+            data[entry..entry + 8].fill(0);
+            fixes.push(format!("cleared a certificate table entry (offset {:#x}, size {:#x}) that points at no certificate", offset, size));
+        }
+    }
+    let referenced = read_security_dir(&data).is_some_and(|(_, offset, size)| offset != 0 && size != 0);
+    if !referenced {
+        if let Some(end) = pe_image_end(&data).filter(|&end| end < data.len() && is_certificate_tail(&data, end)) {
+            fixes.push(format!("removed {} bytes of certificate data no table entry points at", data.len() - end));
+            data.truncate(end);
+        }
+    }
+    if !pe_checksum_valid(&data) {
+        update_pe_checksum(&mut data);
+        fixes.push("recomputed the stale header checksum".to_string());
+    }
+
+    if !fixes.is_empty() && !dry_run {
+        write_atomic(path, &data)?;
+    }
+    Ok(fixes)
+}
+
+/// Replaces `path` with the PE at `backup` for `repair`.
+fn restore_backup(path: &Path, backup: &Path, dry_run: bool) -> PyResult<Vec<String>> {
+    let data = fs::read(backup)
+        .map_err(|e| PyErr::new::<MetaEditError, _>(format!("Failed to read backup {}: {}", backup.display(), e)))?;
+    if optional_header_fields(&data).is_none() {
+        return Err(PyErr::new::<PEParseError, _>(format!("backup is not a PE file: {}", backup.display())));
+    }
+    if fs::read(path).is_ok_and(|current| current == data) {
+        return Ok(Vec::new());
+    }
+    if !dry_run {
+        write_atomic(path, &data)?;
+    }
+    Ok(vec![format!("restored {} from {}", path.display(), backup.display())])
+}
+
+/// Silences informational output from the apply paths. Warnings and errors still surface.
+#[pyfunction]
+#[pyo3(signature = (quiet=true))]
+fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// A JSON string literal.
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The error for PE operations on a build without PE editing compiled in.
+fn pe_unavailable<T>() -> PyResult<T> {
+    Err(PyErr::new::<MetaEditError, _>("PE editing not available in this build"))
+}
+
+/// Reports which platform a binary targets from its magic bytes: "windows" (PE),
+/// "macos" (Mach-O, thin or universal), "linux" (ELF) or "unknown".
+#[pyfunction]
+fn detect_target(file_path: String) -> PyResult<String> {
+    use std::io::Read;
+
+    let path = Path::new(&file_path);
+    if !path.exists() {
+        return Err(PyErr::new::<pyo3::exceptions::PyFileNotFoundError, _>(
+            format!("File not found: {}", file_path),
+        ));
+    }
+
+    let mut header = Vec::with_capacity(0x40);
+    fs::File::open(path)?.take(0x40).read_to_end(&mut header)?;
+    Ok(target_from_magic(&header).to_string())
+}
+
+/// The target `apply()` produces on this build.
+fn host_target() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "windows"
+    } else if cfg!(target_os = "macos") {
+        "macos"
+    } else {
+        "linux"
+    }
+}
+
+fn target_from_magic(header: &[u8]) -> &'static str {
+    match header {
+        [b'M', b'Z', ..] => "windows",
+        [0x7f, b'E', b'L', b'F', ..] => "linux",
+        [0xfe, 0xed, 0xfa, 0xce | 0xcf, ..] | [0xce | 0xcf, 0xfa, 0xed, 0xfe, ..] => "macos",
+        // Universal binaries share 0xCAFEBABE with Java class files; a fat header's
+        // arch count is tiny while a class file's major version is 45 or more
+        [0xca, 0xfe, 0xba, 0xbe, a, b, c, d, ..] if u32::from_be_bytes([*a, *b, *c, *d]) < 45 => "macos",
+        _ => "unknown",
+    }
+}
+
+#[pymodule]
+fn _metaedit(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<MetadataEditor>()?;
+    m.add_class::<ApplyResult>()?;
+    #[cfg(target_os = "windows")]
+    m.add_class::<ResourceDiff>()?;
+    m.add_function(wrap_pyfunction!(edit, m)?)?;
+    m.add_function(wrap_pyfunction!(update, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_target, m)?)?;
+    m.add_function(wrap_pyfunction!(set_quiet, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_version, m)?)?;
+    m.add_function(wrap_pyfunction!(set_signed_policy, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_icon, m)?)?;
+    m.add_function(wrap_pyfunction!(set_defaults, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_defaults, m)?)?;
+    m.add_function(wrap_pyfunction!(set_temp_dir, m)?)?;
+    m.add_function(wrap_pyfunction!(icon_dominant_color, m)?)?;
+    m.add_function(wrap_pyfunction!(repair, m)?)?;
+    m.add_function(wrap_pyfunction!(inspect_dir, m)?)?;
+    
+    m.add("MetaEditError", py.get_type::<MetaEditError>())?;
+    m.add("PEParseError", py.get_type::<PEParseError>())?;
+    m.add("IconError", py.get_type::<IconError>())?;
+    // m.add("SigningError", py.get_type::<SigningError>())?;
+    Ok(())
+}
+
+/// Drops sizes larger than the source image, since upscaled frames only add bloat.
+/// Always keeps the smallest requested size so tiny sources still produce an icon, and
+/// every size in `always`.
+#[cfg(target_os = "windows")]
+fn native_icon_sizes(sizes: &[u32], source_dim: u32, always: &[u32]) -> PyResult<Vec<u32>> {
+    let (kept, skipped): (Vec<u32>, Vec<u32>) = sizes.iter().partition(|&&size| size <= source_dim || always.contains(&size));
+    let kept = if kept.is_empty() {
+        sizes.iter().min().into_iter().copied().collect()
+    } else {
+        kept
+    };
+
+    let skipped: Vec<String> = skipped.iter().filter(|s| !kept.contains(s)).map(|s| s.to_string()).collect();
+    if !skipped.is_empty() {
+        warn(&format!(
+            "icon source is only {}px; skipping upscaled sizes: {}",
+            source_dim,
+            skipped.join(", ")
+        ))?;
+    }
+    Ok(kept)
+}
+
+/// ICO frame sizes Explorer and the taskbar pick directly at 100% scaling.
+#[cfg(target_os = "windows")]
+const SHELL_ICON_SIZES: [u32; 2] = [48, 32];
+
+#[cfg(target_os = "windows")]
+fn file_type_name(vft: u32) -> &'static str {
+    match vft {
+        VFT_APP => "app",
+        VFT_DLL => "dll",
+        VFT_DRV => "driver",
+        VFT_STATIC_LIB => "static_lib",
+        _ => "unknown",
+    }
+}
+
+/// Decodes an icon source, sniffing the format from its content rather than trusting the extension.
+/// Returns `None` when the format isn't recognised at all.
+fn decode_icon_image(data: &[u8], source: &str, max_dimension: u32) -> PyResult<Option<image::DynamicImage>> {
+    if data.starts_with(b"icns") {
+        return decode_icns(data, source, max_dimension).map(Some);
+    }
+    let mut reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| PyErr::new::<IconError, _>(format!("Failed to read icon {}: {:?}", source, e)))?;
+
+    let mut limits = image::Limits::default();
+    limits.max_image_width = Some(max_dimension);
+    limits.max_image_height = Some(max_dimension);
+    reader.limits(limits);
+
+    let format = match reader.format() {
+        Some(format) => format,
+        None => return Ok(None),
+    };
+
+    match reader.decode() {
+        Ok(img) => Ok(Some(img)),
+        Err(image::ImageError::Limits(_)) => Err(PyErr::new::<IconError, _>(format!(
+            "icon {} exceeds the maximum size of {}x{} pixels", source, max_dimension, max_dimension
+        ))),
+        // Let exotic ICOs fall back to being embedded as-is
+        Err(_) if format == image::ImageFormat::Ico => Ok(None),
+        Err(image::ImageError::Unsupported(_)) if format == image::ImageFormat::Avif => Err(PyErr::new::<IconError, _>(
+            "AVIF icons require metaedit to be built with the `avif` feature".to_string(),
+        )),
+        Err(e) => Err(PyErr::new::<IconError, _>(format!("Failed to decode icon {}: {}", source, e))),
+    }
+}
+
+/// The largest image in an `.icns`, so an icns-only asset can feed ICO and PNG generation.
+/// Only PNG entries (every slot since OS X 10.7) are read; legacy RLE and JPEG 2000 entries
+/// are skipped.
+fn decode_icns(data: &[u8], source: &str, max_dimension: u32) -> PyResult<image::DynamicImage> {
+    let mut largest: Option<image::DynamicImage> = None;
+    let mut offset = 8;
+    while let Some(header) = data.get(offset..offset + 8) {
+        let length = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+        if length < 8 {
+            break;
+        }
+        let payload = data.get(offset + 8..offset + length).unwrap_or(&data[offset + 8..]);
+        if payload.starts_with(b"\x89PNG") {
+            if let Some(img) = decode_icon_image(payload, source, max_dimension)? {
+                if largest.as_ref().is_none_or(|l| img.width() > l.width()) {
+                    largest = Some(img);
+                }
+            }
+        }
+        offset += length;
+    }
+    largest.ok_or_else(|| PyErr::new::<IconError, _>(format!(
+        "No usable image in ICNS {} (only PNG entries are supported)", source
+    )))
+}
+
+/// Writes `data` to `path`. With `only_if_changed`, an identical existing file is left
+/// untouched (no mtime churn). Returns whether anything was written.
+fn write_if_changed(path: &Path, data: &[u8], only_if_changed: bool) -> PyResult<bool> {
+    if only_if_changed {
+        if let Ok(existing) = fs::read(path) {
+            if existing == data {
+                return Ok(false);
+            }
+        }
+    }
+    write_atomic(path, data)?;
+    Ok(true)
+}
+
+/// Keeps the current contents of the PE at `path` as `<file>.bak` before it is overwritten
+/// in place, so `repair` has a copy to fall back to. A file whose header is already
+/// unreadable never replaces an earlier backup.
+fn save_pe_backup(path: &Path) -> PyResult<()> {
+    let data = fs::read(path)?;
+    if optional_header_fields(&data).is_none() {
+        return Ok(());
+    }
+    write_if_changed(&pe_backup_path(path), &data, true).map(|_| ())
+}
+
+/// Where `save_pe_backup` puts the previous contents of `path`: `app.exe` -> `app.exe.bak`.
+fn pe_backup_path(path: &Path) -> std::path::PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    std::path::PathBuf::from(name)
+}
+
+/// Writes via a sibling temp file (or one in the `set_temp_dir()` directory) that is
+/// flushed and then renamed over `path`, so a failure midway (permissions, disk full) never
+/// leaves a truncated file behind. A temp dir on another filesystem can't be renamed from,
+/// so the data is copied to a second temp file next to `path` and that one is renamed.
+fn write_atomic(path: &Path, data: &[u8]) -> PyResult<()> {
+    use std::io::Write;
+
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("output");
+    let temp_dir = TEMP_DIR.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let target_dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let cross_device = temp_dir.as_ref().is_some_and(|dir| !same_filesystem(dir, target_dir));
+    if cross_device {
+        warn(&format!(
+            "temp dir {} is on a different filesystem than {}; copying the file next to it before the final rename",
+            temp_dir.as_ref().unwrap().display(), path.display()
+        ))?;
+    }
+
+    let mut temps = Vec::new();
+    let result = (|| {
+        let (tmp_path, mut file) = create_temp_file(temp_dir.as_deref().unwrap_or(target_dir), file_name)?;
+        temps.push(tmp_path.clone());
+        file.write_all(data)?;
+        file.sync_all()?;
+        drop(file);
+        // Keep the mode of the file being replaced (e.g. the executable bit)
+        if let Ok(meta) = fs::metadata(path) {
+            fs::set_permissions(&tmp_path, meta.permissions())?;
+        }
+        let staged = if cross_device {
+            let (staged, mut file) = create_temp_file(target_dir, file_name)?;
+            temps.push(staged.clone());
+            std::io::copy(&mut fs::File::open(&tmp_path)?, &mut file)?;
+            file.sync_all()?;
+            drop(file);
+            fs::set_permissions(&staged, fs::metadata(&tmp_path)?.permissions())?;
+            fs::remove_file(&tmp_path)?;
+            staged
+        } else {
+            tmp_path
+        };
+        fs::rename(&staged, path)
+    })();
+
+    if result.is_err() {
+        for tmp_path in &temps {
+            let _ = fs::remove_file(tmp_path);
+        }
+    }
+    result.map_err(|e| write_error(path, e))
+}
+
+/// Creates a fresh temp file for `file_name` in `dir`. The name carries the process id and a
+/// counter, and `create_new` refuses an existing file, so two writers (threads, processes, or
+/// same-named targets sharing a `set_temp_dir()` directory) never share one.
+fn create_temp_file(dir: &Path, file_name: &str) -> std::io::Result<(std::path::PathBuf, fs::File)> {
+    loop {
+        let n = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let tmp_path = dir.join(format!(".{}.{}-{}.metaedit-tmp", file_name, std::process::id(), n));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&tmp_path) {
+            Ok(file) => return Ok((tmp_path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Whether two existing directories share a filesystem, i.e. a rename between them works.
+#[cfg(unix)]
+fn same_filesystem(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev(),
+        _ => true,
+    }
+}
+
+/// Whether two existing directories share a filesystem, i.e. a rename between them works.
+/// Compares drive letters or UNC shares.
+#[cfg(not(unix))]
+fn same_filesystem(a: &Path, b: &Path) -> bool {
+    let prefix = |p: &Path| fs::canonicalize(p).ok().and_then(|p| p.components().next().map(|c| c.as_os_str().to_ascii_lowercase()));
+    match (prefix(a), prefix(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    }
+}
+
+/// Serializes `dict` as an XML property list, laid out like Xcode's (tab-indented).
+fn plist_xml(dict: &IndexMap<String, PlistEntry>) -> String {
+    let escape = |s: &str| s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let mut xml = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+        "<plist version=\"1.0\">\n<dict>\n",
+    ));
+    for (key, value) in dict {
+        xml.push_str(&format!("\t<key>{}</key>\n", escape(key)));
+        match value {
+            PlistEntry::String(s) => xml.push_str(&format!("\t<string>{}</string>\n", escape(s))),
+            PlistEntry::Bool(b) => xml.push_str(if *b { "\t<true/>\n" } else { "\t<false/>\n" }),
+            PlistEntry::Int(i) => xml.push_str(&format!("\t<integer>{}</integer>\n", i)),
+            PlistEntry::Array(items) if items.is_empty() => xml.push_str("\t<array/>\n"),
+            PlistEntry::Array(items) => {
+                xml.push_str("\t<array>\n");
+                for item in items {
+                    xml.push_str(&format!("\t\t<string>{}</string>\n", escape(item)));
+                }
+                xml.push_str("\t</array>\n");
+            }
+        }
+    }
+    xml.push_str("</dict>\n</plist>\n");
+    xml
+}
+
+/// The `plist` crate's value for `entry`, for merging into a parsed Info.plist.
+#[cfg(target_os = "macos")]
+fn plist_value(entry: &PlistEntry) -> Value {
+    match entry {
+        PlistEntry::String(s) => Value::String(s.clone()),
+        PlistEntry::Bool(b) => Value::Boolean(*b),
+        PlistEntry::Int(i) => Value::Integer((*i).into()),
+        PlistEntry::Array(items) => Value::Array(items.iter().cloned().map(Value::String).collect()),
+    }
+}
+
+/// The executable of an existing bundle: its Info.plist's CFBundleExecutable, else the only
+/// file in `Contents/MacOS`, else the bundle's name without `.app`.
+fn bundle_executable(bundle: &Path) -> PyResult<String> {
+    let contents = bundle.join("Contents");
+    let plist_path = contents.join("Info.plist");
+    if plist_path.exists() {
+        if let Some(name) = plist_string(&plist_path, "CFBundleExecutable")? {
+            return Ok(name);
+        }
+    }
+
+    let binaries: Vec<String> = fs::read_dir(contents.join("MacOS"))
+        .map(|entries| entries.filter_map(|e| e.ok()).filter(|e| e.path().is_file())
+            .map(|e| e.file_name().to_string_lossy().into_owned()).collect())
+        .unwrap_or_default();
+    if let [only] = binaries.as_slice() {
+        return Ok(only.clone());
+    }
+
+    let name = bundle.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    if !contents.join("MacOS").join(&name).is_file() {
+        warn(&format!("{} has no Contents/MacOS/{} executable; the bundle won't launch", bundle.display(), name))?;
+    }
+    Ok(name)
+}
+
+/// The string value of `key` in the property list at `path`.
+#[cfg(target_os = "macos")]
+fn plist_string(path: &Path, key: &str) -> PyResult<Option<String>> {
+    let info = Value::from_file(path)
+        .map_err(|e| PyErr::new::<MetaEditError, _>(format!("Failed to read {}: {}", path.display(), e)))?;
+    Ok(info.as_dictionary().and_then(|d| d.get(key)).and_then(|v| v.as_string()).map(str::to_string))
+}
+
+/// The string value of top-level `key` in the XML property list at `path`. Without the
+/// `plist` crate, binary property lists can't be read; they yield None with a warning.
+#[cfg(not(target_os = "macos"))]
+fn plist_string(path: &Path, key: &str) -> PyResult<Option<String>> {
+    let data = fs::read(path)?;
+    if data.starts_with(b"bplist") {
+        warn(&format!("{} is a binary property list, which only macOS builds can read", path.display()))?;
+        return Ok(None);
+    }
+    let xml = String::from_utf8_lossy(&data);
+    let Some(pos) = xml.find(&format!("<key>{}</key>", key)) else { return Ok(None) };
+    let rest = xml[pos..].split_once("</key>").map_or("", |(_, rest)| rest).trim_start();
+    Ok(rest.strip_prefix("<string>")
+        .and_then(|value| value.split_once("</string>"))
+        .map(|(value, _)| value.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")))
+}
+
+/// Puts `src` at `dest` by copying, moving or hard-linking it. Moves and links that fail
+/// because the bundle is on another filesystem fall back to copying.
+fn place_binary(src: &Path, dest: &Path, placement: &str, only_if_changed: bool) -> PyResult<bool> {
+    match placement {
+        "move" => {
+            if fs::rename(src, dest).is_ok() {
+                // rename() is a no-op when both names are hard links to the same file
+                if src.exists() {
+                    fs::remove_file(src).map_err(|e| write_error(src, e))?;
+                }
+                return Ok(true);
+            }
+            write_atomic(dest, &fs::read(src)?)?;
+            fs::remove_file(src).map_err(|e| write_error(src, e))?;
+            Ok(true)
+        }
+        "hardlink" => {
+            if dest.exists() {
+                if same_file(src, dest)? {
+                    return Ok(false);
+                }
+                fs::remove_file(dest).map_err(|e| write_error(dest, e))?;
+            }
+            if fs::hard_link(src, dest).is_err() {
+                warn(&format!("cannot hard-link {} into the bundle; copying instead", src.display()))?;
+                write_atomic(dest, &fs::read(src)?)?;
+            }
+            Ok(true)
+        }
+        _ => write_if_changed(dest, &fs::read(src)?, only_if_changed),
+    }
+}
+
+/// Lays out a DMG staging folder: a copy of `bundle` plus an `Applications` -> /Applications
+/// symlink, so the mounted image offers drag-to-install.
+fn stage_dmg(bundle: &Path, staging: &Path, only_if_changed: bool) -> PyResult<bool> {
+    fs::create_dir_all(staging).map_err(|e| write_error(staging, e))?;
+    let mut changed = copy_tree(bundle, &staging.join(bundle.file_name().unwrap()), only_if_changed)?;
+
+    let link = staging.join("Applications");
+    let target = Path::new("/Applications");
+    if fs::read_link(&link).ok().as_deref() != Some(target) {
+        if fs::symlink_metadata(&link).is_ok() {
+            fs::remove_file(&link).map_err(|e| write_error(&link, e))?;
+        }
+        symlink(target, &link).map_err(|e| write_error(&link, e))?;
+        changed = true;
+    }
+    Ok(changed)
+}
+
+/// Recursively copies `src` to `dst`, keeping symlinks (framework `Versions/Current` etc.)
+/// and file modes. Returns whether anything was written.
+fn copy_tree(src: &Path, dst: &Path, only_if_changed: bool) -> PyResult<bool> {
+    fs::create_dir_all(dst).map_err(|e| write_error(dst, e))?;
+    let mut changed = false;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let from = entry.path();
+        let to = dst.join(entry.file_name());
+        let file_type = entry.file_type()?;
+
+        if file_type.is_symlink() {
+            let target = fs::read_link(&from)?;
+            if fs::read_link(&to).ok() != Some(target.clone()) {
+                if fs::symlink_metadata(&to).is_ok() {
+                    fs::remove_file(&to).map_err(|e| write_error(&to, e))?;
+                }
+                symlink(&target, &to).map_err(|e| write_error(&to, e))?;
+                changed = true;
+            }
+        } else if file_type.is_dir() {
+            changed |= copy_tree(&from, &to, only_if_changed)?;
+        } else {
+            changed |= write_if_changed(&to, &fs::read(&from)?, only_if_changed)?;
+            let perms = fs::metadata(&from)?.permissions();
+            if fs::metadata(&to)?.permissions() != perms {
+                fs::set_permissions(&to, perms).map_err(|e| write_error(&to, e))?;
+                changed = true;
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// A Shell Link (`.lnk`, MS-SHLLINK) to the absolute path `target`: a LinkInfo with the
+/// local path, then the description, working directory (the target's folder) and icon
+/// location (index 0) as Unicode StringData.
+#[cfg(target_os = "windows")]
+fn shell_link(target: &str, description: Option<&str>, icon: &str) -> Vec<u8> {
+    const HAS_LINK_INFO: u32 = 0x02;
+    const HAS_NAME: u32 = 0x04;
+    const HAS_WORKING_DIR: u32 = 0x10;
+    const HAS_ICON_LOCATION: u32 = 0x40;
+    const IS_UNICODE: u32 = 0x80;
+    let mut flags = HAS_LINK_INFO | HAS_WORKING_DIR | HAS_ICON_LOCATION | IS_UNICODE;
+    if description.is_some() {
+        flags |= HAS_NAME;
+    }
+    let working_dir = Path::new(target).parent().map(|p| p.display().to_string()).unwrap_or_default();
+    let file_size = fs::metadata(target).map_or(0, |m| m.len() as u32);
+
+    let mut lnk = Vec::new();
+    lnk.extend_from_slice(&0x4cu32.to_le_bytes());
+    // LinkCLSID 00021401-0000-0000-C000-000000000046
+    lnk.extend_from_slice(&[0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46]);
+    lnk.extend_from_slice(&flags.to_le_bytes());
+    lnk.extend_from_slice(&0x20u32.to_le_bytes()); // FILE_ATTRIBUTE_ARCHIVE
+    lnk.extend_from_slice(&[0; 24]); // creation, access and write times
+    lnk.extend_from_slice(&file_size.to_le_bytes());
+    lnk.extend_from_slice(&0i32.to_le_bytes()); // icon index
+    lnk.extend_from_slice(&1u32.to_le_bytes()); // SW_SHOWNORMAL
+    lnk.extend_from_slice(&[0; 12]); // hotkey and reserved fields
+
+    // LinkInfo: header with Unicode offsets, a fixed-drive VolumeID, then the base path in
+    // ANSI (non-ASCII as '?') and UTF-16; the common path suffix is empty
+    let ansi: Vec<u8> = target.chars().map(|c| if c.is_ascii() { c as u8 } else { b'?' }).chain([0]).collect();
+    let unicode: Vec<u8> = target.encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect();
+    let volume_id: &[u8] = &[0x11, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0x10, 0, 0, 0, 0];
+    let header_size = 0x24u32;
+    let base_path = header_size + volume_id.len() as u32;
+    let suffix = base_path + ansi.len() as u32;
+    let base_path_unicode = suffix + 1;
+    let suffix_unicode = base_path_unicode + unicode.len() as u32;
+    let size = suffix_unicode + 2;
+    for field in [size, header_size, 1, header_size, base_path, 0, suffix, base_path_unicode, suffix_unicode] {
+        lnk.extend_from_slice(&field.to_le_bytes());
+    }
+    lnk.extend_from_slice(volume_id);
+    lnk.extend_from_slice(&ansi);
+    lnk.push(0);
+    lnk.extend_from_slice(&unicode);
+    lnk.extend_from_slice(&[0, 0]);
+
+    for text in description.into_iter().chain([working_dir.as_str(), icon]) {
+        let units: Vec<u16> = text.encode_utf16().collect();
+        lnk.extend_from_slice(&(units.len() as u16).to_le_bytes());
+        lnk.extend(units.iter().flat_map(|u| u.to_le_bytes()));
+    }
+    // TerminalBlock
+    lnk.extend_from_slice(&[0; 4]);
+    lnk
+}
+
+/// `path` with the first 8 hex digits of the FNV-1a hash of `data` inserted before the
+/// extension. FNV rather than std's hasher because the name must not change between runs.
+#[cfg(target_os = "windows")]
+fn content_addressed_path(path: &Path, data: &[u8]) -> std::path::PathBuf {
+    let hash = data.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| (h ^ b as u64).wrapping_mul(0x100_0000_01b3));
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}.{:08x}.{}", stem, hash >> 32, ext.to_string_lossy()),
+        None => format!("{}.{:08x}", stem, hash >> 32),
+    };
+    path.with_file_name(name)
+}
+
+/// Fails up front when `path` can't be opened for writing because another process holds
+/// it, typically the program itself still running, instead of with an OS error mid-write.
+#[cfg(target_os = "windows")]
+fn ensure_not_in_use(path: &Path) -> PyResult<()> {
+    // ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+    const IN_USE: [i32; 2] = [32, 33];
+    match fs::OpenOptions::new().write(true).open(path) {
+        Err(e) if e.raw_os_error().is_some_and(|code| IN_USE.contains(&code)) => Err(PyErr::new::<MetaEditError, _>(format!(
+            "{}: file is in use by another process; close the application and try again", path.display()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Adds the execute bit wherever the read bit is set (0644 -> 0755), like `chmod +x`
+/// without widening access. Returns whether the mode changed.
+#[cfg(unix)]
+fn ensure_executable(path: &Path) -> PyResult<bool> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut perms = fs::metadata(path)?.permissions();
+    let mode = perms.mode();
+    let wanted = mode | ((mode & 0o444) >> 2);
+    if wanted == mode {
+        return Ok(false);
+    }
+    perms.set_mode(wanted);
+    fs::set_permissions(path, perms).map_err(|e| write_error(path, e))?;
+    Ok(true)
+}
+
+/// Windows has no execute bit to set; bundles and AppDirs built there need it set when
+/// they are packaged on the target system.
+#[cfg(not(unix))]
+fn ensure_executable(_path: &Path) -> PyResult<bool> {
+    Ok(false)
+}
+
+/// Creates `link` pointing at `target` (relative to `link`'s folder unless absolute).
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+/// Creates `link` pointing at `target` (relative to `link`'s folder unless absolute). Needs
+/// Developer Mode or the SeCreateSymbolicLink privilege.
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    let resolved = link.parent().unwrap_or(Path::new(".")).join(target);
+    if resolved.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+/// Whether `a` and `b` are the same file, e.g. hard links to one inode.
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> PyResult<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let (a, b) = (fs::metadata(a)?, fs::metadata(b)?);
+    Ok(a.dev() == b.dev() && a.ino() == b.ino())
+}
+
+/// Whether `a` and `b` are the same file. std has no stable file identity off Unix, so
+/// this is always false and hard links are simply recreated.
+#[cfg(not(unix))]
+fn same_file(_a: &Path, _b: &Path) -> PyResult<bool> {
+    Ok(false)
+}
+
+/// Turns permission and locking failures into a `MetaEditError` that says what to do about
+/// them; other I/O errors pass through as `OSError`.
+fn write_error(path: &Path, err: std::io::Error) -> PyErr {
+    // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION: another process has the file open
+    if cfg!(target_os = "windows") && matches!(err.raw_os_error(), Some(32) | Some(33)) {
+        return PyErr::new::<MetaEditError, _>(format!(
+            "cannot write {}: the file is in use by another process; close it and try again",
+            path.display()
+        ));
+    }
+    if err.kind() == std::io::ErrorKind::PermissionDenied {
+        return PyErr::new::<MetaEditError, _>(format!(
+            "permission denied writing {}; the file may be in use or require elevated privileges",
+            path.display()
+        ));
+    }
+    err.into()
+}
+
+/// Rejects empty or truncated inputs up front instead of letting them fail deep inside a parser.
+/// The minimum is the fixed header of the format the magic bytes announce (the DOS header for
+/// PE, the 32-bit ELF and Mach-O headers); anything unrecognised just needs to be non-empty.
+fn check_input_size(path: &Path) -> PyResult<()> {
+    use std::io::Read;
+
+    let mut header = Vec::with_capacity(0x40);
+    fs::File::open(path)?.take(0x40).read_to_end(&mut header)?;
+    let (min_size, format) = match target_from_magic(&header) {
+        "windows" => (0x40, "PE"),
+        "linux" => (0x34, "ELF"),
+        "macos" => (0x1c, "Mach-O"),
+        _ => (1, "executable"),
+    };
+    let len = fs::metadata(path)?.len();
+    if len < min_size {
+        return Err(PyErr::new::<MetaEditError, _>(format!(
+            "file is empty or too small to be a valid {}: {} ({} bytes)",
+            format,
+            path.display(),
+            len
+        )));
+    }
+    Ok(())
+}
+
+/// Checks a macOS localization name ("en", "pt-BR", "zh_Hans", "Base"), which becomes a
+/// `.lproj` folder name, so it must not contain path separators or other punctuation.
+fn validate_lproj_region(region: &str) -> PyResult<String> {
+    let valid = region.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && region.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(PyErr::new::<MetaEditError, _>(format!("Invalid localization region '{}'", region)));
+    }
+    Ok(region.to_string())
+}
+
+/// Escapes a value for a `.strings` file literal.
+fn strings_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// A VERSIONINFO string table key: exactly 8 hex digits (language id, then codepage),
+/// lowercased.
+fn normalize_table_key(key: &str) -> PyResult<String> {
+    if key.len() != 8 || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(PyErr::new::<MetaEditError, _>(format!(
+            "Invalid string table key '{}': expected 8 hex digits of language and codepage, e.g. 040904b0", key
+        )));
+    }
+    Ok(key.to_ascii_lowercase())
+}
+
+/// Normalizes "de_de" / "DE-de" / "de" into BCP 47 casing ("de-DE" / "de"), rejecting
+/// anything metaedit can't map to a Windows language id.
+fn normalize_locale(locale: &str) -> PyResult<String> {
+    let mut parts = locale.split(['-', '_']);
+    let lang = parts.next().unwrap_or_default().to_lowercase();
+    let normalized = match (parts.next(), parts.next()) {
+        (None, _) => lang,
+        (Some(region), None) => format!("{}-{}", lang, region.to_uppercase()),
+        _ => String::new(),
+    };
+    if locale_lcid(&normalized).is_none() {
+        return Err(PyErr::new::<MetaEditError, _>(format!("Unsupported locale: {}", locale)));
+    }
+    Ok(normalized)
+}
+
+fn locale_lcid(locale: &str) -> Option<u16> {
+    if locale.contains('-') {
+        LOCALE_LCIDS.iter().find(|(tag, _)| *tag == locale).map(|(_, lcid)| *lcid)
+    } else {
+        // First entry per language is its primary region
+        LOCALE_LCIDS.iter().find(|(tag, _)| tag.split('-').next() == Some(locale)).map(|(_, lcid)| *lcid)
+    }
+}
+
+/// Picks a VERSIONINFO string table by language selector: a full "040904b0" key, a bare
+/// language id ("0x0409" / "0409") or a locale ("de-DE"). No selector means the first table.
+#[cfg(target_os = "windows")]
+fn find_string_table<'a>(tables: &'a [VersionStringTable], lang: Option<&str>) -> PyResult<Option<&'a VersionStringTable>> {
+    let lang = match lang {
+        Some(lang) => lang,
+        None => return Ok(tables.first()),
+    };
+
+    let hex = lang.trim_start_matches("0x").trim_start_matches("0X").to_lowercase();
+    let prefix = if hex.len() == 8 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok(tables.iter().find(|t| t.key.to_lowercase() == hex));
+    } else if hex.len() == 4 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        hex
+    } else {
+        let locale = normalize_locale(lang)?;
+        format!("{:04x}", locale_lcid(&locale).unwrap_or(0x0409))
+    };
+    Ok(tables.iter().find(|t| t.key.to_lowercase().starts_with(&prefix)))
+}
+
+/// Lines of a .desktop file's `[Desktop Entry]` group, without the header.
+#[cfg(target_os = "linux")]
+fn desktop_entry_lines(content: &str) -> impl Iterator<Item = &str> {
+    content.trim_start_matches('\u{feff}').lines()
+        .skip_while(|line| line.trim() != "[Desktop Entry]")
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with('['))
+}
+
+/// A value as it must appear in a .desktop file, where every entry is one line: backslashes
+/// become the spec's `\\` escape, CRLF and stray CRs become line breaks, and line breaks
+/// become `\n`.
+fn desktop_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace("\r\n", "\n").replace('\r', "\n").replace('\n', "\\n")
+}
+
+/// Writes `entries` into the `[Desktop Entry]` group of an existing .desktop file.
+/// Comments, other groups (e.g. `[Desktop Action ...]`) and keys not in `entries` are kept
+/// as they are; an existing key is replaced only when the user set it. Missing keys go at
+/// the end of the group. Without a `[Desktop Entry]` group the file is written fresh.
+/// The result is UTF-8 with `\n` line endings and no BOM, whatever the existing file used.
+fn merge_desktop_entry(existing: &str, entries: &[(String, String, bool)]) -> String {
+    let mut lines: Vec<String> = existing.trim_start_matches('\u{feff}').lines().map(str::to_string).collect();
+    let start = match lines.iter().position(|line| line.trim() == "[Desktop Entry]") {
+        Some(start) => start,
+        None => {
+            lines = vec!["[Desktop Entry]".to_string()];
+            0
+        }
+    };
+    let mut end = lines[start + 1..].iter().position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |i| start + 1 + i);
+    // New keys go after the group's last non-blank line, keeping the blank line before the next group
+    let mut insert_at = (start + 1..end).rev().find(|&i| !lines[i].trim().is_empty()).map_or(start + 1, |i| i + 1);
+
+    for (key, value, user_set) in entries {
+        let line = format!("{}={}", key, desktop_value(value));
+        let existing = (start + 1..end).find(|&i| {
+            lines[i].split_once('=').is_some_and(|(k, _)| k.trim() == key)
+        });
+        match existing {
+            Some(i) if *user_set => lines[i] = line,
+            Some(_) => {}
+            None => {
+                lines.insert(insert_at, line);
+                insert_at += 1;
+                end += 1;
+            }
+        }
+    }
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+/// The size in an icon set file name: "32" or "32x32".
+fn icon_file_size(stem: &str) -> Option<u32> {
+    let size = match stem.split_once(['x', 'X']) {
+        Some((width, height)) if width == height => width,
+        Some(_) => return None,
+        None => stem,
+    };
+    size.parse().ok().filter(|size| (1..=1024).contains(size))
+}
+
+/// Apple's `LSApplicationCategoryType` values, without the `public.app-category.` prefix.
+const APP_CATEGORIES: &[&str] = &[
+    "business", "developer-tools", "education", "entertainment", "finance", "games",
+    "graphics-design", "healthcare-fitness", "lifestyle", "medical", "music", "news",
+    "photography", "productivity", "reference", "social-networking", "sports", "travel",
+    "utilities", "video", "weather",
+    "action-games", "adventure-games", "arcade-games", "board-games", "card-games",
+    "casino-games", "dice-games", "educational-games", "family-games", "kids-games",
+    "music-games", "puzzle-games", "racing-games", "role-playing-games", "simulation-games",
+    "sports-games", "strategy-games", "trivia-games", "word-games",
+];
+
+/// Architecture names `LSArchitecturePriority` accepts on current macOS.
+const MACOS_ARCHITECTURES: &[&str] = &["arm64", "x86_64"];
+
+/// `CFBundleInfoDictionaryVersion` of generated Info.plists; "6.0" is the only version Apple has used.
+const DEFAULT_INFO_DICTIONARY_VERSION: &str = "6.0";
+
+/// Boolean keys of the Desktop Entry specification that `set_desktop_bool` accepts.
+const DESKTOP_BOOL_KEYS: &[&str] = &[
+    "DBusActivatable", "SingleMainWindow", "PrefersNonDefaultGPU", "Terminal", "NoDisplay", "StartupNotify",
+];
+
+/// Desktop environments registered in the freedesktop.org menu specification.
+const REGISTERED_DESKTOPS: &[&str] = &[
+    "Budgie", "Cinnamon", "COSMIC", "DDE", "Deepin", "EDE", "Endless", "Enlightenment", "GNOME",
+    "GNOME-Classic", "GNOME-Flashback", "KDE", "LXDE", "LXQt", "MATE", "Old", "Pantheon", "Razor",
+    "ROX", "TDE", "Unity", "XFCE",
+];
+
+/// Accepts registered desktop environment names and vendor-specific `X-` names.
+fn validate_desktop_names(desktops: &[String]) -> PyResult<()> {
+    for desktop in desktops {
+        let vendor = desktop.strip_prefix("X-").is_some_and(|rest| {
+            !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+        if !vendor && !REGISTERED_DESKTOPS.contains(&desktop.as_str()) {
+            return Err(PyErr::new::<MetaEditError, _>(format!(
+                "Unknown desktop environment '{}' (expected e.g. GNOME, KDE, XFCE or an X- vendor name)", desktop
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `$XDG_DATA_HOME`, falling back to `~/.local/share` per the XDG base directory spec.
+fn xdg_data_home() -> PyResult<std::path::PathBuf> {
+    match std::env::var_os("XDG_DATA_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => Ok(std::path::PathBuf::from(dir)),
+        None => {
+            let home = std::env::var_os("HOME")
+                .ok_or_else(|| PyErr::new::<MetaEditError, _>("Cannot locate icon theme directory: HOME is not set"))?;
+            Ok(Path::new(&home).join(".local/share"))
+        }
+    }
+}
+
+/// Default for `set_max_icon_dimension`: far above any real icon, far below a decompression bomb.
+const DEFAULT_MAX_ICON_DIMENSION: u32 = 16384;
+
+/// PNG-backed ICNS element types and their exact pixel sizes (Retina slots included).
+const ICNS_SLOTS: [(&[u8; 4], u32); 7] = [
+    (b"icp4", 16), (b"icp5", 32), (b"icp6", 64), (b"ic07", 128),
+    (b"ic08", 256), (b"ic09", 512), (b"ic10", 1024),
+];
+
+/// Sizes installed into the hicolor icon theme on Linux.
+const THEME_ICON_SIZES: [u32; 6] = [16, 32, 48, 64, 128, 256];
+
+/// Conventional name of a numeric resource type ("RT_ICON"); unknown types render as "#<id>".
+#[cfg(target_os = "windows")]
+fn resource_type_name(id: u32) -> String {
+    let name = match id {
+        1 => "RT_CURSOR",
+        2 => "RT_BITMAP",
+        3 => "RT_ICON",
+        4 => "RT_MENU",
+        5 => "RT_DIALOG",
+        6 => "RT_STRING",
+        7 => "RT_FONTDIR",
+        8 => "RT_FONT",
+        9 => "RT_ACCELERATOR",
+        10 => "RT_RCDATA",
+        11 => "RT_MESSAGETABLE",
+        12 => "RT_GROUP_CURSOR",
+        14 => "RT_GROUP_ICON",
+        16 => "RT_VERSION",
+        17 => "RT_DLGINCLUDE",
+        19 => "RT_PLUGPLAY",
+        20 => "RT_VXD",
+        21 => "RT_ANICURSOR",
+        22 => "RT_ANIICON",
+        23 => "RT_HTML",
+        24 => "RT_MANIFEST",
+        _ => return format!("#{}", id),
+    };
+    name.to_string()
+}
+
+/// Flattens a resource tree into (type/id/language path, data length) pairs.
+#[cfg(target_os = "windows")]
+fn resource_leaves(table: &ResourceTable) -> Vec<(Vec<ResourceEntryName>, usize)> {
+    fn walk(table: &ResourceTable, path: &mut Vec<ResourceEntryName>, out: &mut Vec<(Vec<ResourceEntryName>, usize)>) {
+        for (name, entry) in table.entries.iter() {
+            path.push(name.clone());
+            match entry {
+                ResourceEntry::Table(sub) => walk(sub, path, out),
+                ResourceEntry::Data(data) => out.push((path.clone(), data.data().len())),
+            }
+            path.pop();
+        }
+    }
+    let mut out = Vec::new();
+    walk(table, &mut Vec::new(), &mut out);
+    out
+}
+
+/// Re-parses an edited image and confirms its resource directory matches what was written,
+/// so a relocation bug in the rewrite can never reach disk.
+#[cfg(target_os = "windows")]
+fn verify_resource_round_trip(data: &[u8], expected: &[(Vec<ResourceEntryName>, usize)]) -> PyResult<()> {
+    let image = Image::parse(data).map_err(|e| PyErr::new::<PEParseError, _>(format!("Edited PE failed to re-parse: {:?}", e)))?;
+    let actual = match image.resource_directory() {
+        Some(resources) => resource_leaves(resources.root()),
+        None if expected.is_empty() => return Ok(()),
+        None => return Err(PyErr::new::<PEParseError, _>("Edited PE lost its resource directory; aborting write")),
+    };
+    if actual != expected {
+        return Err(PyErr::new::<PEParseError, _>(format!(
+            "Edited PE has an inconsistent resource directory ({} entries written, {} resolvable); aborting write",
+            expected.len(),
+            actual.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Gathers the raw bytes of every data leaf below `table`.
+#[cfg(target_os = "windows")]
+fn collect_resource_data<'a>(table: &'a ResourceTable, out: &mut Vec<&'a [u8]>) {
+    for entry in table.entries.values() {
+        match entry {
+            ResourceEntry::Table(sub) => collect_resource_data(sub, out),
+            ResourceEntry::Data(data) => out.push(data.data()),
+        }
+    }
+}
+
+/// Every resource leaf keyed by its type/id/language path, in directory order.
+#[cfg(target_os = "windows")]
+fn resource_leaf_map(table: &ResourceTable) -> IndexMap<Vec<ResourceEntryName>, &[u8]> {
+    fn walk<'a>(table: &'a ResourceTable, path: &mut Vec<ResourceEntryName>, out: &mut IndexMap<Vec<ResourceEntryName>, &'a [u8]>) {
+        for (name, entry) in table.entries.iter() {
+            path.push(name.clone());
+            match entry {
+                ResourceEntry::Table(sub) => walk(sub, path, out),
+                ResourceEntry::Data(data) => {
+                    out.insert(path.clone(), data.data());
+                }
+            }
+            path.pop();
+        }
+    }
+    let mut out = IndexMap::new();
+    walk(table, &mut Vec::new(), &mut out);
+    out
+}
+
+/// "RT_ICON/1/1033" for a resource leaf path; the type uses its RT_* name.
+#[cfg(target_os = "windows")]
+fn resource_label(path: &[ResourceEntryName]) -> String {
+    path.iter().enumerate().map(|(depth, name)| match name {
+        ResourceEntryName::ID(id) if depth == 0 => resource_type_name(*id),
+        ResourceEntryName::ID(id) => id.to_string(),
+        ResourceEntryName::Name(name) => name.clone(),
+    }).collect::<Vec<_>>().join("/")
+}
+
+/// Raw data of every leaf under resource type `kind`, in directory order.
+#[cfg(target_os = "windows")]
+fn resource_leaf_data(root: &ResourceTable, kind: u32) -> Vec<&[u8]> {
+    let mut out = Vec::new();
+    if let Some(ResourceEntry::Table(table)) = root.get(ResourceEntryName::ID(kind)) {
+        collect_resource_data(table, &mut out);
+    }
+    out
+}
+
+/// Decodes an RT_STRING block: 16 strings, each a u16 length followed by UTF-16 code units.
+#[cfg(target_os = "windows")]
+fn decode_string_block(data: &[u8]) -> PyResult<Vec<String>> {
+    let truncated = || PyErr::new::<PEParseError, _>("Truncated RT_STRING block");
+    let mut strings = Vec::with_capacity(16);
+    let mut offset = 0;
+    for _ in 0..16 {
+        let len = data.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize).ok_or_else(truncated)?;
+        offset += 2;
+        let units: Vec<u16> = data.get(offset..offset + len * 2).ok_or_else(truncated)?
+            .chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        offset += len * 2;
+        strings.push(String::from_utf16_lossy(&units));
+    }
+    Ok(strings)
+}
+
+/// Encodes 16 strings as an RT_STRING block.
+#[cfg(target_os = "windows")]
+fn encode_string_block(strings: &[String]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for s in strings {
+        let units: Vec<u16> = s.encode_utf16().take(u16::MAX as usize).collect();
+        data.extend_from_slice(&(units.len() as u16).to_le_bytes());
+        for unit in units {
+            data.extend_from_slice(&unit.to_le_bytes());
+        }
+    }
+    data
+}
+
+/// Data of the first leaf under `entry` (first name, first language).
+#[cfg(target_os = "windows")]
+fn first_resource_data(entry: &ResourceEntry) -> Option<&[u8]> {
+    match entry {
+        ResourceEntry::Data(data) => Some(data.data()),
+        ResourceEntry::Table(table) => table.entries.values().next().and_then(first_resource_data),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn encode_frame(img: &image::DynamicImage, id: u32, format: image::ImageFormat) -> PyResult<Vec<u8>> {
+    let mut out = Vec::new();
+    img.write_to(&mut Cursor::new(&mut out), format)
+        .map_err(|e| PyErr::new::<IconError, _>(format!("Failed to encode icon frame {}: {}", id, e)))?;
+    Ok(out)
+}
+
+/// The largest decoded frame by area, breaking ties by color depth.
+#[cfg(target_os = "windows")]
+fn largest_frame(frames: &[(u16, u32, image::DynamicImage)]) -> Option<&(u16, u32, image::DynamicImage)> {
+    frames.iter().max_by_key(|(bit_count, _, img)| (img.width() as u64 * img.height() as u64, *bit_count))
+}
+
+/// Reassembles an `.ico` file from RT_GROUP_ICON entries and their RT_ICON frames: the
+/// group's 14-byte entries become 16-byte ICONDIRENTRYs with file offsets instead of ids.
+#[cfg(target_os = "windows")]
+fn build_ico(group: &[([u8; 14], Vec<u8>)]) -> Vec<u8> {
+    let mut ico = Vec::new();
+    ico.extend_from_slice(&0u16.to_le_bytes());
+    ico.extend_from_slice(&1u16.to_le_bytes());
+    ico.extend_from_slice(&(group.len() as u16).to_le_bytes());
+
+    let mut offset = 6 + 16 * group.len();
+    for (entry, frame) in group {
+        ico.extend_from_slice(&entry[..8]);
+        ico.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        ico.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += frame.len();
+    }
+    for (_, frame) in group {
+        ico.extend_from_slice(frame);
+    }
+    ico
+}
+
+/// Checks an ICO's frame directory: every entry's data lies inside the file after the
+/// directory, no two frames overlap, and each frame's PNG IHDR or BITMAPINFOHEADER declares
+/// the entry's dimensions (0 meaning 256). Returns the number of frames, or what's wrong.
+fn check_ico(data: &[u8]) -> Result<usize, String> {
+    let u16_at = |off: usize| data.get(off..off + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let u32_at = |off: usize| data.get(off..off + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+    if u16_at(0) != Some(0) || u16_at(2) != Some(1) {
+        return Err("not an ICO header".to_string());
+    }
+    let count = u16_at(4).unwrap_or(0) as usize;
+    if count == 0 {
+        return Err("no frames".to_string());
+    }
+    let directory_end = 6 + count * 16;
+    if directory_end > data.len() {
+        return Err(format!("directory of {} entries runs past the end of the file", count));
+    }
+
+    let mut ranges = Vec::with_capacity(count);
+    for i in 0..count {
+        let entry = &data[6 + i * 16..6 + (i + 1) * 16];
+        let dim = |b: u8| if b == 0 { 256 } else { b as u32 };
+        let (width, height) = (dim(entry[0]), dim(entry[1]));
+        let size = u32::from_le_bytes(entry[8..12].try_into().unwrap()) as usize;
+        let offset = u32::from_le_bytes(entry[12..16].try_into().unwrap()) as usize;
+        if offset < directory_end || offset + size > data.len() || size == 0 {
+            return Err(format!("frame {} ({} bytes at {:#x}) lies outside the image data", i, size, offset));
+        }
+        let frame = &data[offset..offset + size];
+        let declared = if frame.starts_with(b"\x89PNG") {
+            // IHDR width and height, big-endian, right after the signature and chunk header
+            frame.get(16..24).map(|b| (u32::from_be_bytes(b[..4].try_into().unwrap()), u32::from_be_bytes(b[4..].try_into().unwrap())))
+        } else {
+            // BITMAPINFOHEADER height covers the XOR and AND masks
+            u32_at(offset + 4).zip(u32_at(offset + 8)).map(|(w, h)| (w, h / 2))
+        };
+        if declared != Some((width, height)) {
+            return Err(format!(
+                "frame {} is listed as {}x{} but encodes {}", i, width, height,
+                declared.map_or("no readable size".to_string(), |(w, h)| format!("{}x{}", w, h))
+            ));
+        }
+        ranges.push((offset, offset + size, i));
+    }
+    ranges.sort_unstable();
+    if let Some(pair) = ranges.windows(2).find(|pair| pair[1].0 < pair[0].1) {
+        return Err(format!("frames {} and {} overlap", pair[0].2, pair[1].2));
+    }
+    Ok(count)
+}
+
+/// Decodes one RT_ICON frame. BMP frames lack a file header, so they are wrapped in a
+/// single-entry ICO and go through the ICO decoder, which also applies the AND mask.
+#[cfg(target_os = "windows")]
+fn decode_icon_frame(frame: &[u8]) -> Option<image::DynamicImage> {
+    if frame.starts_with(b"\x89PNG") {
+        return image::load_from_memory_with_format(frame, image::ImageFormat::Png).ok();
+    }
+
+    let width = i32::from_le_bytes(frame.get(4..8)?.try_into().ok()?);
+    // BITMAPINFOHEADER height covers the XOR and AND masks, so it is doubled
+    let height = i32::from_le_bytes(frame.get(8..12)?.try_into().ok()?) / 2;
+    let mut ico = Vec::with_capacity(22 + frame.len());
+    ico.extend_from_slice(&[0, 0, 1, 0, 1, 0]);
+    ico.push(if width >= 256 { 0 } else { width as u8 });
+    ico.push(if height >= 256 { 0 } else { height as u8 });
+    ico.extend_from_slice(&[0, 0, 1, 0]);
+    ico.extend_from_slice(frame.get(14..16)?);
+    ico.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+    ico.extend_from_slice(&22u32.to_le_bytes());
+    ico.extend_from_slice(frame);
+    image::load_from_memory_with_format(&ico, image::ImageFormat::Ico).ok()
+}
+
+/// Splits an ICO file into the encoded image data of each frame (PNG or BMP without file header).
+#[cfg(target_os = "windows")]
+fn ico_frame_data(ico: &[u8]) -> Vec<&[u8]> {
+    let mut frames = Vec::new();
+    if ico.len() < 6 {
+        return frames;
+    }
+    let count = u16::from_le_bytes([ico[4], ico[5]]) as usize;
+    for i in 0..count {
+        let entry = 6 + i * 16;
+        if ico.len() < entry + 16 {
+            break;
+        }
+        let size = u32::from_le_bytes(ico[entry + 8..entry + 12].try_into().unwrap()) as usize;
+        let offset = u32::from_le_bytes(ico[entry + 12..entry + 16].try_into().unwrap()) as usize;
+        if let Some(frame) = ico.get(offset..offset.saturating_add(size)) {
+            frames.push(frame);
+        }
+    }
+    frames
+}
+
+// How apply() treats signed input, set by `set_signed_policy()`
+const SIGNED_STRIP: u8 = 0;
+const SIGNED_REFUSE: u8 = 1;
+const SIGNED_KEEP: u8 = 2;
+static SIGNED_POLICY: AtomicU8 = AtomicU8::new(SIGNED_STRIP);
+
+/// Module-level metadata applied to each new editor, from `set_defaults()`.
+static DEFAULTS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Where `write_atomic` creates its temp file, from `set_temp_dir()`. Unset means next to the target.
+static TEMP_DIR: Mutex<Option<std::path::PathBuf>> = Mutex::new(None);
+
+/// Per-process sequence number that keeps concurrent `write_atomic` temp names apart.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Set by `set_quiet()`; `METAEDIT_QUIET=1` has the same effect.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Prints informational output unless quiet mode is on.
+fn info(message: &str) {
+    let env_quiet = std::env::var("METAEDIT_QUIET").is_ok_and(|v| !v.is_empty() && v != "0");
+    if !QUIET.load(Ordering::Relaxed) && !env_quiet {
+        println!("{}", message);
+    }
+}
+
+/// Renders the placeholder icon: a slate rounded square with a light frame, 256px PNG.
+fn default_icon_png() -> PyResult<Vec<u8>> {
+    const SIZE: u32 = 256;
+    const RADIUS: f32 = 48.0;
+
+    let img = image::RgbaImage::from_fn(SIZE, SIZE, |x, y| {
+        // Distance outside the rounded corner arcs; 0 inside the shape
+        let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+        let cx = fx.clamp(RADIUS, SIZE as f32 - RADIUS);
+        let cy = fy.clamp(RADIUS, SIZE as f32 - RADIUS);
+        let outside = ((fx - cx).powi(2) + (fy - cy).powi(2)).sqrt() - RADIUS;
+        if outside > 0.0 {
+            return image::Rgba([0, 0, 0, 0]);
+        }
+        let inset = x.min(y).min(SIZE - 1 - x).min(SIZE - 1 - y);
+        if (56..72).contains(&inset) {
+            image::Rgba([226, 232, 240, 255])
+        } else {
+            image::Rgba([71, 85, 105, 255])
+        }
+    });
+
+    let mut png = Vec::new();
+    image::DynamicImage::ImageRgba8(img).write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| PyErr::new::<IconError, _>(format!("Failed to render default icon: {}", e)))?;
+    Ok(png)
+}
+
+/// Emits a Python `UserWarning` so problems that don't abort the edit are still visible.
+fn warn(message: &str) -> PyResult<()> {
+    Python::with_gil(|py| {
+        let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+        PyErr::warn(py, &py.get_type::<pyo3::exceptions::PyUserWarning>(), &message, 1)
+    })
+}
+
+/// Encodes `img` as a packed DIB (BITMAPINFOHEADER + bottom-up pixels), the RT_BITMAP
+/// layout: 24-bit BGR for opaque images, 32-bit BGRA when there's an alpha channel.
+#[cfg(target_os = "windows")]
+fn encode_dib(img: &image::DynamicImage) -> Vec<u8> {
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let bit_count: u16 = if img.color().has_alpha() { 32 } else { 24 };
+    let row_size = (width * bit_count as u32).div_ceil(32) * 4;
+
+    let mut data = Vec::with_capacity((40 + row_size * height) as usize);
+    data.extend_from_slice(&(40u32).to_le_bytes()); // biSize
+    data.extend_from_slice(&(width as i32).to_le_bytes()); // biWidth
+    data.extend_from_slice(&(height as i32).to_le_bytes()); // biHeight (positive: bottom-up)
+    data.extend_from_slice(&(1u16).to_le_bytes()); // biPlanes
+    data.extend_from_slice(&bit_count.to_le_bytes()); // biBitCount
+    data.extend_from_slice(&(0u32).to_le_bytes()); // biCompression (BI_RGB)
+    data.extend_from_slice(&(row_size * height).to_le_bytes()); // biSizeImage
+    data.extend_from_slice(&(0u32).to_le_bytes()); // biXPelsPerMeter
+    data.extend_from_slice(&(0u32).to_le_bytes()); // biYPelsPerMeter
+    data.extend_from_slice(&(0u32).to_le_bytes()); // biClrUsed
+    data.extend_from_slice(&(0u32).to_le_bytes()); // biClrImportant
+
+    for y in (0..height).rev() {
+        let row_start = data.len();
+        for x in 0..width {
+            let pixel = rgba.get_pixel(x, y);
+            data.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+            if bit_count == 32 {
+                data.push(pixel[3]);
+            }
+        }
+        data.resize(row_start + row_size as usize, 0);
+    }
+    data
+}
+
+/// Encodes a DIB frame for an ICO: BITMAPINFOHEADER, palette (below 32 bits), XOR bitmap
+/// and the 1-bit AND mask.
+///
+/// The AND mask is always written, even for 32-bit frames whose alpha channel makes it
+/// redundant when drawing. The ICO format defines the frame as XOR + AND (hence the doubled
+/// biHeight), and `CreateIconFromResourceEx` sizes its read from that. Windows still uses the
+/// mask whenever alpha can't be used: DrawIconEx with DI_MASK, cursors, remote sessions and
+/// displays below 32 bits per pixel. The mask costs width * height / 8 bytes per frame (128
+/// bytes at 32x32), so there is no option to drop it.
+#[cfg(target_os = "windows")]
+fn create_ico_bmp_data(img: &image::DynamicImage, width: u32, height: u32, bit_depth: u16) -> PyResult<Vec<u8>> {
+    let rgba = img.to_rgba8();
+
+    // Paletted frames have no alpha channel, so only the AND mask can express transparency
+    let is_transparent = |alpha: u8| if bit_depth == 32 { alpha == 0 } else { alpha < 128 };
+    
+    // Each row in the AND mask must be a multiple of 4 bytes (32 bits)
+    // Formula: ((width + 31) / 32) * 4
+    let mask_row_size = width.div_ceil(32) * 4;
+    let mask_size = mask_row_size * height;
+
+    // Paletted rows are also padded to 4 bytes
+    let xor_row_size = (width * bit_depth as u32).div_ceil(32) * 4;
+    let palette = if bit_depth < 32 { Some(quantize_icon_palette(&rgba, 1 << bit_depth)) } else { None };
+    let palette_len = palette.as_ref().map_or(0, |colors| colors.len() as u32);
+    
+    // Header (40) + palette + XOR data + AND mask
+    let data_size = 40 + palette_len * 4 + xor_row_size * height + mask_size;
+    let mut data = Vec::with_capacity(data_size as usize);
+
+    // BITMAPINFOHEADER (40 bytes)
+    data.extend_from_slice(&(40u32).to_le_bytes()); // biSize
+    data.extend_from_slice(&(width as i32).to_le_bytes()); // biWidth
+    // ICO BMPs often use (height * 2) in the header to indicate XOR+AND combination
+    data.extend_from_slice(&((height * 2) as i32).to_le_bytes()); // biHeight
+    data.extend_from_slice(&(1u16).to_le_bytes()); // biPlanes
+    data.extend_from_slice(&bit_depth.to_le_bytes()); // biBitCount (BGRA at 32, palette indices below)
+    data.extend_from_slice(&(0u32).to_le_bytes()); // biCompression (BI_RGB)
+    data.extend_from_slice(&(0u32).to_le_bytes()); // biSizeImage (can be 0 for BI_RGB)
+    data.extend_from_slice(&(0u32).to_le_bytes()); // biXPelsPerMeter
+    data.extend_from_slice(&(0u32).to_le_bytes()); // biYPelsPerMeter
+    data.extend_from_slice(&palette_len.to_le_bytes()); // biClrUsed
+    data.extend_from_slice(&(0u32).to_le_bytes()); // biClrImportant
+
+    match &palette {
+        None => {
+            // XOR Mask (Pixel Data) - Stored Bottom-Up, BGRA format
+            for y in (0..height).rev() {
+                for x in 0..width {
+                    let pixel = rgba.get_pixel(x, y);
+                    data.push(pixel[2]); // B
+                    data.push(pixel[1]); // G
+                    data.push(pixel[0]); // R
+                    data.push(pixel[3]); // A
+                }
+            }
+        }
+        Some(colors) => {
+            // Color table (RGBQUAD: B, G, R, reserved)
+            for color in colors {
+                data.extend_from_slice(&[color[2], color[1], color[0], 0]);
+            }
+            // XOR Mask - Stored Bottom-Up, packed palette indices (MSB first)
+            let per_byte = 8 / bit_depth as u32;
+            for y in (0..height).rev() {
+                let mut row_bytes = vec![0u8; xor_row_size as usize];
+                for x in 0..width {
+                    let pixel = rgba.get_pixel(x, y);
+                    // Index 0 is reserved black so transparent pixels render correctly under the AND mask
+                    let index = if is_transparent(pixel[3]) { 0 } else { nearest_palette_index(colors, &pixel.0) };
+                    let shift = (per_byte - 1 - x % per_byte) * bit_depth as u32;
+                    row_bytes[(x / per_byte) as usize] |= index << shift;
+                }
+                data.extend_from_slice(&row_bytes);
+            }
+        }
+    }
+
+    // AND Mask (1-bit transparency) - Stored Bottom-Up
+    // 0 = Opaque, 1 = Transparent. Always present; see the function docs for why.
+    for y in (0..height).rev() {
+        let mut row_bytes = vec![0u8; mask_row_size as usize];
         for x in 0..width {
             let pixel = rgba.get_pixel(x, y);
-            // If alpha is 0, we mark it as transparent (1) in the AND mask.
+            // If the pixel is transparent, we mark it as transparent (1) in the AND mask.
             // Otherwise opaque (0).
-            if pixel[3] == 0 {
+            if is_transparent(pixel[3]) {
                 let byte_idx = (x / 8) as usize;
                 let bit_idx = 7 - (x % 8);
                 row_bytes[byte_idx] |= 1 << bit_idx;
@@ -395,17 +4747,455 @@ fn create_ico_bmp_data(img: &image::DynamicImage, width: u32, height: u32) -> Py
     Ok(data)
 }
 
+/// Builds a palette of `colors` entries for a paletted ICO frame using NeuQuant.
+/// Entry 0 is always black, used under transparent pixels.
 #[cfg(target_os = "windows")]
-fn strip_pe_signature(data: &mut Vec<u8>) -> bool {
+fn quantize_icon_palette(rgba: &image::RgbaImage, colors: usize) -> Vec<[u8; 3]> {
+    let mut opaque: Vec<u8> = rgba.pixels().filter(|p| p[3] >= 128).flat_map(|p| [p[0], p[1], p[2], 255]).collect();
+    if opaque.is_empty() {
+        opaque.extend_from_slice(&[0, 0, 0, 255]);
+    }
+    // Icons are small, so sample every pixel for the best palette
+    let quantizer = color_quant::NeuQuant::new(1, colors - 1, &opaque);
+
+    let mut palette = vec![[0u8; 3]];
+    for chunk in quantizer.color_map_rgb().chunks(3) {
+        palette.push([chunk[0], chunk[1], chunk[2]]);
+    }
+    palette
+}
+
+/// Closest palette entry by squared RGB distance, skipping the reserved transparent entry 0.
+#[cfg(target_os = "windows")]
+fn nearest_palette_index(palette: &[[u8; 3]], pixel: &[u8; 4]) -> u8 {
+    let distance = |c: &[u8; 3]| (0..3).map(|i| (c[i] as i32 - pixel[i] as i32).pow(2)).sum::<i32>();
+    (1..palette.len()).min_by_key(|&i| distance(&palette[i])).unwrap_or(0) as u8
+}
+
+/// Copies the DOS stub (everything between the DOS header and the PE signature, which
+/// includes the linker's Rich header) from `original` back into `rewritten`, so a resource
+/// edit never disturbs it. Returns false if the PE header moved and the stub can't be
+/// restored in place.
+#[cfg(target_os = "windows")]
+fn restore_dos_stub(original: &[u8], rewritten: &mut [u8]) -> bool {
+    let e_lfanew = |data: &[u8]| data.get(0x3c..0x40).map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize);
+    match (e_lfanew(original), e_lfanew(rewritten)) {
+        (Some(a), Some(b)) if a == b && a >= 0x40 && a <= original.len() && a <= rewritten.len() => {
+            rewritten[0x40..a].copy_from_slice(&original[0x40..a]);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Points the Optional Header's Resource Table entry (data directory 2) at the resource
+/// directory when it doesn't already resolve to one, e.g. after the rewrite moved resources
+/// into a fresh `.rsrc` section. Returns true if the entry was changed.
+#[cfg(target_os = "windows")]
+fn fix_resource_data_directory(data: &mut [u8]) -> bool {
+    let read_u16 = |data: &[u8], off: usize| data.get(off..off + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let read_u32 = |data: &[u8], off: usize| data.get(off..off + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+
+    let Some(e_lfanew) = read_u32(data, 0x3c).map(|v| v as usize) else { return false };
+    if data.get(e_lfanew..e_lfanew + 4) != Some(b"PE\0\0".as_slice()) {
+        return false;
+    }
+    let (Some(section_count), Some(opt_header_size)) = (read_u16(data, e_lfanew + 6), read_u16(data, e_lfanew + 20)) else {
+        return false;
+    };
+    let opt_header_offset = e_lfanew + 24;
+    let (rva_count_offset, rsrc_dir) = match read_u16(data, opt_header_offset) {
+        Some(0x10b) => (opt_header_offset + 92, opt_header_offset + 96 + 16),
+        Some(0x20b) => (opt_header_offset + 108, opt_header_offset + 112 + 16),
+        _ => return false,
+    };
+    if read_u32(data, rva_count_offset).is_none_or(|count| count < 3) {
+        return false;
+    }
+
+    // (name, virtual address, virtual size, raw pointer, raw size) per section
+    let sections_offset = opt_header_offset + opt_header_size as usize;
+    let sections: Vec<([u8; 8], u32, u32, u32, u32)> = (0..section_count as usize)
+        .filter_map(|i| {
+            let header = sections_offset + i * 40;
+            Some((
+                data.get(header..header + 8)?.try_into().ok()?,
+                read_u32(data, header + 12)?,
+                read_u32(data, header + 8)?,
+                read_u32(data, header + 20)?,
+                read_u32(data, header + 16)?,
+            ))
+        })
+        .collect();
+
+    // A plausible IMAGE_RESOURCE_DIRECTORY: at least one entry, all pointing inside the section
+    let is_resource_dir = |rva: u32| {
+        sections.iter().any(|&(_, virt_addr, virt_size, raw_ptr, raw_size)| {
+            if rva < virt_addr || rva >= virt_addr + virt_size.max(raw_size) {
+                return false;
+            }
+            let dir = (raw_ptr + (rva - virt_addr)) as usize;
+            let available = (virt_addr + raw_size).saturating_sub(rva);
+            let entries = match (read_u16(data, dir + 12), read_u16(data, dir + 14)) {
+                (Some(named), Some(ids)) => named as usize + ids as usize,
+                _ => return false,
+            };
+            entries > 0
+                && 16 + entries as u32 * 8 <= available
+                && (0..entries).all(|i| read_u32(data, dir + 20 + i * 8).is_some_and(|o| o & 0x7fff_ffff < available))
+        })
+    };
+
+    let current = read_u32(data, rsrc_dir).unwrap_or(0);
+    if current != 0 && is_resource_dir(current) {
+        return false;
+    }
+    // The rewrite appends its own section, so prefer the last `.rsrc`
+    let Some(&(_, virt_addr, virt_size, _, raw_size)) = sections.iter().rev()
+        .find(|(name, virt_addr, ..)| name.starts_with(b".rsrc") && is_resource_dir(*virt_addr))
+    else {
+        return false;
+    };
+    let size = if virt_size != 0 { virt_size } else { raw_size };
+    data[rsrc_dir..rsrc_dir + 4].copy_from_slice(&virt_addr.to_le_bytes());
+    data[rsrc_dir + 4..rsrc_dir + 8].copy_from_slice(&size.to_le_bytes());
+    true
+}
+
+/// Overwrites the file's single RT_VERSION resource with `blob` in place, without rebuilding
+/// `.rsrc`. Returns None (so the caller falls back to a full rewrite) when the resource is
+/// missing, ambiguous or too small to hold the new data.
+#[cfg(target_os = "windows")]
+fn patch_version_resource(data: &[u8], blob: &[u8]) -> Option<Vec<u8>> {
+    let read_u16 = |off: usize| data.get(off..off + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let read_u32 = |off: usize| data.get(off..off + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+
+    let e_lfanew = read_u32(0x3c)? as usize;
+    if data.get(e_lfanew..e_lfanew + 4)? != b"PE\0\0" {
+        return None;
+    }
+    let section_count = read_u16(e_lfanew + 6)? as usize;
+    let opt_header_size = read_u16(e_lfanew + 20)? as usize;
+    let opt_header_offset = e_lfanew + 24;
+    // Resource directory is data directory index 2
+    let rsrc_dir = match read_u16(opt_header_offset)? {
+        0x10b => opt_header_offset + 96 + 16,
+        0x20b => opt_header_offset + 112 + 16,
+        _ => return None,
+    };
+    let rsrc_rva = read_u32(rsrc_dir)?;
+
+    let sections = opt_header_offset + opt_header_size;
+    let rva_to_offset = |rva: u32| -> Option<usize> {
+        (0..section_count).find_map(|i| {
+            let header = sections + i * 40;
+            let virt_size = read_u32(header + 8)?;
+            let virt_addr = read_u32(header + 12)?;
+            let raw_size = read_u32(header + 16)?;
+            let raw_ptr = read_u32(header + 20)?;
+            let span = virt_size.max(raw_size);
+            (rva >= virt_addr && rva < virt_addr + span).then(|| (raw_ptr + (rva - virt_addr)) as usize)
+        })
+    };
+    let rsrc = rva_to_offset(rsrc_rva)?;
+
+    // Each IMAGE_RESOURCE_DIRECTORY level must hold exactly one matching entry
+    let single_entry = |dir: usize, id: Option<u32>| -> Option<u32> {
+        let named = read_u16(dir + 12)? as usize;
+        let ids = read_u16(dir + 14)? as usize;
+        let entries: Vec<(u32, u32)> = (0..named + ids)
+            .map(|i| Some((read_u32(dir + 16 + i * 8)?, read_u32(dir + 20 + i * 8)?)))
+            .collect::<Option<_>>()?;
+        let mut matching = entries.iter().filter(|(name, _)| id.is_none_or(|id| *name == id));
+        match (matching.next(), matching.next()) {
+            (Some(&(_, offset)), None) => Some(offset),
+            _ => None,
+        }
+    };
+    let subdir = |offset: u32| (offset & 0x8000_0000 != 0).then(|| rsrc + (offset & 0x7fff_ffff) as usize);
+
+    let names = subdir(single_entry(rsrc, Some(RT_VERSION))?)?;
+    let langs = subdir(single_entry(names, None)?)?;
+    let leaf = single_entry(langs, None)?;
+    if leaf & 0x8000_0000 != 0 {
+        return None;
+    }
+
+    // IMAGE_RESOURCE_DATA_ENTRY: OffsetToData (RVA), Size, CodePage, Reserved
+    let data_entry = rsrc + leaf as usize;
+    let old_size = read_u32(data_entry + 4)? as usize;
+    let blob_offset = rva_to_offset(read_u32(data_entry)?)?;
+    if blob.len() > old_size || data.len() < blob_offset + old_size {
+        return None;
+    }
+
+    let mut patched = data.to_vec();
+    patched[blob_offset..blob_offset + blob.len()].copy_from_slice(blob);
+    patched[blob_offset + blob.len()..blob_offset + old_size].fill(0);
+    patched[data_entry + 4..data_entry + 8].copy_from_slice(&(blob.len() as u32).to_le_bytes());
+    Some(patched)
+}
+
+/// "1.2.3-beta" -> "1,2,3,0", the comma-separated quad `FILEVERSION` expects. Unparseable versions become 0,0,0,0.
+fn rc_version_quad(version: &str) -> String {
+    let mut parts = parse_version(version.to_string()).map(|(parts, _)| parts).unwrap_or_default();
+    parts.resize(4, 0);
+    parts.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",")
+}
+
+/// MSI ProductVersion: three numeric fields within 255.255.65535. A fourth field is
+/// ignored by Windows Installer, so it is dropped.
+fn wix_version(version: &str) -> PyResult<String> {
+    let (mut parts, _) = parse_version(version.to_string())?;
+    parts.resize(3, 0);
+    if parts[0] > 255 || parts[1] > 255 || parts[2] > 65535 {
+        return Err(PyErr::new::<MetaEditError, _>(format!(
+            "Version '{}' exceeds the MSI ProductVersion limits of 255.255.65535", version
+        )));
+    }
+    Ok(format!("{}.{}.{}", parts[0], parts[1], parts[2]))
+}
+
+/// Escapes a value for an `.rc` string literal (doubled quotes, escaped backslashes).
+fn rc_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\"\"")
+}
+
+/// The current calendar year (UTC), or that of `SOURCE_DATE_EPOCH` when it's set.
+fn current_year() -> PyResult<u32> {
+    let secs = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(value) => value.trim().parse::<i64>()
+            .map_err(|_| PyErr::new::<MetaEditError, _>(format!("Invalid SOURCE_DATE_EPOCH '{}'", value)))?,
+        Err(_) => std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64),
+    };
+    // Civil-from-days (Howard Hinnant's algorithm), reduced to the year
+    let days = secs.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    // March-based years: January and February belong to the next calendar year
+    let year = year_of_era + era * 400 + if month_index >= 10 { 1 } else { 0 };
+    Ok(year as u32)
+}
+
+/// A random (version 4) UUID. std's `RandomState` is seeded from the OS RNG per instance,
+/// which is plenty for build ids without pulling in a dependency.
+fn random_uuid() -> String {
+    use std::hash::{BuildHasher, Hasher};
+    let half = |salt: u64| {
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        hasher.write_u64(salt);
+        hasher.finish()
+    };
+    let mut bytes = [0u8; 16];
+    bytes[..8].copy_from_slice(&half(0).to_be_bytes());
+    bytes[8..].copy_from_slice(&half(1).to_be_bytes());
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// COFF File Header Characteristics flags by their `IMAGE_FILE_` name.
+const COFF_CHARACTERISTICS: [(&str, u16); 15] = [
+    ("RELOCS_STRIPPED", 0x0001),
+    ("EXECUTABLE_IMAGE", 0x0002),
+    ("LINE_NUMS_STRIPPED", 0x0004),
+    ("LOCAL_SYMS_STRIPPED", 0x0008),
+    ("AGGRESSIVE_WS_TRIM", 0x0010),
+    ("LARGE_ADDRESS_AWARE", 0x0020),
+    ("BYTES_REVERSED_LO", 0x0080),
+    ("32BIT_MACHINE", 0x0100),
+    ("DEBUG_STRIPPED", 0x0200),
+    ("REMOVABLE_RUN_FROM_SWAP", 0x0400),
+    ("NET_RUN_FROM_SWAP", 0x0800),
+    ("SYSTEM", 0x1000),
+    ("DLL", 0x2000),
+    ("UP_SYSTEM_ONLY", 0x4000),
+    ("BYTES_REVERSED_HI", 0x8000),
+];
+
+/// Offset of the Characteristics field: `e_lfanew + 4` (PE signature) `+ 18` into the
+/// COFF File Header.
+fn coff_characteristics_offset(data: &[u8]) -> Option<usize> {
+    let e_lfanew = u32::from_le_bytes(data.get(0x3c..0x40)?.try_into().unwrap()) as usize;
+    if data.get(e_lfanew..e_lfanew + 4)? != b"PE\0\0" {
+        return None;
+    }
+    let offset = e_lfanew + 4 + 18;
+    data.get(offset..offset + 2).map(|_| offset)
+}
+
+/// End of the PE image proper in the file: the furthest of the headers, each section's raw
+/// data and the COFF symbol table with its string table. The certificate table is not
+/// included.
+fn pe_image_end(data: &[u8]) -> Option<usize> {
+    let read_u16 = |off: usize| data.get(off..off + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let read_u32 = |off: usize| data.get(off..off + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+
+    let e_lfanew = read_u32(0x3c)? as usize;
+    if data.get(e_lfanew..e_lfanew + 4)? != b"PE\0\0" {
+        return None;
+    }
+    let section_count = read_u16(e_lfanew + 6)? as usize;
+    let opt_header_offset = e_lfanew + 24;
+    let sections_offset = opt_header_offset + read_u16(e_lfanew + 20)? as usize;
+
+    // SizeOfHeaders sits at the same offset in PE32 and PE32+
+    let mut end = (read_u32(opt_header_offset + 60)? as usize).max(sections_offset + section_count * 40);
+    for i in 0..section_count {
+        let header = sections_offset + i * 40;
+        let (raw_size, raw_ptr) = (read_u32(header + 16)?, read_u32(header + 20)?);
+        if raw_size != 0 {
+            end = end.max(raw_ptr as usize + raw_size as usize);
+        }
+    }
+
+    // Unstripped MinGW builds keep a COFF symbol table after the sections
+    let (symbols, symbol_count) = (read_u32(e_lfanew + 12)? as usize, read_u32(e_lfanew + 16)? as usize);
+    if symbols != 0 {
+        let strings = symbols + symbol_count * 18;
+        end = end.max(strings + read_u32(strings).unwrap_or(0) as usize);
+    }
+    Some(end)
+}
+
+/// Where the overlay starts: the end of the image, extended over a certificate table that
+/// directly follows it.
+fn overlay_start(data: &[u8]) -> Option<usize> {
+    let end = pe_image_end(data)?;
+    Some(match read_security_dir(data) {
+        Some((_, offset, size)) if offset != 0 && size != 0 && offset as usize <= end => end.max(offset as usize + size as usize),
+        _ => end,
+    })
+}
+
+/// Whether everything from `start` (the end of the image) to the end of `data` is
+/// WIN_CERTIFICATE records, each padded to 8 bytes: a certificate table a partial strip
+/// left behind after clearing its directory entry.
+fn is_certificate_tail(data: &[u8], start: usize) -> bool {
+    let mut pos = start.next_multiple_of(8);
+    if pos >= data.len() || data[start..pos].iter().any(|&b| b != 0) {
+        return false;
+    }
+    while pos < data.len() {
+        let Some(header) = data.get(pos..pos + 8) else { return false };
+        let length = u32::from_le_bytes(header[0..4].try_into().unwrap()) as usize;
+        let revision = u16::from_le_bytes([header[4], header[5]]);
+        let kind = u16::from_le_bytes([header[6], header[7]]);
+        if length < 8 || pos + length > data.len() || !matches!(revision, 0x0100 | 0x0200) || !matches!(kind, 1..=4) {
+            return false;
+        }
+        pos = (pos + length).next_multiple_of(8);
+    }
+    true
+}
+
+/// Offset of the Optional Header CheckSum field and its stored value.
+fn pe_checksum_field(data: &[u8]) -> Option<(usize, u32)> {
+    let e_lfanew = data.get(0x3c..0x40).map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize)?;
+    let offset = e_lfanew + 24 + 64;
+    let stored = data.get(offset..offset + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()))?;
+    Some((offset, stored))
+}
+
+/// The ImageHlp checksum of `data`: a 16-bit one's-complement style sum that skips the
+/// CheckSum field at `offset`, plus the file length.
+fn compute_pe_checksum(data: &[u8], offset: usize) -> u32 {
+    let mut sum: u64 = 0;
+    for (i, chunk) in data.chunks(2).enumerate() {
+        if i * 2 == offset || i * 2 == offset + 2 {
+            continue;
+        }
+        sum += u16::from_le_bytes([chunk[0], *chunk.get(1).unwrap_or(&0)]) as u64;
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    (sum as u32 & 0xffff) + data.len() as u32
+}
+
+/// Whether the stored CheckSum matches `data`; a zero (unset) checksum counts as valid.
+fn pe_checksum_valid(data: &[u8]) -> bool {
+    match pe_checksum_field(data) {
+        Some((offset, stored)) => stored == 0 || compute_pe_checksum(data, offset) == stored,
+        None => true,
+    }
+}
+
+/// Recomputes the Optional Header CheckSum after the file changed. A zero checksum means
+/// "not checked" and stays zero.
+fn update_pe_checksum(data: &mut [u8]) {
+    let Some((offset, stored)) = pe_checksum_field(data) else { return };
+    if stored == 0 {
+        return;
+    }
+    let checksum = compute_pe_checksum(data, offset);
+    data[offset..offset + 4].copy_from_slice(&checksum.to_le_bytes());
+}
+
+/// The Optional Header fields `get_optional_header` reports, as (snake_case name, value).
+fn optional_header_fields(data: &[u8]) -> Option<Vec<(&'static str, u64)>> {
+    let read_u16 = |off: usize| data.get(off..off + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as u64);
+    let read_u32 = |off: usize| data.get(off..off + 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()) as u64);
+    let read_u64 = |off: usize| data.get(off..off + 8).map(|b| u64::from_le_bytes(b.try_into().unwrap()));
+
+    let e_lfanew = read_u32(0x3c)? as usize;
+    if data.get(e_lfanew..e_lfanew + 4)? != b"PE\0\0" {
+        return None;
+    }
+    let opt = e_lfanew + 24;
+    let magic = read_u16(opt)?;
+    // PE32+ widens ImageBase and drops BaseOfData, shifting the tail of the header by 16 bytes
+    let (image_base, tail) = match magic {
+        0x10b => (read_u32(opt + 28)?, opt + 92),
+        0x20b => (read_u64(opt + 24)?, opt + 108),
+        _ => return None,
+    };
+    Some(vec![
+        ("magic", magic),
+        ("address_of_entry_point", read_u32(opt + 16)?),
+        ("image_base", image_base),
+        ("section_alignment", read_u32(opt + 32)?),
+        ("file_alignment", read_u32(opt + 36)?),
+        ("size_of_image", read_u32(opt + 56)?),
+        ("size_of_headers", read_u32(opt + 60)?),
+        ("checksum", read_u32(opt + 64)?),
+        ("subsystem", read_u16(opt + 68)?),
+        ("dll_characteristics", read_u16(opt + 70)?),
+        ("loader_flags", read_u32(tail - 4)?),
+        ("number_of_rva_and_sizes", read_u32(tail)?),
+    ])
+}
+
+/// Whether the PE carries a certificate table, for callers that act on the answer (is_signed,
+/// the signed policy). `read_security_dir` can only say None for an image with fewer than 5
+/// data directories; here that is a PEParseError naming the count instead of "unsigned".
+fn pe_signed(data: &[u8], path: &str) -> PyResult<bool> {
+    let count = optional_header_fields(data)
+        .and_then(|fields| fields.into_iter().find(|(name, _)| *name == "number_of_rva_and_sizes"))
+        .map(|(_, count)| count);
+    if let Some(count) = count.filter(|&count| count < 5) {
+        return Err(PyErr::new::<PEParseError, _>(format!(
+            "{} has NumberOfRvaAndSizes = {}, so there is no Security directory entry (index 4) to read", path, count
+        )));
+    }
+    Ok(matches!(read_security_dir(data), Some((_, virt_addr, size)) if virt_addr != 0 && size != 0))
+}
+
+/// Locates the Security Directory entry and returns (entry offset, certificate table offset, size).
+fn read_security_dir(data: &[u8]) -> Option<(usize, u32, u32)> {
     // Minimum size for DOS header + PE Sig + File Header
-    if data.len() < 0x40 { return false; }
+    if data.len() < 0x40 { return None; }
     
     // Read e_lfanew (offset to PE header)
     let e_lfanew = u32::from_le_bytes(data[0x3c..0x40].try_into().unwrap()) as usize;
-    if data.len() < e_lfanew + 4 + 20 + 2 { return false; }
+    if data.len() < e_lfanew + 4 + 20 + 2 { return None; }
     
     // Validate PE signature "PE\0\0"
-    if &data[e_lfanew..e_lfanew+4] != b"PE\0\0" { return false; }
+    if &data[e_lfanew..e_lfanew+4] != b"PE\0\0" { return None; }
     
     // Optional Header Magic is at e_lfanew + 4 (Sig) + 20 (FileHeader)
     let opt_header_offset = e_lfanew + 24;
@@ -418,15 +5208,214 @@ fn strip_pe_signature(data: &mut Vec<u8>) -> bool {
     let rva_offset = match magic {
         0x10b => opt_header_offset + 96 + 32,
         0x20b => opt_header_offset + 112 + 32,
-        _ => { println!("DEBUG: Unknown magic: {:x}, opt_header_offset: {}", magic, opt_header_offset); return false; },
+        _ => return None,
     };
     
-    if data.len() < rva_offset + 8 { println!("DEBUG: File too short for rva"); return false; }
+    // Images with fewer than 5 data directories have no Security entry; reading one
+    // would land in the section table
+    let rva_count = u32::from_le_bytes(data.get(rva_offset - 32 - 4..rva_offset - 32)?.try_into().unwrap());
+    if rva_count < 5 { return None; }
+
+    if data.len() < rva_offset + 8 { return None; }
     
     let virt_addr = u32::from_le_bytes(data[rva_offset..rva_offset+4].try_into().unwrap());
     let size = u32::from_le_bytes(data[rva_offset+4..rva_offset+8].try_into().unwrap());
-    
-    println!("DEBUG: Found Security Dir at offset {}: VA={:x}, Size={}", rva_offset, virt_addr, size);
+    Some((rva_offset, virt_addr, size))
+}
+
+const DPI_AWARENESS_MODES: [&str; 4] = ["unaware", "system", "per_monitor", "per_monitor_v2"];
+
+/// Rewrites the `<dpiAware>` / `<dpiAwareness>` settings of an application manifest,
+/// synthesizing a minimal manifest when there is none. `<dpiAware>` covers Windows 8.1
+/// and older; `<dpiAwareness>` takes precedence from Windows 10 1607 on.
+#[cfg(target_os = "windows")]
+fn set_manifest_dpi_awareness(manifest: Option<&str>, mode: &str) -> PyResult<String> {
+    let (dpi_aware, dpi_awareness) = match mode {
+        "unaware" => ("false", "unaware"),
+        "system" => ("true", "system"),
+        "per_monitor" => ("true/pm", "PerMonitor"),
+        _ => ("true/pm", "PerMonitorV2, PerMonitor"),
+    };
+    let settings = format!(
+        "<dpiAware xmlns=\"http://schemas.microsoft.com/SMI/2005/WindowsSettings\">{}</dpiAware>\n      \
+         <dpiAwareness xmlns=\"http://schemas.microsoft.com/SMI/2016/WindowsSettings\">{}</dpiAwareness>",
+        dpi_aware, dpi_awareness
+    );
+
+    let manifest = match manifest {
+        Some(manifest) if !manifest.trim().is_empty() => manifest,
+        _ => {
+            return Ok(format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+                 <assembly xmlns=\"urn:schemas-microsoft-com:asm.v1\" manifestVersion=\"1.0\">\n  \
+                 <application xmlns=\"urn:schemas-microsoft-com:asm.v3\">\n    \
+                 <windowsSettings>\n      {}\n    </windowsSettings>\n  </application>\n</assembly>\n",
+                settings
+            ))
+        }
+    };
+
+    let mut xml = remove_xml_element(manifest, "dpiAware");
+    xml = remove_xml_element(&xml, "dpiAwareness");
+
+    if let Some(start) = find_xml_tag(&xml, "windowsSettings") {
+        let end = start + xml[start..].find('>').unwrap_or(0) + 1;
+        if xml[..end].ends_with("/>") {
+            // <windowsSettings/>: expand it so it can hold children
+            let tag = xml[start..end - 2].trim_end().to_string();
+            xml.replace_range(start..end, &format!("{}>\n      {}\n    </windowsSettings>", tag, settings));
+        } else {
+            xml.insert_str(end, &format!("\n      {}", settings));
+        }
+        return Ok(xml);
+    }
+
+    let close = xml.rfind("</assembly>").ok_or_else(|| {
+        PyErr::new::<MetaEditError, _>("existing manifest has no </assembly> element; cannot set DPI awareness")
+    })?;
+    xml.insert_str(close, &format!(
+        "  <application xmlns=\"urn:schemas-microsoft-com:asm.v3\">\n    <windowsSettings>\n      {}\n    </windowsSettings>\n  </application>\n",
+        settings
+    ));
+    Ok(xml)
+}
+
+/// Replaces the string value of top-level `key` in a JSON object, leaving all other
+/// formatting intact. Returns None if the key is missing or its value isn't a string.
+fn set_json_string_field(json: &str, key: &str, value: &str) -> Option<String> {
+    let bytes = json.as_bytes();
+    // End of the string literal whose opening quote is at `start`
+    let string_end = |start: usize| -> Option<usize> {
+        let mut i = start + 1;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => i += 2,
+                b'"' => return Some(i),
+                _ => i += 1,
+            }
+        }
+        None
+    };
+    let skip_ws = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    };
+
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth -= 1,
+            b'"' => {
+                let end = string_end(i)?;
+                let colon = skip_ws(end + 1);
+                // At depth 1 a string followed by ':' is one of the top-level keys
+                if depth == 1 && bytes.get(colon) == Some(&b':') && &json[i + 1..end] == key {
+                    let value_start = skip_ws(colon + 1);
+                    if bytes.get(value_start) != Some(&b'"') {
+                        return None;
+                    }
+                    let value_end = string_end(value_start)?;
+                    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+                    return Some(format!("{}\"{}\"{}", &json[..value_start], escaped, &json[value_end + 1..]));
+                }
+                i = end;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Byte offset of the first `<tag` start tag (not a longer tag sharing the prefix).
+#[cfg(target_os = "windows")]
+fn find_xml_tag(xml: &str, tag: &str) -> Option<usize> {
+    let open = format!("<{}", tag);
+    let mut from = 0;
+    while let Some(pos) = xml[from..].find(&open) {
+        let start = from + pos;
+        match xml[start + open.len()..].chars().next() {
+            Some(c) if c.is_whitespace() || c == '>' || c == '/' => return Some(start),
+            _ => from = start + open.len(),
+        }
+    }
+    None
+}
+
+/// Every start and end tag whose local name is `name`, whatever its namespace prefix
+/// (`<trustInfo>`, `<asmv3:trustInfo>`), as (offset, is end tag, tag text).
+#[cfg(target_os = "windows")]
+fn xml_tags<'a>(xml: &'a str, name: &str) -> Vec<(usize, bool, &'a str)> {
+    let mut tags = Vec::new();
+    for (start, _) in xml.match_indices('<') {
+        let rest = &xml[start + 1..];
+        let closing = rest.starts_with('/');
+        let rest = rest.strip_prefix('/').unwrap_or(rest);
+        let tag_name = &rest[..rest.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(rest.len())];
+        let local = tag_name.rsplit(':').next().unwrap_or(tag_name);
+        if local != name {
+            continue;
+        }
+        if let Some(end) = xml[start..].find('>') {
+            tags.push((start, closing, &xml[start..=start + end]));
+        }
+    }
+    tags
+}
+
+/// Value of attribute `attr` in a start tag, with the predefined XML entities decoded.
+#[cfg(target_os = "windows")]
+fn xml_attr(tag: &str, attr: &str) -> Option<String> {
+    let mut from = 0;
+    while let Some(pos) = tag[from..].find(attr) {
+        let start = from + pos;
+        from = start + attr.len();
+        if !tag[..start].ends_with(char::is_whitespace) {
+            continue;
+        }
+        let Some(rest) = tag[from..].trim_start().strip_prefix('=') else { continue };
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &rest[1..rest[1..].find(quote)? + 1];
+        return Some(value.replace("&quot;", "\"").replace("&apos;", "'").replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&"));
+    }
+    None
+}
+
+/// Removes every `<tag ...>...</tag>` or `<tag/>` element along with its leading indentation.
+#[cfg(target_os = "windows")]
+fn remove_xml_element(xml: &str, tag: &str) -> String {
+    let mut xml = xml.to_string();
+    let close = format!("</{}>", tag);
+    while let Some(start) = find_xml_tag(&xml, tag) {
+        let open_end = match xml[start..].find('>') {
+            Some(pos) => start + pos + 1,
+            None => break,
+        };
+        let end = if xml[..open_end].ends_with("/>") {
+            open_end
+        } else {
+            match xml[open_end..].find(&close) {
+                Some(pos) => open_end + pos + close.len(),
+                None => break,
+            }
+        };
+        let line_start = xml[..start].trim_end_matches([' ', '\t']).len();
+        let line_start = if xml[..line_start].ends_with('\n') { line_start - 1 } else { start };
+        xml.replace_range(line_start..end, "");
+    }
+    xml
+}
+
+fn strip_pe_signature(data: &mut Vec<u8>) -> bool {
+    let (rva_offset, virt_addr, size) = match read_security_dir(data) {
+        Some(entry) => entry,
+        None => return false,
+    };
 
     if virt_addr == 0 || size == 0 {
         return false; // No signature present
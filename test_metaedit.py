@@ -1,9 +1,15 @@
 import metaedit
+import datetime
+import io
+import json
 import os
+import pickle
 import shutil
+import struct
 import sys
 import unittest
 import tempfile
+import zlib
 from pathlib import Path
 
 # Try importing verification libraries
@@ -19,6 +25,73 @@ try:
 except ImportError:
     HAS_PILLOW = False
 
+IMAGE_FILE_MACHINE_ARM64 = 0xAA64
+
+def png_header(width, height):
+    """A PNG signature and IHDR only: enough to declare dimensions without any pixel data."""
+    ihdr = struct.pack(">IIBBBBB", width, height, 8, 6, 0, 0, 0)
+    chunk = b"IHDR" + ihdr
+    return b"\x89PNG\r\n\x1a\n" + struct.pack(">I", len(ihdr)) + chunk + struct.pack(">I", zlib.crc32(chunk))
+
+def png_image(width, height):
+    """A complete, decodable RGBA PNG of transparent pixels."""
+    def chunk(kind, body):
+        return struct.pack(">I", len(body)) + kind + body + struct.pack(">I", zlib.crc32(kind + body))
+    rows = b"".join(b"\x00" + b"\x00" * (width * 4) for _ in range(height))
+    return png_header(width, height) + chunk(b"IDAT", zlib.compress(rows)) + chunk(b"IEND", b"")
+
+def ico_file(frames):
+    """An ICO built from (width, height, offset, payload) tuples; offset None means packed in order."""
+    header = struct.pack("<HHH", 0, 1, len(frames))
+    entries = b""
+    body = b""
+    next_offset = 6 + 16 * len(frames)
+    for width, height, offset, payload in frames:
+        if offset is None:
+            offset = next_offset + len(body)
+            body += payload
+        entries += struct.pack("<BBBBHHII", width % 256, height % 256, 0, 0, 1, 32, len(payload), offset)
+    return header + entries + body
+
+def build_minimal_pe(path, pe32_plus=False, certificate=None, machine=None):
+    """
+    Writes a tiny but structurally valid PE (one .text section) for byte-level tests
+    that must run on any host. `certificate` bytes are appended and referenced from the
+    Security Directory. `machine` defaults to i386 / AMD64 to match `pe32_plus`.
+    """
+    if machine is None:
+        machine = 0x8664 if pe32_plus else 0x14c
+    opt_size = 240 if pe32_plus else 224
+    dirs_offset = 112 if pe32_plus else 96
+
+    dos = bytearray(0x40)
+    dos[0:2] = b"MZ"
+    dos[0x3c:0x40] = struct.pack("<I", 0x40)
+
+    file_header = struct.pack("<HHIIIHH", machine, 1, 0, 0, 0, opt_size, 0x0102)
+
+    opt = bytearray(opt_size)
+    opt[0:2] = struct.pack("<H", 0x20b if pe32_plus else 0x10b)
+    opt[32:40] = struct.pack("<II", 0x1000, 0x200)      # Section/File alignment
+    opt[56:60] = struct.pack("<I", 0x2000)              # SizeOfImage
+    opt[60:64] = struct.pack("<I", 0x200)               # SizeOfHeaders
+    opt[68:70] = struct.pack("<H", 2)                   # Subsystem (GUI)
+    opt[dirs_offset - 4:dirs_offset] = struct.pack("<I", 16)  # NumberOfRvaAndSizes
+
+    section = struct.pack("<8sIIIIIIHHI", b".text", 0x200, 0x1000, 0x200, 0x200, 0, 0, 0, 0, 0x60000020)
+
+    headers = bytes(dos) + b"PE\0\0" + file_header + bytes(opt) + section
+    data = bytearray(headers.ljust(0x200, b"\0") + b"\xc3" * 0x200)
+
+    if certificate:
+        security = 0x40 + 24 + dirs_offset + 4 * 8
+        data[security:security + 8] = struct.pack("<II", len(data), len(certificate))
+        data += certificate
+
+    with open(path, "wb") as f:
+        f.write(data)
+
+
 class TestMetaEdit(unittest.TestCase):
     def setUp(self):
         # Create a temporary directory
@@ -63,6 +136,173 @@ class TestMetaEdit(unittest.TestCase):
         
         print("String Update Success")
 
+    def test_resources_resolvable_after_edit(self):
+        if sys.platform != "win32" or not HAS_PEFILE:
+            return
+
+        metaedit.update(self.exe_path, CompanyName="Round Trip Corp")
+        pe = pefile.PE(self.exe_path)
+        try:
+            types = [entry.id for entry in pe.DIRECTORY_ENTRY_RESOURCE.entries]
+            self.assertIn(pefile.RESOURCE_TYPE['RT_VERSION'], types)
+        finally:
+            pe.close()
+
+    def test_resource_data_directory_points_at_rsrc(self):
+        if sys.platform != "win32" or not HAS_PEFILE:
+            return
+
+        # A full rewrite (not the in-place version patch) relocates the resource directory
+        editor = metaedit.edit(self.exe_path).set_version("2.0.0.0").set_string_entry(1, 0, "Rewritten")
+        editor.apply()
+        pe = pefile.PE(self.exe_path)
+        try:
+            rsrc = pe.OPTIONAL_HEADER.DATA_DIRECTORY[pefile.DIRECTORY_ENTRY['IMAGE_DIRECTORY_ENTRY_RESOURCE']]
+            section = pe.get_section_by_rva(rsrc.VirtualAddress)
+            self.assertIsNotNone(section)
+            self.assertTrue(section.Name.startswith(b".rsrc"))
+            types = [entry.id for entry in pe.DIRECTORY_ENTRY_RESOURCE.entries]
+            self.assertIn(pefile.RESOURCE_TYPE['RT_VERSION'], types)
+        finally:
+            pe.close()
+
+    def test_list_resources(self):
+        if sys.platform != "win32":
+            return
+
+        metaedit.update(self.exe_path, CompanyName="Listed Corp")
+        resources = metaedit.edit(self.exe_path).list_resources()
+        self.assertIn("RT_VERSION", [kind for kind, _, _ in resources])
+        self.assertTrue(all(size > 0 for _, _, size in resources))
+
+    def test_characteristics(self):
+        pe_path = os.path.join(self.test_dir, "minimal.exe")
+        build_minimal_pe(pe_path)
+        self.assertEqual(metaedit.edit(pe_path).get_characteristics(), ["EXECUTABLE_IMAGE", "32BIT_MACHINE"])
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(pe_path).set_characteristic("IMAGE_FILE_PORTABLE", True)
+
+        if sys.platform != "win32":
+            return
+        metaedit.edit(self.exe_path).set_characteristic("IMAGE_FILE_DLL", True).set_file_type("dll").apply()
+        self.assertIn("DLL", metaedit.edit(self.exe_path).get_characteristics())
+
+    def test_optional_header(self):
+        pe_path = os.path.join(self.test_dir, "minimal.exe")
+        for pe32_plus in (False, True):
+            build_minimal_pe(pe_path, pe32_plus=pe32_plus, certificate=b"\x08" * 32)
+            header = metaedit.edit(pe_path).get_optional_header()
+            self.assertEqual(header["magic"], 0x20b if pe32_plus else 0x10b)
+            self.assertEqual(header["size_of_image"], 0x2000)
+            self.assertEqual(header["size_of_headers"], 0x200)
+            self.assertEqual(header["subsystem"], 2)
+            self.assertEqual(header["number_of_rva_and_sizes"], 16)
+            self.assertTrue(metaedit.edit(pe_path).is_signed())
+
+            # With only 4 data directories there is no Security entry to read
+            count_offset = 0x40 + 24 + (108 if pe32_plus else 92)
+            with open(pe_path, "r+b") as f:
+                f.seek(count_offset)
+                f.write(struct.pack("<I", 4))
+            with self.assertRaisesRegex(metaedit.PEParseError, "NumberOfRvaAndSizes = 4"):
+                metaedit.edit(pe_path).is_signed()
+            with self.assertRaises(metaedit.PEParseError):
+                metaedit.edit(pe_path).append_overlay(b"payload")
+
+        with open(pe_path, "wb") as f:
+            f.write(b"MZ" + b"\0" * 100)
+        with self.assertRaises(metaedit.PEParseError):
+            metaedit.edit(pe_path).get_optional_header()
+
+    def test_exceptions_pickle(self):
+        # Errors raised in a process pool worker are pickled back to the parent
+        with open(self.exe_path, "wb") as f:
+            f.write(b"MZ" + b"\0" * 100)
+        with self.assertRaises(metaedit.PEParseError) as ctx:
+            metaedit.edit(self.exe_path).get_optional_header()
+        restored = pickle.loads(pickle.dumps(ctx.exception))
+        self.assertIs(type(restored), metaedit.PEParseError)
+        self.assertEqual(str(restored), str(ctx.exception))
+        self.assertIsInstance(restored, metaedit.MetaEditError)
+
+    def test_edits_land_in_existing_non_english_table(self):
+        if sys.platform != "win32":
+            return
+
+        # A German-only binary: its single string table is 040704b0
+        metaedit.edit(self.exe_path, {"CompanyName": "Alte GmbH"}).set_locale("de-DE").apply()
+        metaedit.update(self.exe_path, CompanyName="Neue GmbH")
+
+        editor = metaedit.edit(self.exe_path)
+        self.assertEqual(editor.get_string("CompanyName", "040704b0"), "Neue GmbH")
+        self.assertIsNone(editor.get_version_info("040904b0"))
+
+    def test_set_bitmap(self):
+        with self.assertRaises(metaedit.IconError):
+            metaedit.edit(self.exe_path).set_bitmap(101, os.path.join(self.test_dir, "missing.png"))
+
+        if sys.platform != "win32" or not HAS_PILLOW:
+            return
+
+        splash = os.path.join(self.test_dir, "splash.png")
+        Image.new('RGB', (30, 20), color=(0, 0, 255)).save(splash)
+        metaedit.edit(self.exe_path).set_bitmap(101, splash).apply()
+
+        bitmaps = [(id, size) for kind, id, size in metaedit.edit(self.exe_path).list_resources() if kind == "RT_BITMAP"]
+        # 40-byte header + 20 rows of 30 BGR pixels padded to 92 bytes
+        self.assertEqual(bitmaps, [(101, 40 + 92 * 20)])
+
+    def test_pe_operations_without_pe_support(self):
+        if sys.platform == "win32":
+            return
+
+        editor = metaedit.edit(self.exe_path)
+        for call in (editor.list_resources, editor.get_fixed_file_info, lambda: editor.get_string("CompanyName")):
+            with self.assertRaisesRegex(metaedit.MetaEditError, "PE editing not available"):
+                call()
+        with self.assertRaisesRegex(metaedit.MetaEditError, "PE editing not available"):
+            metaedit.edit(self.exe_path).set_string_entry(1, 0, "Hello").apply()
+
+    def test_plan_resources(self):
+        if sys.platform != "win32":
+            return
+
+        metaedit.update(self.exe_path, CompanyName="Old Corp")
+        with open(self.exe_path, "rb") as f:
+            before = f.read()
+
+        diff = metaedit.edit(self.exe_path, {"CompanyName": "New Corp"}).set_string_entry(1, 0, "Hello").plan_resources()
+        self.assertIn(("CompanyName", "Old Corp", "New Corp"), diff.strings)
+        self.assertTrue(any(label.startswith("RT_VERSION/") for label in diff.modified))
+        self.assertTrue(any(label.startswith("RT_STRING/1/") for label in diff.added))
+        self.assertEqual(diff.removed, [])
+        with open(self.exe_path, "rb") as f:
+            self.assertEqual(f.read(), before)
+
+    def test_strings_only_edit_leaves_other_resources(self):
+        if sys.platform != "win32" or not HAS_PEFILE:
+            return
+
+        def other_resources():
+            pe = pefile.PE(self.exe_path)
+            try:
+                leaves = {}
+                for rtype in pe.DIRECTORY_ENTRY_RESOURCE.entries:
+                    if rtype.id == pefile.RESOURCE_TYPE['RT_VERSION']:
+                        continue
+                    for name in rtype.directory.entries:
+                        for lang in name.directory.entries:
+                            entry = lang.data.struct
+                            leaves[(rtype.id, name.id, lang.id)] = pe.get_data(entry.OffsetToData, entry.Size)
+                return leaves
+            finally:
+                pe.close()
+
+        before = other_resources()
+        metaedit.update(self.exe_path, CompanyName="Fast Path Corp")
+        self.assertEqual(other_resources(), before)
+        self.assertEqual(metaedit.edit(self.exe_path).get_string("CompanyName"), "Fast Path Corp")
+
     def test_icon_update(self):
         if sys.platform != "win32":
             return
@@ -83,6 +323,56 @@ class TestMetaEdit(unittest.TestCase):
             
         print("Icon Update Success")
 
+    def test_icon_skips_upscaled_sizes(self):
+        if sys.platform != "win32":
+            return
+        if not HAS_PILLOW:
+            return
+
+        small_icon = os.path.join(self.test_dir, "small_icon.png")
+        Image.new('RGBA', (48, 48), color = (0, 255, 0, 255)).save(small_icon)
+        with self.assertWarnsRegex(UserWarning, "256, 128, 64"):
+            metaedit.update(self.exe_path, icon=small_icon)
+
+    def test_icon_update_webp(self):
+        if sys.platform != "win32":
+            return
+        if not HAS_PILLOW:
+            print("Skipping WebP icon test (Pillow not installed)")
+            return
+
+        webp_path = os.path.join(self.test_dir, "test_icon.webp")
+        Image.new('RGBA', (64, 64), color = (0, 128, 255, 255)).save(webp_path, "WEBP")
+        metaedit.update(self.exe_path, icon=webp_path)
+
+        if HAS_PEFILE:
+            pe = pefile.PE(self.exe_path)
+            self.assertTrue(hasattr(pe, 'DIRECTORY_ENTRY_RESOURCE'), "Resources directory disappeared!")
+            pe.close()
+
+    def test_paletted_icon(self):
+        with self.assertRaises(metaedit.IconError):
+            metaedit.edit(self.exe_path).set_icon_bit_depth(24)
+
+        if sys.platform != "win32" or not HAS_PILLOW:
+            return
+        metaedit.edit(self.exe_path).set_icon(self.icon_path).set_icon_bit_depth(8).apply()
+
+        if HAS_PEFILE:
+            pe = pefile.PE(self.exe_path)
+            self.assertTrue(hasattr(pe, 'DIRECTORY_ENTRY_RESOURCE'))
+            pe.close()
+
+    def test_png_compression(self):
+        with self.assertRaises(metaedit.IconError):
+            metaedit.edit(self.exe_path).set_png_compression("ultra")
+
+        if sys.platform != "win32" or not HAS_PILLOW:
+            return
+        big_icon = os.path.join(self.test_dir, "big_icon.png")
+        Image.new('RGBA', (256, 256), color = (10, 20, 30, 255)).save(big_icon)
+        metaedit.edit(self.exe_path).set_icon(big_icon).set_png_compression("best").apply()
+
     def test_signature_stripping(self):
         if sys.platform != "win32":
             return
@@ -101,6 +391,1552 @@ class TestMetaEdit(unittest.TestCase):
             finally:
                 pe.close()
 
+    def test_read_strings_by_language(self):
+        if sys.platform != "win32":
+            return
+
+        metaedit.update(self.exe_path, CompanyName="MetaEdit Corp")
+        editor = metaedit.edit(self.exe_path)
+        self.assertEqual(editor.get_string("CompanyName"), "MetaEdit Corp")
+        self.assertIsNone(editor.get_string("CompanyName", lang="0x0411"))
+        self.assertIsNone(editor.get_version_info(lang="ja-JP"))
+
+    def test_bump_version(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).bump_version("patchlevel")
+
+        metaedit.edit(self.exe_path).set_version("1.2.3.4").apply()
+        editor = metaedit.edit(self.exe_path)
+        self.assertEqual(editor.bump_version("revision"), "1.2.3.5")
+        editor.apply()
+        self.assertEqual(metaedit.edit(self.exe_path).bump_version("minor"), "1.3.0.0")
+        self.assertEqual(metaedit.edit(self.exe_path).bump_version("major"), "2.0.0.0")
+
+    def test_product_version(self):
+        if sys.platform != "win32":
+            return
+
+        metaedit.edit(self.exe_path).set_version("1.3.2.0").set_product_version("2024.0").apply()
+        editor = metaedit.edit(self.exe_path)
+        self.assertEqual(editor.get_string("FileVersion"), "1.3.2.0")
+        self.assertEqual(editor.get_string("ProductVersion"), "2024.0")
+
+        metaedit.edit(self.exe_path).set_version("1.4.0.0").apply()
+        self.assertEqual(editor.get_string("ProductVersion"), "1.4.0.0")
+
+    def test_extract_icon_frames(self):
+        if sys.platform != "win32" or not HAS_PILLOW:
+            return
+
+        metaedit.edit(self.exe_path).set_icon(self.icon_path).set_icon_bit_depth(8).apply()
+        out_dir = os.path.join(self.test_dir, "frames")
+        paths = metaedit.edit(self.exe_path).extract_icon_frames(out_dir)
+
+        self.assertIn(os.path.join(out_dir, "icon_16x16.png"), paths)
+        for path in paths:
+            with Image.open(path) as img:
+                self.assertEqual(img.format, "PNG")
+
+    def test_extract_icon_bytes(self):
+        if sys.platform != "win32" or not HAS_PILLOW:
+            return
+
+        metaedit.edit(self.exe_path).set_icon(self.icon_path).apply()
+        editor = metaedit.edit(self.exe_path)
+        with Image.open(io.BytesIO(editor.extract_icon_bytes())) as img:
+            # The 64px source caps the frame sizes
+            self.assertEqual(img.size, (64, 64))
+        with Image.open(io.BytesIO(editor.extract_icon_bytes(32))) as img:
+            self.assertEqual(img.size, (32, 32))
+        with self.assertRaises(metaedit.IconError):
+            editor.extract_icon_bytes(100)
+
+    def test_extract_icon(self):
+        if sys.platform != "win32" or not HAS_PILLOW:
+            return
+
+        metaedit.edit(self.exe_path).set_icon(self.icon_path).apply()
+        editor = metaedit.edit(self.exe_path)
+        with self.assertRaises(metaedit.IconError):
+            editor.extract_icon(os.path.join(self.test_dir, "icon.gif"))
+
+        for ext, fmt in (("ico", "ICO"), ("png", "PNG"), ("bmp", "BMP")):
+            path = os.path.join(self.test_dir, "extracted." + ext)
+            editor.extract_icon(path)
+            with Image.open(path) as img:
+                self.assertEqual(img.format, fmt)
+                self.assertEqual(img.size, (64, 64))
+
+    def test_ico_sizes(self):
+        for sizes in [[], [0], [512]]:
+            with self.assertRaises(metaedit.IconError):
+                metaedit.edit(self.exe_path).set_ico_sizes(sizes)
+
+        if sys.platform != "win32" or not HAS_PILLOW:
+            return
+        with self.assertWarns(UserWarning):
+            metaedit.edit(self.exe_path).set_icon(self.icon_path).set_ico_sizes([64, 16]).apply()
+        frames = metaedit.edit(self.exe_path).extract_icon_frames(self.test_dir)
+        self.assertEqual(sorted(frames), sorted(os.path.join(self.test_dir, f"icon_{s}x{s}.png") for s in [64, 48, 32, 16]))
+
+    def test_dpi_awareness(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_dpi_awareness("retina")
+
+        if sys.platform != "win32":
+            return
+
+        editor = metaedit.edit(self.exe_path).set_dpi_awareness("per_monitor_v2")
+        self.assertTrue(editor.apply().result.changed)
+        self.assertFalse(editor.apply(only_if_changed=True).result.changed)
+
+    def test_set_copyright(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_copyright("  ")
+
+        rc_path = os.path.join(self.test_dir, "copyright.rc")
+        def copyright_line(editor):
+            editor.export_rc(rc_path)
+            with open(rc_path, encoding="utf-8") as f:
+                return next(line.strip() for line in f if "LegalCopyright" in line)
+
+        self.assertEqual(copyright_line(metaedit.edit(self.exe_path).set_copyright("Acme Ltd", 2019)),
+                         'VALUE "LegalCopyright", "\u00a9 2019 Acme Ltd"')
+        old = os.environ.get("SOURCE_DATE_EPOCH")
+        try:
+            # The last second of 2023 and the first of 2024
+            os.environ["SOURCE_DATE_EPOCH"] = "1704067199"
+            self.assertIn("2023 Acme", copyright_line(metaedit.edit(self.exe_path).set_copyright("Acme")))
+            os.environ["SOURCE_DATE_EPOCH"] = "1704067200"
+            self.assertIn("2024 Acme", copyright_line(metaedit.edit(self.exe_path).set_copyright("Acme")))
+            del os.environ["SOURCE_DATE_EPOCH"]
+            year = datetime.datetime.now(datetime.timezone.utc).year
+            self.assertIn("%d Acme" % year, copyright_line(metaedit.edit(self.exe_path).set_copyright("Acme")))
+        finally:
+            if old is not None:
+                os.environ["SOURCE_DATE_EPOCH"] = old
+
+        if sys.platform == "darwin":
+            import plistlib
+            metaedit.edit(self.exe_path).set_copyright("Acme", 2020).apply()
+            with open(os.path.join(self.test_dir, "test_app.app", "Contents", "Info.plist"), "rb") as f:
+                self.assertEqual(plistlib.load(f)["NSHumanReadableCopyright"], "\u00a9 2020 Acme")
+
+    def test_export_rc(self):
+        rc_path = os.path.join(self.test_dir, "app.rc")
+        metaedit.edit(self.exe_path, {"CompanyName": 'Say "Hi" Corp'}).set_version("1.2.3").set_file_type("dll").export_rc(rc_path)
+
+        with open(rc_path) as f:
+            rc = f.read()
+        self.assertIn(" FILEVERSION 1,2,3,0\n", rc)
+        self.assertIn(" FILETYPE 0x2L\n", rc)
+        self.assertIn('VALUE "CompanyName", "Say ""Hi"" Corp"', rc)
+        self.assertIn('VALUE "Translation", 0x0409, 1200', rc)
+        self.assertNotIn("ICON", rc)
+
+    def test_version_string_precedence(self):
+        rc_path = os.path.join(self.test_dir, "app.rc")
+        editors = [
+            metaedit.edit(self.exe_path).set_version("1.2.0.0").set_string("FileVersion", "1.2 (beta)"),
+            metaedit.edit(self.exe_path).set_string("FileVersion", "1.2 (beta)").set_version("1.2.0.0"),
+        ]
+        for editor in editors:
+            editor.export_rc(rc_path)
+            with open(rc_path) as f:
+                rc = f.read()
+            # The explicit string wins, once; the numeric field and ProductVersion follow set_version
+            self.assertEqual(rc.count('VALUE "FileVersion"'), 1)
+            self.assertIn('VALUE "FileVersion", "1.2 (beta)"', rc)
+            self.assertIn('VALUE "ProductVersion", "1.2.0.0"', rc)
+            self.assertIn(" FILEVERSION 1,2,0,0\n", rc)
+
+        if sys.platform == "win32":
+            editor = editors[1]
+            self.assertTrue(editor.apply().result.changed)
+            self.assertEqual(metaedit.edit(self.exe_path).get_string("FileVersion"), "1.2 (beta)")
+            self.assertFalse(editor.apply(only_if_changed=True).result.changed)
+
+
+    def test_build_id(self):
+        editor = metaedit.edit(self.exe_path).set_build_id()
+        self.assertRegex(editor.build_id, r"^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$")
+        self.assertNotEqual(editor.build_id, metaedit.edit(self.exe_path).set_build_id().build_id)
+
+        rc_path = os.path.join(self.test_dir, "app.rc")
+        metaedit.edit(self.exe_path).set_build_id("3f2c1ab").export_rc(rc_path)
+        with open(rc_path) as f:
+            self.assertIn('VALUE "BuildID", "3f2c1ab"', f.read())
+
+        if sys.platform == "linux":
+            metaedit.edit(self.exe_path).set_build_id("3f2c1ab").apply()
+            with open(os.path.join(self.test_dir, "test_app.desktop")) as f:
+                self.assertIn("X-BuildID=3f2c1ab\n", f.read())
+
+    def test_string_from_file(self):
+        notice = os.path.join(self.test_dir, "notice.txt")
+        with open(notice, "w", encoding="utf-8") as f:
+            f.write("© 2026 Acme Corp. All rights reserved.\n")
+        rc_path = os.path.join(self.test_dir, "app.rc")
+        metaedit.edit(self.exe_path).set_string_from_file("LegalCopyright", notice).export_rc(rc_path)
+        with open(rc_path, encoding="utf-8") as f:
+            self.assertIn('VALUE "LegalCopyright", "© 2026 Acme Corp. All rights reserved."\n', f.read())
+
+        with open(notice, "w") as f:
+            f.write("Line one\nLine two\n")
+        with self.assertWarns(UserWarning):
+            metaedit.edit(self.exe_path).set_string_from_file("Comments", notice)
+
+        with open(notice, "wb") as f:
+            f.write(b"\xff\xfe not utf-8")
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_string_from_file("Comments", notice)
+
+    def test_cli_set_strings(self):
+        from click.testing import CliRunner
+        from metaedit.cli import main
+
+        notice = os.path.join(self.test_dir, "notice.txt")
+        with open(notice, "w") as f:
+            f.write("(c) From File\n")
+        runner = CliRunner()
+        result = runner.invoke(main, [self.exe_path, "--set", f"LegalCopyright=@{notice}", "--set", "Comments=inline"])
+        self.assertEqual(result.exit_code, 0, result.output)
+        self.assertEqual(runner.invoke(main, [self.exe_path, "--set", "novalue"]).exit_code, 2)
+
+    def test_with_file(self):
+        base = metaedit.edit(self.exe_path, {"CompanyName": "Batch Corp", "version": "4.5.6.7"})
+        other_path = os.path.join(self.test_dir, "other_app.exe")
+        shutil.copy(self.exe_path, other_path)
+
+        other = base.with_file(other_path)
+        self.assertEqual(other.file_path, os.path.abspath(other_path))
+        self.assertEqual(base.file_path, os.path.abspath(self.exe_path))
+        rc_path = os.path.join(self.test_dir, "other.rc")
+        other.export_rc(rc_path)
+        with open(rc_path) as f:
+            self.assertIn('VALUE "CompanyName", "Batch Corp"', f.read())
+
+        # The copy is independent of the editor it came from
+        other.set_string("CompanyName", "Other Corp")
+        base.export_rc(rc_path)
+        with open(rc_path) as f:
+            self.assertIn('VALUE "CompanyName", "Batch Corp"', f.read())
+
+    def test_batch_apply_iter(self):
+        paths = []
+        for i in range(3):
+            path = os.path.join(self.test_dir, f"batch_{i}.exe")
+            shutil.copy(self.exe_path, path)
+            paths.append(path)
+        missing = os.path.join(self.test_dir, "missing.exe")
+
+        base = metaedit.MetadataEditor(self.exe_path).set_version("1.2.0.0")
+        results = base.batch_apply_iter(iter([paths[0], missing, paths[1]]), stop_on_error=False)
+        path, result = next(results)  # lazy: one file at a time
+        self.assertEqual((path, result.changed), (paths[0], True))
+        path, error = next(results)
+        self.assertEqual(path, missing)
+        self.assertIsInstance(error, FileNotFoundError)
+        self.assertEqual(next(results)[0], paths[1])
+
+        with self.assertRaises(FileNotFoundError):
+            list(base.batch_apply_iter([missing]))
+
+    def test_cli_stdin(self):
+        from click.testing import CliRunner
+        from metaedit.cli import main
+
+        path = os.path.join(self.test_dir, "piped.exe")
+        shutil.copy(self.exe_path, path)
+        runner = CliRunner()
+        result = runner.invoke(main, ["--version", "1.2.0.0", "--stdin"], input=f"{path}\n\n")
+        self.assertEqual(result.exit_code, 0, result.output)
+        self.assertIn(f"{path}: metadata updated", result.output)
+
+        result = runner.invoke(main, ["--stdin"], input=os.path.join(self.test_dir, "missing.exe") + "\n")
+        self.assertEqual(result.exit_code, 1)
+        self.assertEqual(runner.invoke(main, []).exit_code, 2)
+
+    def test_temp_dir(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.set_temp_dir(os.path.join(self.test_dir, "missing"))
+
+        temp_dir = os.path.join(self.test_dir, "tmp")
+        os.mkdir(temp_dir)
+        rc_path = os.path.join(self.test_dir, "app.rc")
+        metaedit.set_temp_dir(temp_dir)
+        try:
+            metaedit.edit(self.exe_path, {"ProductName": "Temp App"}).export_rc(rc_path)
+        finally:
+            metaedit.set_temp_dir(None)
+        with open(rc_path) as f:
+            self.assertIn('VALUE "ProductName", "Temp App"', f.read())
+        self.assertEqual(os.listdir(temp_dir), [])
+
+        # Same-named targets in different directories share the temp dir without sharing a
+        # temp file, and another writer's in-flight file there is left alone
+        foreign = os.path.join(temp_dir, ".app.rc.metaedit-tmp")
+        with open(foreign, "w") as f:
+            f.write("in flight")
+        arches = ("win", "arm")
+        metaedit.set_temp_dir(temp_dir)
+        try:
+            for arch in arches:
+                os.mkdir(os.path.join(self.test_dir, arch))
+                metaedit.edit(self.exe_path, {"ProductName": arch}).export_rc(os.path.join(self.test_dir, arch, "app.rc"))
+        finally:
+            metaedit.set_temp_dir(None)
+        for arch in arches:
+            with open(os.path.join(self.test_dir, arch, "app.rc")) as f:
+                self.assertIn('VALUE "ProductName", "%s"' % arch, f.read())
+        with open(foreign) as f:
+            self.assertEqual(f.read(), "in flight")
+        os.remove(foreign)
+
+        # A temp dir on another filesystem still works, by copying next to the target
+        other_fs = "/dev/shm"
+        if not os.path.isdir(other_fs) or os.stat(other_fs).st_dev == os.stat(self.test_dir).st_dev:
+            return
+        before = set(os.listdir(other_fs))
+        metaedit.set_temp_dir(other_fs)
+        try:
+            with self.assertWarns(UserWarning):
+                metaedit.edit(self.exe_path, {"ProductName": "Copied App"}).export_rc(rc_path)
+        finally:
+            metaedit.set_temp_dir(None)
+        with open(rc_path) as f:
+            self.assertIn('VALUE "ProductName", "Copied App"', f.read())
+        self.assertEqual(set(os.listdir(other_fs)), before)
+        self.assertEqual([f for f in os.listdir(self.test_dir) if f.endswith(".metaedit-tmp")], [])
+
+    def test_string_table_key(self):
+        for key in ["0409", "040904b", "040904b0x", "04090zb0"]:
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.edit(self.exe_path).set_string_table_key(key)
+
+        rc_path = os.path.join(self.test_dir, "app.rc")
+        metaedit.edit(self.exe_path, {"ProductName": "Keyed"}).set_string_table_key("040704E4").export_rc(rc_path)
+        with open(rc_path) as f:
+            rc = f.read()
+        self.assertIn('BLOCK "040704e4"', rc)
+        self.assertIn('VALUE "Translation", 0x0407, 1252', rc)
+
+    def test_defaults(self):
+        metaedit.set_defaults({"CompanyName": "Default Corp", "LegalCopyright": "(c) Default"})
+        try:
+            editor = metaedit.edit(self.exe_path, {"CompanyName": "Override Corp"})
+            editor.export_rc(os.path.join(self.test_dir, "app.rc"))
+        finally:
+            metaedit.clear_defaults()
+
+        with open(os.path.join(self.test_dir, "app.rc")) as f:
+            rc = f.read()
+        self.assertIn('VALUE "CompanyName", "Override Corp"', rc)
+        self.assertIn('VALUE "LegalCopyright", "(c) Default"', rc)
+
+        metaedit.edit(self.exe_path).export_rc(os.path.join(self.test_dir, "plain.rc"))
+        with open(os.path.join(self.test_dir, "plain.rc")) as f:
+            self.assertNotIn("LegalCopyright", f.read())
+
+    def test_validate_icon(self):
+        with self.assertRaises(metaedit.IconError):
+            metaedit.validate_icon(os.path.join(self.test_dir, "missing.png"))
+        with self.assertRaises(metaedit.IconError):
+            metaedit.validate_icon(self.exe_path)
+
+        if not HAS_PILLOW:
+            return
+        report = metaedit.validate_icon(self.icon_path)
+        self.assertEqual(report, {"format": "png", "width": "64", "height": "64", "has_alpha": "true"})
+
+    def test_validate_icon_ico_frames(self):
+        path = os.path.join(self.test_dir, "frames.ico")
+        png = png_image(16, 16)
+
+        with open(path, "wb") as f:
+            f.write(ico_file([(16, 16, None, png)]))
+        self.assertEqual(metaedit.validate_icon(path)["frames"], "1")
+
+        broken = {
+            "out_of_bounds": [(16, 16, 4096, png)],
+            "overlapping": [(16, 16, None, png + png), (16, 16, 38 + len(png), png)],
+            "size_mismatch": [(32, 32, None, png)],
+        }
+        for name, frames in broken.items():
+            with self.subTest(name):
+                with open(path, "wb") as f:
+                    f.write(ico_file(frames))
+                with self.assertRaises(metaedit.IconError):
+                    metaedit.validate_icon(path)
+
+    def test_icns_source(self):
+        if not HAS_PILLOW:
+            return
+
+        entries = b""
+        for kind, size, color in [(b"icp4", 16, (0, 0, 255, 255)), (b"ic07", 128, (0, 255, 0, 255))]:
+            buf = io.BytesIO()
+            Image.new('RGBA', (size, size), color = color).save(buf, format="PNG")
+            entries += kind + struct.pack(">I", len(buf.getvalue()) + 8) + buf.getvalue()
+        icns_path = os.path.join(self.test_dir, "app.icns")
+        with open(icns_path, "wb") as f:
+            f.write(b"icns" + struct.pack(">I", len(entries) + 8) + entries)
+
+        # The largest entry is what gets used
+        report = metaedit.validate_icon(icns_path)
+        self.assertEqual((report["format"], report["width"]), ("icns", "128"))
+        self.assertEqual(metaedit.icon_dominant_color(icns_path), (0, 255, 0))
+
+        legacy_path = os.path.join(self.test_dir, "legacy.icns")
+        with open(legacy_path, "wb") as f:
+            f.write(b"icns" + struct.pack(">I", 24) + b"is32" + struct.pack(">I", 16) + b"\0" * 8)
+        with self.assertRaises(metaedit.IconError):
+            metaedit.validate_icon(legacy_path)
+
+        if sys.platform == "win32":
+            metaedit.edit(self.exe_path).set_icon(icns_path).apply()
+            largest = metaedit.edit(self.exe_path).extract_icon_bytes()
+            self.assertEqual(Image.open(io.BytesIO(largest)).size, (128, 128))
+
+    def test_icon_dominant_color(self):
+        with self.assertRaises(metaedit.IconError):
+            metaedit.icon_dominant_color(os.path.join(self.test_dir, "missing.png"))
+
+        if not HAS_PILLOW:
+            return
+        self.assertEqual(metaedit.icon_dominant_color(self.icon_path), (255, 0, 0))
+
+        # Mostly blue with a red stripe on a transparent border: blue wins
+        icon_path = os.path.join(self.test_dir, "mixed.png")
+        img = Image.new('RGBA', (64, 64), color = (0, 0, 0, 0))
+        img.paste((20, 40, 200, 255), (8, 8, 56, 56))
+        img.paste((255, 0, 0, 255), (8, 8, 56, 16))
+        img.save(icon_path)
+        self.assertEqual(metaedit.icon_dominant_color(icon_path), (20, 40, 200))
+
+    def test_export_wix_fragment(self):
+        wxi_path = os.path.join(self.test_dir, "product.wxi")
+        metaedit.edit(self.exe_path, {"ProductName": "Test & App", "CompanyName": "Test Corp"}).set_version("v1.2.3.4").export_wix_fragment(wxi_path)
+
+        with open(wxi_path) as f:
+            wxi = f.read()
+        self.assertIn('<?define ProductName = "Test & App" ?>', wxi)
+        self.assertIn('<?define ProductVersion = "1.2.3" ?>', wxi)
+        self.assertIn('<?define Manufacturer = "Test Corp" ?>', wxi)
+
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_version("300.0.0").export_wix_fragment(wxi_path)
+    def test_parse_version(self):
+        self.assertEqual(metaedit.parse_version("1.2.3.4-beta"), ([1, 2, 3, 4], "beta"))
+        self.assertEqual(metaedit.parse_version("1.2"), ([1, 2], None))
+        self.assertEqual(metaedit.parse_version("v1.2.3"), ([1, 2, 3], None))
+        self.assertEqual(metaedit.parse_version("2.0rc1"), ([2, 0], "rc1"))
+        for bad in ["", "beta", "1..2", "1.2.3.4.5", "1.2-", "v"]:
+            with self.assertRaises(metaedit.MetaEditError, msg=bad):
+                metaedit.parse_version(bad)
+
+    def test_string_table(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_string_entry(1, 16, "out of range")
+
+        if sys.platform != "win32":
+            return
+
+        metaedit.edit(self.exe_path).set_string_entry(4000, 3, "https://example.com").apply()
+        table = metaedit.edit(self.exe_path).get_string_table(4000)
+        self.assertEqual(len(table), 16)
+        self.assertEqual(table[3], "https://example.com")
+        self.assertEqual(table[0], "")
+        self.assertIsNone(metaedit.edit(self.exe_path).get_string_table(4001))
+
+    def test_file_type(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_file_type("screensaver")
+
+        if sys.platform != "win32":
+            return
+        metaedit.edit(self.exe_path).set_file_type("dll").apply()
+        info = metaedit.edit(self.exe_path).get_fixed_file_info()
+        self.assertEqual(info["file_type"], "dll")
+
+    def test_list_version_languages(self):
+        if sys.platform != "win32":
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.edit(self.exe_path).list_version_languages()
+            return
+
+        metaedit.edit(self.exe_path, {"CompanyName": "Firma GmbH"}).set_locale("de-DE").apply()
+        self.assertIn("040704b0", metaedit.edit(self.exe_path).list_version_languages())
+
+    def test_estimate_icon_size(self):
+        if sys.platform != "win32":
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.edit(self.exe_path).estimate_icon_size(self.icon_path)
+            return
+        if not HAS_PILLOW:
+            return
+
+        full = metaedit.edit(self.exe_path).estimate_icon_size(self.icon_path)
+        trimmed = metaedit.edit(self.exe_path).set_ico_sizes([32, 16]).set_shell_icon_sizes(False).estimate_icon_size(self.icon_path)
+        self.assertGreater(full, trimmed)
+        self.assertGreater(trimmed, 0)
+
+    def test_create_shortcut(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_create_shortcut(path=os.path.join(self.test_dir, "app.txt"))
+        if sys.platform != "win32":
+            return
+
+        metaedit.edit(self.exe_path, {"FileDescription": "Test Launcher"}).set_create_shortcut().apply()
+        with open(os.path.join(self.test_dir, "test_app.lnk"), "rb") as f:
+            lnk = f.read()
+        self.assertTrue(lnk.startswith(b"\x4c\0\0\0\x01\x14\x02\0"))
+        self.assertIn("Test Launcher".encode("utf-16-le"), lnk)
+        self.assertIn(os.path.realpath(self.exe_path).encode("utf-16-le"), lnk)
+
+        custom = os.path.join(self.test_dir, "Start Menu.lnk")
+        metaedit.edit(self.exe_path).set_create_shortcut(path=custom).apply()
+        self.assertTrue(os.path.isfile(custom))
+
+    def test_refuses_file_in_use(self):
+        if sys.platform != "win32":
+            return
+
+        import ctypes
+        from ctypes import wintypes
+        kernel32 = ctypes.WinDLL("kernel32", use_last_error=True)
+        kernel32.CreateFileW.restype = wintypes.HANDLE
+        # Share it for reading only, as a running program holds its image
+        handle = kernel32.CreateFileW(self.exe_path, 0x80000000, 1, None, 3, 0, None)
+        self.assertNotEqual(handle, wintypes.HANDLE(-1).value)
+        try:
+            with self.assertRaises(metaedit.MetaEditError) as ctx:
+                metaedit.edit(self.exe_path).set_version("2.0.0.0").apply()
+            self.assertIn("in use", str(ctx.exception))
+        finally:
+            kernel32.CloseHandle(wintypes.HANDLE(handle))
+        metaedit.edit(self.exe_path).set_version("2.0.0.0").apply()
+
+    def test_rich_header_preserved(self):
+        if sys.platform != "win32":
+            return
+
+        def dos_stub():
+            with open(self.exe_path, "rb") as f:
+                data = f.read(4096)
+            e_lfanew = struct.unpack_from("<I", data, 0x3c)[0]
+            return data[0x40:e_lfanew]
+
+        # python.exe is built by MSVC, so its stub carries a Rich header
+        original = dos_stub()
+        self.assertIn(b"Rich", original)
+        metaedit.edit(self.exe_path).set_version("9.8.7.6").apply()
+        self.assertEqual(dos_stub(), original)
+        if HAS_PILLOW:
+            # An icon forces the full resource rewrite
+            metaedit.edit(self.exe_path).set_icon(self.icon_path).apply()
+            self.assertEqual(dos_stub(), original)
+
+    def test_get_manifest_info(self):
+        if sys.platform != "win32":
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.edit(self.exe_path).get_manifest_info()
+            return
+
+        # python.exe ships a manifest that runs as the invoking user
+        info = metaedit.edit(self.exe_path).get_manifest_info()
+        self.assertEqual(info.get("execution_level"), "asInvoker")
+
+    def test_save_as(self):
+        output = os.path.join(self.test_dir, "app.exe")
+        if sys.platform != "win32":
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.edit(self.exe_path).save_as(output)
+            return
+
+        with open(self.exe_path, "rb") as f:
+            original = f.read()
+        editor = metaedit.edit(self.exe_path).set_version("9.8.7.6")
+        self.assertEqual(editor.save_as(output), output)
+        with open(self.exe_path, "rb") as f:
+            self.assertEqual(f.read(), original)
+        self.assertEqual(metaedit.edit(output).get_string("FileVersion"), "9.8.7.6")
+
+        # Same input and settings, same name: app.<8 hex digits>.exe
+        first = editor.save_as(output, content_hash=True)
+        self.assertRegex(os.path.basename(first), r"^app\.[0-9a-f]{8}\.exe$")
+        self.assertEqual(editor.save_as(output, content_hash=True), first)
+        self.assertNotEqual(metaedit.edit(self.exe_path).set_version("1.2.3.4").save_as(output, content_hash=True), first)
+
+    def test_version_consistency(self):
+        if sys.platform != "win32":
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.edit(self.exe_path).version_consistency()
+            return
+
+        metaedit.edit(self.exe_path).set_version("1.0.0.0").apply()
+        self.assertEqual(metaedit.edit(self.exe_path).version_consistency(), {})
+
+        # The classic broken build: the string was bumped, FixedFileInfo wasn't
+        metaedit.edit(self.exe_path).set_string("FileVersion", "2.0").apply()
+        report = metaedit.edit(self.exe_path).version_consistency()
+        self.assertEqual(list(report), ["file_version"])
+        self.assertIn("1.0.0.0", report["file_version"])
+
+    def test_only_if_changed(self):
+        editor = metaedit.edit(self.exe_path, {"CompanyName": "MetaEdit Corp", "version": "1.2.3.4"})
+        self.assertTrue(editor.apply().result.changed)
+
+        result = editor.apply(only_if_changed=True).result
+        self.assertFalse(result.changed)
+        self.assertEqual(result.message, "no change needed")
+
+    def test_json_report(self):
+        report_path = os.path.join(self.test_dir, "report.json")
+        editor = metaedit.edit(self.exe_path, {"ProductName": "Report App", "version": "2.0.0.0"})
+        result = editor.apply(report_path=report_path).result
+
+        with open(report_path) as f:
+            report = json.load(f)
+        self.assertEqual(report, json.loads(result.to_json()))
+        self.assertEqual(list(report), ["file", "format", "output", "changed", "signature", "signature_invalid", "changes"])
+        self.assertEqual(report["file"], self.exe_path)
+        self.assertTrue(report["changed"])
+        self.assertEqual(report["signature"], "none")
+        changes = {c["field"]: (c["old"], c["new"]) for c in report["changes"]}
+        name_key = {"win32": "ProductName", "darwin": "CFBundleName"}.get(sys.platform, "Name")
+        self.assertEqual(changes[name_key][1], "Report App")
+        self.assertEqual([c["field"] for c in report["changes"]], sorted(changes))
+
+        # A second run reports nothing left to change
+        report = json.loads(editor.apply().result.to_json())
+        self.assertEqual(report["changes"], [])
+
+    def test_icon_background(self):
+        if sys.platform != "linux" or not HAS_PILLOW:
+            return
+
+        wide_icon = os.path.join(self.test_dir, "wide_icon.png")
+        Image.new('RGBA', (64, 32), color = (255, 0, 0, 255)).save(wide_icon)
+        data_home = os.path.join(self.test_dir, "share")
+        os.environ["XDG_DATA_HOME"] = data_home
+        try:
+            metaedit.edit(self.exe_path).set_icon(wide_icon) \
+                .set_icon_background((255, 255, 255, 255)).set_install_icon_theme().apply()
+        finally:
+            del os.environ["XDG_DATA_HOME"]
+
+        themed = Image.open(os.path.join(data_home, "icons", "hicolor", "64x64", "apps", "test_app.png"))
+        self.assertEqual(themed.size, (64, 64))
+        self.assertEqual(themed.convert("RGBA").getpixel((0, 0)), (255, 255, 255, 255))
+        self.assertEqual(themed.convert("RGBA").getpixel((32, 32)), (255, 0, 0, 255))
+
+    def test_icon_from_dir(self):
+        if not HAS_PILLOW:
+            return
+
+        icon_dir = os.path.join(self.test_dir, "icons")
+        os.makedirs(icon_dir)
+        with self.assertRaises(metaedit.IconError):
+            metaedit.edit(self.exe_path).set_icon_from_dir(icon_dir)
+        Image.new('RGBA', (16, 16), color=(0, 0, 255, 255)).save(os.path.join(icon_dir, "16.png"))
+        Image.new('RGBA', (64, 64), color=(255, 0, 0, 255)).save(os.path.join(icon_dir, "64x64.png"))
+        with open(os.path.join(icon_dir, "README.txt"), "w") as f:
+            f.write("ignored")
+
+        if sys.platform == "linux":
+            data_home = os.path.join(self.test_dir, "share")
+            os.environ["XDG_DATA_HOME"] = data_home
+            try:
+                metaedit.edit(self.exe_path).set_icon_from_dir(icon_dir).set_install_icon_theme().apply()
+            finally:
+                del os.environ["XDG_DATA_HOME"]
+            themed = os.path.join(data_home, "icons", "hicolor", "{0}x{0}", "apps", "test_app.png")
+            # 16px comes from the set untouched; 32px is resized from the 64px file
+            self.assertEqual(Image.open(themed.format(16)).convert("RGBA").getpixel((8, 8)), (0, 0, 255, 255))
+            self.assertEqual(Image.open(themed.format(32)).convert("RGBA").getpixel((16, 16)), (255, 0, 0, 255))
+
+        Image.new('RGBA', (20, 20)).save(os.path.join(icon_dir, "24.png"))
+        with self.assertRaises(metaedit.IconError):
+            metaedit.edit(self.exe_path).set_icon_from_dir(icon_dir)
+
+    def test_linux_icon_theme_install(self):
+        if sys.platform != "linux" or not HAS_PILLOW:
+            return
+
+        data_home = os.path.join(self.test_dir, "share")
+        old = os.environ.get("XDG_DATA_HOME")
+        os.environ["XDG_DATA_HOME"] = data_home
+        try:
+            metaedit.edit(self.exe_path).set_icon(self.icon_path).set_install_icon_theme().apply()
+        finally:
+            if old is None:
+                del os.environ["XDG_DATA_HOME"]
+            else:
+                os.environ["XDG_DATA_HOME"] = old
+
+        themed = os.path.join(data_home, "icons", "hicolor", "48x48", "apps", "test_app.png")
+        self.assertTrue(os.path.exists(themed))
+        with open(os.path.join(self.test_dir, "test_app.desktop")) as f:
+            self.assertIn("Icon=test_app\n", f.read())
+
+    def test_typed_plist_values(self):
+        editor = (
+            metaedit.edit(self.exe_path)
+            .set_plist_bool("LSUIElement", True)
+            .set_plist_int("LSMinimumSystemVersionByArchitecture", 11)
+            .set_plist_array("CFBundleLocalizations", ["en", "de"])
+        )
+        editor.apply()
+        if sys.platform != "darwin":
+            return
+
+        import plistlib
+        with open(os.path.join(self.test_dir, "test_app.app", "Contents", "Info.plist"), "rb") as f:
+            info = plistlib.load(f)
+        self.assertIs(info["LSUIElement"], True)
+        self.assertEqual(info["LSMinimumSystemVersionByArchitecture"], 11)
+        self.assertEqual(info["CFBundleLocalizations"], ["en", "de"])
+
+    def test_bundle_conformance_keys(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_info_dictionary_version(" ")
+
+        import plistlib
+        plist_path = os.path.join(self.test_dir, "test_app.app", "Contents", "Info.plist")
+        metaedit.edit(self.exe_path).apply_all(["macos"])
+        with open(plist_path, "rb") as f:
+            info = plistlib.load(f)
+        self.assertEqual(info["CFBundleInfoDictionaryVersion"], "6.0")
+        self.assertEqual(info["CFBundlePackageType"], "APPL")
+
+        metaedit.edit(self.exe_path).set_info_dictionary_version("7.0").apply_all(["macos"])
+        with open(plist_path, "rb") as f:
+            self.assertEqual(plistlib.load(f)["CFBundleInfoDictionaryVersion"], "7.0")
+
+    def test_app_category(self):
+        for category in ["", "public.app-category.", "developer tools", "com.example.tools"]:
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.edit(self.exe_path).set_category(category)
+
+        editor = metaedit.edit(self.exe_path).set_category("Developer-Tools")
+        self.assertTrue(any("app category" in w for w in editor.validate_for("windows")))
+        if sys.platform != "darwin":
+            return
+
+        import plistlib
+        editor.apply()
+        with open(os.path.join(self.test_dir, "test_app.app", "Contents", "Info.plist"), "rb") as f:
+            self.assertEqual(plistlib.load(f)["LSApplicationCategoryType"], "public.app-category.developer-tools")
+
+    def test_architecture_keys(self):
+        for architectures in [[], ["ppc"], ["arm64", "arm64"]]:
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.edit(self.exe_path).set_architecture_priority(architectures)
+
+        metaedit.edit(self.exe_path).set_architecture_priority(["arm64", "x86_64"]) \
+            .set_requires_native_execution().apply()
+        if sys.platform != "darwin":
+            return
+
+        import plistlib
+        with open(os.path.join(self.test_dir, "test_app.app", "Contents", "Info.plist"), "rb") as f:
+            info = plistlib.load(f)
+        self.assertEqual(info["LSArchitecturePriority"], ["arm64", "x86_64"])
+        self.assertIs(info["LSRequiresNativeExecution"], True)
+
+    def test_icns_slots(self):
+        if sys.platform != "darwin" or not HAS_PILLOW:
+            return
+
+        wide = os.path.join(self.test_dir, "wide.png")
+        Image.new('RGBA', (300, 200), color=(255, 0, 0, 255)).save(wide)
+        with self.assertWarns(UserWarning):
+            metaedit.edit(self.exe_path).set_icon(wide).apply()
+
+        with open(os.path.join(self.test_dir, "test_app.app", "Contents", "Resources", "app.icns"), "rb") as f:
+            icns = f.read()
+        self.assertEqual(icns[:4], b"icns")
+        self.assertEqual(struct.unpack(">I", icns[4:8])[0], len(icns))
+
+        sizes = {}
+        offset = 8
+        while offset < len(icns):
+            kind, length = struct.unpack(">4sI", icns[offset:offset + 8])
+            with Image.open(io.BytesIO(icns[offset + 8:offset + length])) as img:
+                sizes[kind] = img.size
+            offset += length
+        self.assertEqual(sizes[b"ic07"], (128, 128))
+        self.assertEqual(sizes[b"ic10"], (1024, 1024))
+
+    def test_icon_name(self):
+        with self.assertRaises(metaedit.IconError):
+            metaedit.edit(self.exe_path).set_icon_name("../AppIcon")
+
+        if sys.platform != "darwin" or not HAS_PILLOW:
+            return
+
+        import plistlib
+        metaedit.edit(self.exe_path).set_icon(self.icon_path).set_icon_name("AppIcon").apply()
+        contents = os.path.join(self.test_dir, "test_app.app", "Contents")
+        with open(os.path.join(contents, "Info.plist"), "rb") as f:
+            icon_file = plistlib.load(f)["CFBundleIconFile"]
+        self.assertEqual(icon_file, "AppIcon")
+        self.assertTrue(os.path.exists(os.path.join(contents, "Resources", icon_file + ".icns")))
+
+    def test_localized_bundle(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_development_region("../en")
+
+        if sys.platform != "darwin":
+            return
+
+        import plistlib
+        metaedit.edit(self.exe_path).set_localized_name("de", 'Die "App"', "App").apply()
+        contents = os.path.join(self.test_dir, "test_app.app", "Contents")
+        with open(os.path.join(contents, "Info.plist"), "rb") as f:
+            self.assertEqual(plistlib.load(f)["CFBundleDevelopmentRegion"], "en")
+        with open(os.path.join(contents, "Resources", "de.lproj", "InfoPlist.strings")) as f:
+            strings = f.read()
+        self.assertIn('"CFBundleName" = "Die \\"App\\"";', strings)
+        self.assertIn('"CFBundleDisplayName" = "App";', strings)
+
+    def test_existing_bundle_keeps_executable(self):
+        if sys.platform != "darwin":
+            return
+
+        import plistlib
+        bundle = os.path.join(self.test_dir, "Existing.app")
+        os.makedirs(os.path.join(bundle, "Contents", "MacOS"))
+        with open(os.path.join(bundle, "Contents", "MacOS", "launcher"), "wb") as f:
+            f.write(b"\xcf\xfa\xed\xfe")
+
+        metaedit.edit(bundle, {"ProductName": "Existing"}).apply()
+        with open(os.path.join(bundle, "Contents", "Info.plist"), "rb") as f:
+            self.assertEqual(plistlib.load(f)["CFBundleExecutable"], "launcher")
+
+    def test_edit_bundle(self):
+        bundle = os.path.join(self.test_dir, "Existing.app")
+        os.makedirs(bundle)
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path, {"ProductName": "Merged"}).edit_bundle(bundle)
+
+        if sys.platform != "darwin":
+            return
+
+        import plistlib
+        contents = os.path.join(bundle, "Contents")
+        os.makedirs(os.path.join(contents, "MacOS"))
+        with open(os.path.join(contents, "Info.plist"), "wb") as f:
+            plistlib.dump({"CFBundleExecutable": "launcher", "CFBundleIdentifier": "com.example.app"}, f)
+
+        editor = metaedit.edit(self.exe_path, {"ProductName": "Merged", "version": "2.0.0.0"}).edit_bundle(bundle)
+        self.assertTrue(editor.result.changed)
+        self.assertEqual(editor.result.file, bundle)
+        self.assertEqual(editor.result.output, bundle)
+        self.assertEqual(editor.result.format, "macos")
+        with open(os.path.join(contents, "Info.plist"), "rb") as f:
+            info = plistlib.load(f)
+        self.assertEqual(info["CFBundleExecutable"], "launcher")
+        self.assertEqual(info["CFBundleIdentifier"], "com.example.app")
+        self.assertEqual(info["CFBundleName"], "Merged")
+        self.assertEqual(os.listdir(os.path.join(contents, "MacOS")), [])
+        self.assertFalse(os.path.exists(os.path.join(contents, "Resources")))
+
+    def test_bundle_name(self):
+        for name in ["", "a/b", ".."]:
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.edit(self.exe_path).set_bundle_name(name)
+
+        editor = metaedit.edit(self.exe_path, {"ProductName": "Nice"}).set_bundle_name("Nice App.app")
+        self.assertTrue(any("bundle name" in w for w in editor.validate_for("linux")))
+        if sys.platform != "darwin":
+            return
+
+        import plistlib
+        editor.apply()
+        contents = os.path.join(self.test_dir, "Nice App.app", "Contents")
+        self.assertFalse(os.path.exists(os.path.join(self.test_dir, "test_app.app")))
+        self.assertTrue(os.path.isfile(os.path.join(contents, "MacOS", "test_app.exe")))
+        with open(os.path.join(contents, "Info.plist"), "rb") as f:
+            self.assertEqual(plistlib.load(f)["CFBundleExecutable"], "test_app.exe")
+
+    def test_dmg_staging(self):
+        if sys.platform != "darwin":
+            return
+
+        staging = os.path.join(self.test_dir, "dmg")
+        metaedit.edit(self.exe_path, {"ProductName": "Staged"}).set_dmg_staging(staging).apply()
+
+        self.assertTrue(os.path.isfile(os.path.join(staging, "test_app.app", "Contents", "Info.plist")))
+        self.assertEqual(os.readlink(os.path.join(staging, "Applications")), "/Applications")
+        staged_bin = os.path.join(staging, "test_app.app", "Contents", "MacOS", "test_app.exe")
+        self.assertTrue(os.access(staged_bin, os.X_OK))
+
+    def test_binary_placement(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_binary_placement("symlink")
+
+        bundled = os.path.join(self.test_dir, "test_app.app", "Contents", "MacOS", "test_app.exe")
+        metaedit.edit(self.exe_path).set_binary_placement("hardlink").apply_all(["macos"])
+        self.assertTrue(os.path.samefile(self.exe_path, bundled))
+
+        metaedit.edit(self.exe_path).set_binary_placement("move").apply_all(["macos"])
+        self.assertFalse(os.path.exists(self.exe_path))
+        self.assertTrue(os.path.isfile(bundled))
+
+    def test_executable_bit(self):
+        # The bundled copy is executable even when the source isn't
+        os.chmod(self.exe_path, 0o644)
+        metaedit.edit(self.exe_path, {"ProductName": "Exec"}).apply_all(["macos"])
+        bundled = os.path.join(self.test_dir, "test_app.app", "Contents", "MacOS", "test_app.exe")
+        self.assertTrue(os.access(bundled, os.X_OK))
+        if sys.platform == "linux":
+            metaedit.edit(self.exe_path).set_executable().apply()
+            self.assertEqual(os.stat(self.exe_path).st_mode & 0o777, 0o755)
+
+    def test_default_icon(self):
+        metaedit.edit(self.exe_path, {"ProductName": "Placeholder"}, use_default_icon=True).apply()
+        if sys.platform == "linux":
+            with open(os.path.join(self.test_dir, "test_app.desktop")) as f:
+                self.assertIn("Icon=" + os.path.join(self.test_dir, "test_app.png"), f.read())
+            with open(os.path.join(self.test_dir, "test_app.png"), "rb") as f:
+                self.assertEqual(f.read(8), b"\x89PNG\r\n\x1a\n")
+        elif sys.platform == "darwin":
+            self.assertTrue(os.path.exists(os.path.join(self.test_dir, "test_app.app", "Contents", "Resources", "app.icns")))
+
+    def test_verify_after_write(self):
+        editor = metaedit.edit(self.exe_path, {"ProductName": "Verified", "version": "3.1.0.0"}).set_verify_after_write()
+        self.assertTrue(editor.apply().result.changed)
+
+        if sys.platform != "linux":
+            return
+        # A line break is written as the .desktop "\n" escape, which verification expects
+        metaedit.edit(self.exe_path, {"ProductName": "Two\nLines"}).set_verify_after_write().apply()
+
+    def test_validate_for(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).validate_for("android")
+
+        editor = metaedit.edit(self.exe_path, {"ProductName": "Cross", "CompanyName": "Acme", "version": "1.0"}) \
+            .set_plist_bool("LSUIElement", True).set_hidden()
+        windows = editor.validate_for("windows")
+        self.assertEqual(len(windows), 2)
+        self.assertTrue(any("Info.plist values" in w and "macos" in w for w in windows))
+        self.assertTrue(any("Hidden" in w for w in windows))
+
+        macos = editor.validate_for("MacOS")
+        self.assertTrue(any("'CompanyName'" in w for w in macos))
+        self.assertTrue(any("no icon set" in w for w in macos))
+        self.assertFalse(any("CFBundleVersion" in w for w in macos))
+
+        self.assertEqual(metaedit.edit(self.exe_path, {"ProductName": "Plain"}).validate_for("windows"), [])
+
+    def test_platform_icon(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_platform_icon("android", self.exe_path)
+        with self.assertRaises(metaedit.IconError):
+            metaedit.edit(self.exe_path).set_platform_icon("linux", os.path.join(self.test_dir, "missing.png"))
+
+        if sys.platform != "linux" or not HAS_PILLOW:
+            return
+        linux_icon = os.path.join(self.test_dir, "linux_icon.png")
+        Image.new('RGBA', (32, 32), color = (0, 0, 255, 255)).save(linux_icon)
+        metaedit.edit(self.exe_path).set_icon(self.icon_path).set_platform_icon("linux", linux_icon) \
+            .set_platform_icon("windows", self.icon_path).apply()
+        with open(os.path.join(self.test_dir, "test_app.desktop")) as f:
+            self.assertIn(f"Icon={linux_icon}\n", f.read())
+
+        # Without a Linux icon the generic one is used
+        metaedit.edit(self.exe_path).set_icon(self.icon_path).set_platform_icon("windows", linux_icon).apply()
+        with open(os.path.join(self.test_dir, "test_app.desktop")) as f:
+            self.assertIn(f"Icon={self.icon_path}\n", f.read())
+
+    def test_linux_desktop_location(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_desktop_filename("launcher.txt")
+
+        if sys.platform != "linux":
+            return
+
+        out_dir = os.path.join(self.test_dir, "share", "applications")
+        (
+            metaedit.edit(self.exe_path, {"ProductName": "Relocated"})
+            .set_desktop_filename("org.example.App.desktop")
+            .set_output_dir(out_dir)
+            .apply()
+        )
+        with open(os.path.join(out_dir, "org.example.App.desktop")) as f:
+            self.assertIn("Exec=" + os.path.realpath(self.exe_path) + "\n", f.read())
+
+    def test_linux_desktop_merge(self):
+        if sys.platform != "linux":
+            return
+
+        desktop_path = os.path.join(self.test_dir, "test_app.desktop")
+        with open(desktop_path, "w") as f:
+            f.write(
+                "# Maintained by hand\n"
+                "[Desktop Entry]\n"
+                "Type=Application\n"
+                "Name=Old Name\n"
+                "Exec=/opt/test_app/run.sh %U\n"
+                "Categories=Utility;\n"
+                "X-Vendor-Key=keep me\n"
+                "Actions=new-window;\n"
+                "\n"
+                "[Desktop Action new-window]\n"
+                "Name=New Window\n"
+                "Exec=/opt/test_app/run.sh --new-window\n"
+            )
+
+        metaedit.edit(self.exe_path, {"ProductName": "Merged", "version": "2.0"}).apply()
+        with open(desktop_path) as f:
+            content = f.read()
+        self.assertEqual(content,
+            "# Maintained by hand\n"
+            "[Desktop Entry]\n"
+            "Type=Application\n"
+            "Name=Merged\n"
+            "Exec=/opt/test_app/run.sh %U\n"
+            "Categories=Utility;\n"
+            "X-Vendor-Key=keep me\n"
+            "Actions=new-window;\n"
+            "Version=2.0\n"
+            "Terminal=false\n"
+            "\n"
+            "[Desktop Action new-window]\n"
+            "Name=New Window\n"
+            "Exec=/opt/test_app/run.sh --new-window\n"
+        )
+
+        # Merging again is a no-op
+        editor = metaedit.edit(self.exe_path, {"ProductName": "Merged", "version": "2.0"})
+        self.assertFalse(editor.apply(only_if_changed=True).result.changed)
+
+    def test_desktop_bools(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_desktop_bool("Hidden")
+        with self.assertRaises(TypeError):
+            metaedit.edit(self.exe_path).set_desktop_bool("NoDisplay", "yes")
+        if sys.platform != "linux":
+            return
+
+        metaedit.edit(self.exe_path).set_desktop_bool("dbusactivatable") \
+            .set_desktop_bool("SingleMainWindow").set_desktop_bool("PrefersNonDefaultGPU", False) \
+            .set_desktop_bool("Terminal").apply()
+        with open(os.path.join(self.test_dir, "test_app.desktop")) as f:
+            lines = f.read().splitlines()
+        self.assertIn("DBusActivatable=true", lines)
+        self.assertIn("SingleMainWindow=true", lines)
+        self.assertIn("PrefersNonDefaultGPU=false", lines)
+        self.assertEqual([line for line in lines if line.startswith("Terminal=")], ["Terminal=true"])
+
+    def test_linux_desktop_sanitized(self):
+        if sys.platform != "linux":
+            return
+
+        # A BOM and CRLF line endings, as left by a Windows editor
+        desktop_path = os.path.join(self.test_dir, "test_app.desktop")
+        with open(desktop_path, "wb") as f:
+            f.write(b"\xef\xbb\xbf[Desktop Entry]\r\nType=Application\r\nComment=keep\r\n")
+
+        editor = metaedit.edit(self.exe_path, {"ProductName": "Line one\r\nLine two\rthree"}).set_verify_after_write()
+        editor.apply()
+        with open(desktop_path, "rb") as f:
+            content = f.read()
+        self.assertFalse(content.startswith(b"\xef\xbb\xbf"))
+        self.assertNotIn(b"\r", content)
+        self.assertIn(b"Comment=keep\n", content)
+        self.assertIn(b"Name=Line one\\nLine two\\nthree\n", content)
+
+        # A literal backslash-n pair must not read back as a line break
+        metaedit.edit(self.exe_path, {"ProductName": "C:\\new\\app"}).set_verify_after_write().apply()
+        with open(desktop_path, "rb") as f:
+            self.assertIn(b"Name=C:\\\\new\\\\app\n", f.read())
+
+    def test_linux_show_in(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_only_show_in(["Gnome Shell"])
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_only_show_in(["GNOME"]).set_not_show_in(["KDE"])
+
+        metaedit.edit(self.exe_path).set_hidden().set_only_show_in(["GNOME", "X-Cosmic-Beta"]).apply()
+        if sys.platform == "linux":
+            with open(os.path.join(self.test_dir, "test_app.desktop")) as f:
+                content = f.read()
+            self.assertIn("Hidden=true\n", content)
+            self.assertIn("OnlyShowIn=GNOME;X-Cosmic-Beta;\n", content)
+
+    def test_linux_appdir(self):
+        if sys.platform != "linux" or not HAS_PILLOW:
+            return
+
+        appdir = os.path.join(self.test_dir, "Test.AppDir")
+        os.makedirs(appdir)
+        metaedit.edit(self.exe_path).set_icon(self.icon_path).set_appdir(appdir).apply()
+
+        with Image.open(os.path.join(appdir, ".DirIcon")) as img:
+            self.assertEqual(img.size, (256, 256))
+        self.assertTrue(os.path.islink(os.path.join(appdir, "test_app.png")))
+        self.assertTrue(os.path.exists(os.path.join(appdir, "test_app.png")))
+        with open(os.path.join(appdir, "test_app.desktop")) as f:
+            self.assertIn("Icon=test_app\n", f.read())
+
+    def test_electron_package(self):
+        app_dir = os.path.join(self.test_dir, "resources", "app")
+        os.makedirs(app_dir)
+        package_json = os.path.join(app_dir, "package.json")
+        with open(package_json, "w") as f:
+            f.write('{\n  "name": "demo",\n  "engines": {"version": "0.0.1"},\n  "version": "1.0.0"\n}\n')
+
+        with self.assertWarns(UserWarning):
+            metaedit.edit(self.exe_path).set_version("2.1.0.7").apply()
+
+        metaedit.edit(self.exe_path).set_version("2.1.0.7-beta").set_update_electron_package().apply()
+        with open(package_json) as f:
+            content = f.read()
+        self.assertIn('"version": "2.1.0-beta"\n', content)
+        self.assertIn('"engines": {"version": "0.0.1"}', content)
+
+    def test_icon_quality(self):
+        with self.assertRaises(metaedit.IconError):
+            metaedit.edit(self.exe_path).set_icon_quality("ultra")
+
+        if sys.platform != "linux" or not HAS_PILLOW:
+            return
+
+        big_icon = os.path.join(self.test_dir, "big.png")
+        Image.new('RGBA', (512, 512), color=(0, 128, 255, 255)).save(big_icon)
+        appdir = os.path.join(self.test_dir, "Fast.AppDir")
+        os.makedirs(appdir)
+        metaedit.edit(self.exe_path).set_icon(big_icon).set_appdir(appdir).set_icon_quality("fast").apply()
+
+        hicolor = os.path.join(appdir, "usr", "share", "icons", "hicolor")
+        self.assertFalse(os.path.exists(os.path.join(hicolor, "256x256")))
+        with Image.open(os.path.join(appdir, "test_app.png")) as img:
+            self.assertEqual(img.size, (128, 128))
+
+    def test_locale(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).set_locale("xx-YY")
+
+        metaedit.edit(self.exe_path, {"ProductName": "Testanwendung"}).set_locale("de_de").apply()
+        if sys.platform == "linux":
+            with open(os.path.join(self.test_dir, "test_app.desktop")) as f:
+                self.assertIn("Name[de_DE]=Testanwendung\n", f.read())
+
+    def test_quiet_mode(self):
+        # Rust prints straight to fd 1, so capture at the descriptor level
+        capture = tempfile.TemporaryFile()
+        sys.stdout.flush()
+        saved = os.dup(1)
+        os.dup2(capture.fileno(), 1)
+        metaedit.set_quiet()
+        try:
+            metaedit.update(self.exe_path, ProductName="Quiet")
+        finally:
+            metaedit.set_quiet(False)
+            os.dup2(saved, 1)
+            os.close(saved)
+
+        capture.seek(0)
+        self.assertEqual(capture.read(), b"")
+        capture.close()
+
+    def test_atomic_write_leaves_no_temp_files(self):
+        metaedit.update(self.exe_path, ProductName="Atomic")
+        leftovers = [f for f in os.listdir(self.test_dir) if f.endswith(".metaedit-tmp")]
+        self.assertEqual(leftovers, [])
+
+    def test_permission_denied_hint(self):
+        if sys.platform == "win32" or os.geteuid() == 0:
+            return
+
+        os.chmod(self.test_dir, 0o500)
+        try:
+            with self.assertRaisesRegex(metaedit.MetaEditError, "permission denied"):
+                metaedit.update(self.exe_path, ProductName="Locked")
+        finally:
+            os.chmod(self.test_dir, 0o700)
+
+    def test_oversized_icon_rejected(self):
+        bomb = io.BytesIO(png_header(100000, 100000))
+        with self.assertRaisesRegex(metaedit.IconError, "maximum size"):
+            metaedit.edit(self.exe_path).set_icon(bomb).apply()
+
+        with self.assertRaisesRegex(metaedit.IconError, "32x32"):
+            metaedit.edit(self.exe_path).set_icon(io.BytesIO(png_header(64, 64))).set_max_icon_dimension(32).apply()
+
+    def test_missing_icon_fails_fast(self):
+        missing = os.path.join(self.test_dir, "missing.png")
+        with self.assertRaises(metaedit.IconError):
+            metaedit.edit(self.exe_path).set_icon(missing)
+
+    def test_detect_target(self):
+        pe_path = os.path.join(self.test_dir, "tiny.exe")
+        build_minimal_pe(pe_path)
+        self.assertEqual(metaedit.detect_target(pe_path), "windows")
+
+        samples = {
+            "app.elf": b"\x7fELF\x02\x01\x01" + b"\x00" * 9,
+            "app.macho": struct.pack("<I", 0xFEEDFACF) + b"\x00" * 12,
+            "app.fat": struct.pack(">II", 0xCAFEBABE, 2) + b"\x00" * 8,
+            "App.class": struct.pack(">IHH", 0xCAFEBABE, 0, 52) + b"\x00" * 8,
+        }
+        expected = {"app.elf": "linux", "app.macho": "macos", "app.fat": "macos", "App.class": "unknown"}
+        for name, data in samples.items():
+            path = os.path.join(self.test_dir, name)
+            with open(path, "wb") as f:
+                f.write(data)
+            self.assertEqual(metaedit.detect_target(path), expected[name], name)
+
+        text_path = os.path.join(self.test_dir, "notes.txt")
+        with open(text_path, "w") as f:
+            f.write("not a binary")
+        self.assertEqual(metaedit.detect_target(text_path), "unknown")
+
+    def test_arm64_pe(self):
+        # ARM64EC images carry the AMD64 machine type, so ARM64 is the one new header to cover
+        arm64 = os.path.join(self.test_dir, "arm64.exe")
+        build_minimal_pe(arm64, pe32_plus=True, certificate=b"\x08\0\0\0\0\x02\x02\0", machine=IMAGE_FILE_MACHINE_ARM64)
+        self.assertEqual(metaedit.detect_target(arm64), "windows")
+        self.assertTrue(metaedit.edit(arm64).is_signed())
+
+        if sys.platform != "win32":
+            return
+
+        metaedit.update(arm64, CompanyName="Arm Corp", version="1.0.0.0")
+        editor = metaedit.edit(arm64)
+        self.assertEqual(editor.get_string("CompanyName"), "Arm Corp")
+        self.assertFalse(editor.is_signed())
+        with open(arm64, "rb") as f:
+            f.seek(0x44)
+            self.assertEqual(struct.unpack("<H", f.read(2))[0], IMAGE_FILE_MACHINE_ARM64)
+
+    def test_signed_policy(self):
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.set_signed_policy("ignore")
+
+        if sys.platform != "win32":
+            return
+
+        signed = os.path.join(self.test_dir, "signed.exe")
+        try:
+            metaedit.set_signed_policy("refuse")
+            build_minimal_pe(signed, certificate=b"\x08\0\0\0\0\x02\x02\0")
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.update(signed, CompanyName="Refused")
+
+            metaedit.set_signed_policy("keep")
+            with self.assertWarns(UserWarning):
+                editor = metaedit.update(signed, CompanyName="Kept")
+            self.assertTrue(editor.result.signature_invalid)
+            self.assertTrue(metaedit.edit(signed).is_signed())
+        finally:
+            metaedit.set_signed_policy("strip")
+
+        metaedit.update(signed, CompanyName="Stripped")
+        self.assertFalse(metaedit.edit(signed).is_signed())
+
+    def test_apply_all(self):
+        import plistlib
+        host = {"win32": "windows", "darwin": "macos"}.get(sys.platform, "linux")
+
+        with self.assertRaises(metaedit.MetaEditError):
+            metaedit.edit(self.exe_path).apply_all(["beos"])
+
+        release = os.path.join(self.test_dir, "release")
+        os.makedirs(release)
+        binaries = {"linux": os.path.join(release, "app"), "macos": os.path.join(release, "app-mac")}
+        with open(binaries["linux"], "wb") as f:
+            f.write(b"\x7fELF" + b"\0" * 60)
+        with open(binaries["macos"], "wb") as f:
+            f.write(struct.pack("<I", 0xFEEDFACF) + b"\0" * 60)
+        with open(os.path.join(release, "README.txt"), "w") as f:
+            f.write("not a binary")
+        if host == "windows":
+            binaries["windows"] = os.path.join(release, "app.exe")
+            shutil.copy(sys.executable, binaries["windows"])
+        else:
+            # Rewriting PE resources needs the Windows build; nothing is written first
+            with self.assertRaises(metaedit.MetaEditError):
+                metaedit.edit(release).apply_all(["linux", "windows"])
+            self.assertFalse(os.path.exists(binaries["linux"] + ".desktop"))
+
+        editor = metaedit.edit(release, {"ProductName": "All", "version": "1.2.0.0"}).set_plist_array("LSArchitecturePriority", ["arm64", "x86_64"])
+        outputs = editor.apply_all(sorted(binaries))
+        expected = {"macos": binaries["macos"] + ".app", "linux": binaries["linux"] + ".desktop"}
+        if host == "windows":
+            expected["windows"] = binaries["windows"]
+        self.assertEqual(outputs, expected)
+
+        # Every backend ran, whatever the host
+        contents = os.path.join(outputs["macos"], "Contents")
+        with open(os.path.join(contents, "Info.plist"), "rb") as f:
+            info = plistlib.load(f)
+        self.assertEqual(info["CFBundleExecutable"], "app-mac")
+        self.assertEqual(info["CFBundleName"], "All")
+        self.assertEqual(info["CFBundleVersion"], "1.2.0.0")
+        self.assertEqual(info["LSArchitecturePriority"], ["arm64", "x86_64"])
+        self.assertTrue(os.path.isfile(os.path.join(contents, "MacOS", "app-mac")))
+        with open(outputs["linux"]) as f:
+            desktop = f.read()
+        self.assertIn("Name=All\n", desktop)
+        self.assertIn("Version=1.2.0.0\n", desktop)
+
+        # An existing bundle keeps the executable its Info.plist names
+        bundle = os.path.join(self.test_dir, "Existing.app")
+        os.makedirs(os.path.join(bundle, "Contents", "MacOS"))
+        with open(os.path.join(bundle, "Contents", "Info.plist"), "wb") as f:
+            plistlib.dump({"CFBundleExecutable": "real-name"}, f)
+        metaedit.edit(bundle, {"ProductName": "Existing"}).apply_all(["macos"])
+        with open(os.path.join(bundle, "Contents", "Info.plist"), "rb") as f:
+            info = plistlib.load(f)
+        self.assertEqual((info["CFBundleExecutable"], info["CFBundleName"]), ("real-name", "Existing"))
+
+    def test_empty_file(self):
+        empty = os.path.join(self.test_dir, "empty.exe")
+        open(empty, "wb").close()
+        with self.assertRaisesRegex(metaedit.MetaEditError, "empty or too small"):
+            metaedit.update(empty, version="1.0")
+
+        # The minimum follows the file's own format, not the host's
+        for name, header, format in [("short.exe", b"MZ", "PE"), ("short", b"\x7fELF", "ELF")]:
+            path = os.path.join(self.test_dir, name)
+            with open(path, "wb") as f:
+                f.write(header + b"\0" * 16)
+            with self.assertRaisesRegex(metaedit.MetaEditError, "too small to be a valid " + format):
+                metaedit.update(path, version="1.0")
+
+    def test_strip_overlay(self):
+        pe_path = os.path.join(self.test_dir, "overlay.exe")
+        build_minimal_pe(pe_path)
+        image_size = os.path.getsize(pe_path)
+        with open(pe_path, "ab") as f:
+            f.write(b"PK\x03\x04" + b"\0" * 96)
+
+        editor = metaedit.MetadataEditor(pe_path)
+        self.assertEqual(editor.strip_overlay(), 100)
+        self.assertEqual(os.path.getsize(pe_path), image_size)
+        self.assertEqual(editor.strip_overlay(), 0)
+
+        # Data after a trailing certificate table goes; the certificate stays
+        build_minimal_pe(pe_path, certificate=b"\x08" * 64)
+        with open(pe_path, "ab") as f:
+            f.write(b"\xff" * 32)
+        self.assertEqual(editor.strip_overlay(), 32)
+        self.assertTrue(editor.is_signed())
+        self.assertEqual(os.path.getsize(pe_path), image_size + 64)
+
+        # An overlay in front of a referenced certificate cannot be cut without losing it
+        with open(pe_path, "r+b") as f:
+            f.seek(0x40 + 24 + 96 + 32)
+            f.write(struct.pack("<II", image_size + 16, 48))
+        with self.assertRaisesRegex(metaedit.MetaEditError, "remove_signature"):
+            editor.strip_overlay()
+        editor.remove_signature()
+        self.assertFalse(editor.is_signed())
+        self.assertEqual(editor.strip_overlay(), 16)
+        self.assertEqual(os.path.getsize(pe_path), image_size)
+
+    def test_append_overlay(self):
+        pe_path = os.path.join(self.test_dir, "payload.exe")
+        build_minimal_pe(pe_path)
+        editor = metaedit.MetadataEditor(pe_path)
+        self.assertEqual(editor.read_overlay(), b"")
+
+        editor.append_overlay(b"CONFIG1").append_overlay(b"CONFIG2")
+        self.assertEqual(editor.read_overlay(), b"CONFIG1CONFIG2")
+        self.assertEqual(editor.strip_overlay(), 14)
+
+        # Signed input follows the signed policy; by default the signature is stripped
+        build_minimal_pe(pe_path, certificate=b"\x08" * 64)
+        self.assertEqual(editor.read_overlay(), b"")
+        metaedit.set_signed_policy("refuse")
+        try:
+            with self.assertRaises(metaedit.MetaEditError):
+                editor.append_overlay(b"blob")
+        finally:
+            metaedit.set_signed_policy("strip")
+        editor.append_overlay(b"blob")
+        self.assertFalse(editor.is_signed())
+        self.assertEqual(editor.read_overlay(), b"blob")
+
+        # A set checksum is kept valid
+        with open(pe_path, "r+b") as f:
+            f.seek(0x40 + 24 + 64)
+            f.write(struct.pack("<I", 1))
+        editor.append_overlay(b"more")
+        if HAS_PEFILE:
+            pe = pefile.PE(pe_path)
+            self.assertEqual(pe.OPTIONAL_HEADER.CheckSum, pe.generate_checksum())
+
+    def test_is_checksum_valid(self):
+        pe_path = os.path.join(self.test_dir, "checksum.exe")
+        build_minimal_pe(pe_path)
+        editor = metaedit.MetadataEditor(pe_path)
+        # Unset checksum: nothing to contradict
+        self.assertTrue(editor.is_checksum_valid())
+
+        with open(pe_path, "r+b") as f:
+            f.seek(0x40 + 24 + 64)
+            f.write(struct.pack("<I", 1))
+        self.assertFalse(editor.is_checksum_valid())
+
+        # append_overlay recomputes a set checksum; a later byte edit invalidates it
+        editor.append_overlay(b"data")
+        self.assertTrue(editor.is_checksum_valid())
+        with open(pe_path, "r+b") as f:
+            f.seek(0x300)
+            f.write(b"\x90")
+        self.assertFalse(editor.is_checksum_valid())
+
+        if sys.platform != "win32":
+            return
+        # A regular edit keeps a set checksum in step with the rewritten file
+        with open(self.exe_path, "r+b") as f:
+            f.seek(0x3c)
+            e_lfanew = struct.unpack("<I", f.read(4))[0]
+            f.seek(e_lfanew + 24 + 64)
+            f.write(struct.pack("<I", 1))
+        editor = metaedit.edit(self.exe_path, {"ProductName": "Checked"})
+        editor.apply()
+        self.assertTrue(editor.is_checksum_valid())
+
+    def test_repair(self):
+        pe_path = os.path.join(self.test_dir, "damaged.exe")
+        backup = os.path.join(self.test_dir, "known-good.exe")
+        build_minimal_pe(backup)
+        build_minimal_pe(pe_path)
+        self.assertEqual(metaedit.repair(pe_path), [])
+
+        # A certificate entry past the end of the file and a stale checksum
+        security = 0x40 + 24 + 96 + 4 * 8
+        with open(pe_path, "r+b") as f:
+            f.seek(security)
+            f.write(struct.pack("<II", 0x10000, 0x100))
+            f.seek(0x40 + 24 + 64)
+            f.write(struct.pack("<I", 1))
+        self.assertEqual(len(metaedit.repair(pe_path, dry_run=True)), 2)
+        self.assertFalse(metaedit.edit(pe_path).is_checksum_valid())
+        self.assertEqual(len(metaedit.repair(pe_path)), 2)
+        editor = metaedit.edit(pe_path)
+        self.assertTrue(editor.is_checksum_valid())
+        self.assertFalse(editor.is_signed())
+        self.assertEqual(metaedit.repair(pe_path), [])
+
+        # A certificate table left behind after its entry was cleared
+        certificate = struct.pack("<IHH", 24, 0x0200, 0x0002) + b"\x30" * 16
+        build_minimal_pe(pe_path, certificate=certificate)
+        with open(pe_path, "r+b") as f:
+            f.seek(security)
+            f.write(b"\0" * 8)
+        self.assertEqual(len(metaedit.repair(pe_path)), 1)
+        self.assertEqual(os.path.getsize(pe_path), os.path.getsize(backup))
+        # Trailing data that isn't a certificate is a real overlay and stays
+        with open(pe_path, "ab") as f:
+            f.write(b"PK\x03\x04" + b"\0" * 12)
+        self.assertEqual(metaedit.repair(pe_path), [])
+
+        # Unreadable headers need a backup: an explicit one, or the .bak an in-place edit keeps
+        with open(pe_path, "wb") as f:
+            f.write(b"MZ" + b"\0" * 100)
+        with self.assertRaises(metaedit.PEParseError):
+            metaedit.repair(pe_path)
+        with self.assertRaises(metaedit.PEParseError):
+            metaedit.repair(backup, backup_path=pe_path)
+        self.assertEqual(len(metaedit.repair(pe_path, backup_path=backup)), 1)
+        with open(pe_path, "rb") as a, open(backup, "rb") as b:
+            self.assertEqual(a.read(), b.read())
+
+        metaedit.edit(pe_path).append_overlay(b"config")
+        with open(pe_path + ".bak", "rb") as a, open(backup, "rb") as b:
+            self.assertEqual(a.read(), b.read())
+        with open(pe_path, "wb") as f:
+            f.write(b"MZ" + b"\0" * 100)
+        self.assertEqual(len(metaedit.repair(pe_path)), 1)
+        self.assertEqual(metaedit.edit(pe_path).read_overlay(), b"")
+
+    def test_inspect(self):
+        pe_path = os.path.join(self.test_dir, "audit.exe")
+        build_minimal_pe(pe_path, certificate=b"\x08" * 64)
+        editor = metaedit.MetadataEditor(pe_path)
+        self.assertEqual(editor.inspect(), {
+            "format": "windows", "signed": "true", "checksum_valid": "true", "has_overlay": "false",
+        })
+
+        build_minimal_pe(pe_path)
+        with open(pe_path, "r+b") as f:
+            f.seek(0x40 + 24 + 64)
+            f.write(struct.pack("<I", 1))
+            f.seek(0, os.SEEK_END)
+            f.write(b"payload")
+        report = editor.inspect()
+        self.assertEqual((report["signed"], report["checksum_valid"], report["has_overlay"]), ("false", "false", "true"))
+
+        with open(pe_path, "wb") as f:
+            f.write(b"\x7fELF" + b"\0" * 60)
+        self.assertEqual(editor.inspect(), {"format": "linux"})
+
+    def test_inspect_dir(self):
+        root = os.path.join(self.test_dir, "release")
+        os.makedirs(os.path.join(root, "lib"))
+        build_minimal_pe(os.path.join(root, "app.exe"))
+        with open(os.path.join(root, "README.txt"), "w") as f:
+            f.write("not a binary")
+        with open(os.path.join(root, "lib", "helper"), "wb") as f:
+            f.write(b"\x7fELF" + b"\0" * 60)
+
+        reports = metaedit.inspect_dir(root)
+        self.assertEqual(sorted(os.path.relpath(p, root) for p in reports), ["app.exe", os.path.join("lib", "helper")])
+        self.assertEqual(reports[os.path.join(root, "app.exe")]["format"], "windows")
+        self.assertEqual(reports[os.path.join(root, "lib", "helper")], {"format": "linux"})
+        self.assertEqual(list(metaedit.inspect_dir(root, recursive=False)), [os.path.join(root, "app.exe")])
+
+        with self.assertRaises(FileNotFoundError):
+            metaedit.inspect_dir(os.path.join(self.test_dir, "missing"))
+
+        # An unreadable file warns and the rest of the walk still completes
+        if hasattr(os, "geteuid") and os.geteuid() != 0:
+            locked = os.path.join(root, "locked.exe")
+            build_minimal_pe(locked)
+            os.chmod(locked, 0)
+            try:
+                with self.assertWarns(UserWarning):
+                    reports = metaedit.inspect_dir(root)
+            finally:
+                os.chmod(locked, 0o644)
+            self.assertNotIn(locked, reports)
+            self.assertIn(os.path.join(root, "app.exe"), reports)
+
+    def test_is_signed(self):
+        unsigned = os.path.join(self.test_dir, "unsigned.exe")
+        signed = os.path.join(self.test_dir, "signed.exe")
+        build_minimal_pe(unsigned)
+        build_minimal_pe(signed, pe32_plus=True, certificate=b"\x08\0\0\0\0\x02\x02\0")
+
+        self.assertFalse(metaedit.edit(unsigned).is_signed())
+        self.assertTrue(metaedit.edit(signed).is_signed())
+
+        with open(signed, "rb") as f:
+            before = f.read()
+        metaedit.edit(signed).is_signed()
+        with open(signed, "rb") as f:
+            self.assertEqual(f.read(), before)
+
+    def test_icon_from_file_object(self):
+        with self.assertRaises(metaedit.IconError):
+            metaedit.edit(self.exe_path).set_icon(io.BytesIO(b"definitely not an image"))
+
+        if not HAS_PILLOW:
+            return
+        with open(self.icon_path, "rb") as f:
+            metaedit.edit(self.exe_path).set_icon(f).apply()
+
+        if sys.platform == "linux":
+            self.assertTrue(os.path.exists(os.path.join(self.test_dir, "test_app.png")))
+
     def test_error_handling(self):
         # Test non-existent file
         with self.assertRaises(FileNotFoundError):